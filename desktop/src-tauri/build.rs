@@ -1,3 +1,79 @@
+use std::{
+    env,
+    process::Command,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
 fn main() {
+    build_info();
     tauri_build::build()
 }
+
+/// Expose build metadata to `get_build_info`. CI can pin each value with the
+/// same-named env var; dev builds fall back to git and the current time.
+fn build_info() {
+    for var in [
+        "COMPANION_COMMIT",
+        "COMPANION_BUILD_DATE",
+        "COMPANION_CHANNEL",
+        "SOURCE_DATE_EPOCH",
+    ] {
+        println!("cargo:rerun-if-env-changed={var}");
+    }
+    // Moves on every commit and checkout, so the commit hash stays current
+    if let Some(log) = git(&["rev-parse", "--git-path", "logs/HEAD"]) {
+        println!("cargo:rerun-if-changed={log}");
+    }
+
+    let commit = env::var("COMPANION_COMMIT")
+        .ok()
+        .or_else(|| git(&["rev-parse", "--short", "HEAD"]))
+        .unwrap_or_else(|| "unknown".into());
+    let build_date = env::var("COMPANION_BUILD_DATE").unwrap_or_else(|_| {
+        let secs = env::var("SOURCE_DATE_EPOCH")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or_else(|| {
+                SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map(|d| d.as_secs())
+                    .unwrap_or_default()
+            });
+        iso_date(secs)
+    });
+    let channel = env::var("COMPANION_CHANNEL").unwrap_or_else(|_| "stable".into());
+    assert!(
+        matches!(channel.as_str(), "stable" | "beta" | "nightly"),
+        "COMPANION_CHANNEL must be stable, beta or nightly, not {channel:?}"
+    );
+    let target = env::var("TARGET").unwrap_or_default();
+
+    println!("cargo:rustc-env=COMPANION_COMMIT={commit}");
+    println!("cargo:rustc-env=COMPANION_BUILD_DATE={build_date}");
+    println!("cargo:rustc-env=COMPANION_CHANNEL={channel}");
+    println!("cargo:rustc-env=COMPANION_TARGET={target}");
+}
+
+fn git(args: &[&str]) -> Option<String> {
+    let output = Command::new("git").args(args).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let text = String::from_utf8(output.stdout).ok()?;
+    Some(text.trim().to_string()).filter(|s| !s.is_empty())
+}
+
+/// `YYYY-MM-DD` (UTC) for a Unix timestamp, without pulling in a date crate.
+fn iso_date(secs: u64) -> String {
+    // Howard Hinnant's civil_from_days
+    let z = (secs / 86_400) as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!("{year:04}-{month:02}-{day:02}")
+}
@@ -27,7 +27,16 @@ android {
     }
 }
 
+repositories {
+    maven("https://developer.huawei.com/repo/")
+}
+
 dependencies {
     implementation("com.google.firebase:firebase-messaging-ktx:24.1.1")
+    implementation("com.google.android.play:review-ktx:2.0.2")
+    implementation("org.unifiedpush.android:connector:2.4.0")
+    // Bundled by apps that enable the `hms` feature; see HmsPush
+    compileOnly("com.huawei.hms:push:6.12.0.300")
+    compileOnly("com.huawei.agconnect:agconnect-core:1.9.1.301")
     implementation(project(":tauri-android"))
 }
@@ -0,0 +1,84 @@
+package com.hexidecibel.companion.fcm
+
+import android.app.PendingIntent
+import android.content.BroadcastReceiver
+import android.content.Context
+import android.content.Intent
+import androidx.core.app.NotificationManagerCompat
+import androidx.core.app.RemoteInput
+import org.json.JSONArray
+import org.json.JSONObject
+
+private const val PREFS_NAME = "fcm_pending_actions"
+private const val KEY_PENDING = "pending"
+private const val EXTRA_ID = "id"
+private const val EXTRA_ACTION = "action"
+private const val EXTRA_TAG = "tag"
+private const val EXTRA_NOTIFICATION_ID = "notificationId"
+private const val EXTRA_DATA = "data"
+const val REPLY_KEY = "reply"
+
+/**
+ * Receives presses of the action buttons [LocalNotifications] adds. Unlike
+ * dismissals, actions are kept while the app isn't running (a typed reply
+ * shouldn't be lost) and reported by [FcmPlugin] once it loads.
+ */
+class ActionReceiver : BroadcastReceiver() {
+
+    override fun onReceive(context: Context, intent: Intent) {
+        val id = intent.getStringExtra(EXTRA_ID) ?: return
+        val payload = JSONObject()
+            .put("id", id)
+            .put("action", intent.getStringExtra(EXTRA_ACTION))
+            .put("data", JSONObject(intent.getStringExtra(EXTRA_DATA) ?: "{}"))
+        RemoteInput.getResultsFromIntent(intent)?.getCharSequence(REPLY_KEY)?.let {
+            payload.put("reply", it.toString())
+        }
+        // Android keeps a replied-to notification spinning until it is updated
+        val notificationId = intent.getIntExtra(EXTRA_NOTIFICATION_ID, id.hashCode())
+        NotificationManagerCompat.from(context).cancel(intent.getStringExtra(EXTRA_TAG), notificationId)
+
+        val plugin = FcmPlugin.instance
+        if (plugin != null) {
+            plugin.onNotificationAction(payload)
+        } else {
+            val prefs = context.getSharedPreferences(PREFS_NAME, Context.MODE_PRIVATE)
+            val pending = JSONArray(prefs.getString(KEY_PENDING, null) ?: "[]").put(payload)
+            prefs.edit().putString(KEY_PENDING, pending.toString()).apply()
+        }
+    }
+
+    companion object {
+        fun intent(
+            context: Context,
+            id: String,
+            notificationId: Int,
+            tag: String?,
+            action: String,
+            data: Map<String, String>
+        ): PendingIntent {
+            val intent = Intent(context, ActionReceiver::class.java)
+                .putExtra(EXTRA_ID, id)
+                .putExtra(EXTRA_NOTIFICATION_ID, notificationId)
+                .putExtra(EXTRA_ACTION, action)
+                .putExtra(EXTRA_TAG, tag)
+                .putExtra(EXTRA_DATA, JSONObject(data).toString())
+            // Mutable so RemoteInput can attach the reply text
+            return PendingIntent.getBroadcast(
+                context,
+                "$id/$action".hashCode(),
+                intent,
+                PendingIntent.FLAG_UPDATE_CURRENT or PendingIntent.FLAG_MUTABLE
+            )
+        }
+
+        /** Actions pressed while the app wasn't running, oldest first. */
+        fun takePending(context: Context): List<JSONObject> {
+            val prefs = context.getSharedPreferences(PREFS_NAME, Context.MODE_PRIVATE)
+            val saved = prefs.getString(KEY_PENDING, null) ?: return emptyList()
+            prefs.edit().remove(KEY_PENDING).apply()
+            val pending = JSONArray(saved)
+            return (0 until pending.length()).map { pending.getJSONObject(it) }
+        }
+    }
+}
@@ -0,0 +1,14 @@
+package com.hexidecibel.companion.fcm
+
+import android.content.BroadcastReceiver
+import android.content.Context
+import android.content.Intent
+
+/** Arms scheduled notifications again after a reboot, which clears all alarms. */
+class BootReceiver : BroadcastReceiver() {
+
+    override fun onReceive(context: Context, intent: Intent) {
+        if (intent.action != Intent.ACTION_BOOT_COMPLETED) return
+        ScheduledNotifications.rearmAll(context)
+    }
+}
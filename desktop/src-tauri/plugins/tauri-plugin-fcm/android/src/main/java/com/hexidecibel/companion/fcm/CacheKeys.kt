@@ -0,0 +1,84 @@
+package com.hexidecibel.companion.fcm
+
+import android.content.Context
+import android.security.keystore.KeyGenParameterSpec
+import android.security.keystore.KeyProperties
+import android.util.Base64
+import android.util.Log
+import java.security.GeneralSecurityException
+import java.security.KeyStore
+import java.security.SecureRandom
+import javax.crypto.Cipher
+import javax.crypto.KeyGenerator
+import javax.crypto.SecretKey
+import javax.crypto.spec.GCMParameterSpec
+
+private const val TAG = "FcmCacheKeys"
+private const val PREFS_NAME = "fcm_cache_keys"
+private const val KEYSTORE = "AndroidKeyStore"
+private const val TRANSFORMATION = "AES/GCM/NoPadding"
+private const val IV_LENGTH = 12
+private const val KEY_LENGTH = 32
+
+/**
+ * Keys for the Rust side's encrypted message cache. Each is a random 256-bit
+ * key kept in SharedPreferences, wrapped by a Keystore key that never leaves
+ * the device, so a copy of the app's files can't decrypt the cache.
+ */
+object CacheKeys {
+
+    /** The key for [source] as hex, creating it if [create]; null if missing. */
+    fun get(context: Context, source: String, create: Boolean): String? {
+        val prefs = context.getSharedPreferences(PREFS_NAME, Context.MODE_PRIVATE)
+        prefs.getString(source, null)?.let { wrapped ->
+            return try {
+                unwrap(source, Base64.decode(wrapped, Base64.NO_WRAP)).toHex()
+            } catch (e: GeneralSecurityException) {
+                // The Keystore key is gone, e.g. after a backup restore
+                Log.w(TAG, "Cannot unwrap cache key $source", e)
+                null
+            }
+        }
+        if (!create) return null
+        val key = ByteArray(KEY_LENGTH).also { SecureRandom().nextBytes(it) }
+        prefs.edit()
+            .putString(source, Base64.encodeToString(wrap(source, key), Base64.NO_WRAP))
+            .apply()
+        return key.toHex()
+    }
+
+    private fun wrappingKey(source: String): SecretKey {
+        val alias = "fcm_cache_$source"
+        val keyStore = KeyStore.getInstance(KEYSTORE).apply { load(null) }
+        (keyStore.getKey(alias, null) as? SecretKey)?.let { return it }
+        val generator = KeyGenerator.getInstance(KeyProperties.KEY_ALGORITHM_AES, KEYSTORE)
+        generator.init(
+            KeyGenParameterSpec.Builder(
+                alias,
+                KeyProperties.PURPOSE_ENCRYPT or KeyProperties.PURPOSE_DECRYPT,
+            )
+                .setBlockModes(KeyProperties.BLOCK_MODE_GCM)
+                .setEncryptionPaddings(KeyProperties.ENCRYPTION_PADDING_NONE)
+                .build()
+        )
+        return generator.generateKey()
+    }
+
+    private fun wrap(source: String, key: ByteArray): ByteArray {
+        val cipher = Cipher.getInstance(TRANSFORMATION)
+        cipher.init(Cipher.ENCRYPT_MODE, wrappingKey(source))
+        return cipher.iv + cipher.doFinal(key)
+    }
+
+    private fun unwrap(source: String, wrapped: ByteArray): ByteArray {
+        val cipher = Cipher.getInstance(TRANSFORMATION)
+        cipher.init(
+            Cipher.DECRYPT_MODE,
+            wrappingKey(source),
+            GCMParameterSpec(128, wrapped, 0, IV_LENGTH),
+        )
+        return cipher.doFinal(wrapped, IV_LENGTH, wrapped.size - IV_LENGTH)
+    }
+
+    private fun ByteArray.toHex(): String = joinToString("") { "%02x".format(it) }
+}
@@ -0,0 +1,33 @@
+package com.hexidecibel.companion.fcm
+
+import android.app.PendingIntent
+import android.content.BroadcastReceiver
+import android.content.Context
+import android.content.Intent
+
+private const val EXTRA_ID = "id"
+
+/**
+ * Receives the delete intent of notifications the app posts itself, so the
+ * notification history can mark them dismissed. Dismissals while the app
+ * isn't running have nowhere to go and are dropped.
+ */
+class DismissReceiver : BroadcastReceiver() {
+
+    override fun onReceive(context: Context, intent: Intent) {
+        val id = intent.getStringExtra(EXTRA_ID) ?: return
+        FcmPlugin.instance?.onNotificationDismissed(id)
+    }
+
+    companion object {
+        fun intent(context: Context, id: String): PendingIntent {
+            val intent = Intent(context, DismissReceiver::class.java).putExtra(EXTRA_ID, id)
+            return PendingIntent.getBroadcast(
+                context,
+                id.hashCode(),
+                intent,
+                PendingIntent.FLAG_UPDATE_CURRENT or PendingIntent.FLAG_IMMUTABLE
+            )
+        }
+    }
+}
@@ -1,20 +1,184 @@
 package com.hexidecibel.companion.fcm
 
 import android.Manifest
+import android.app.NotificationManager
+import android.content.ActivityNotFoundException
+import android.content.Context
+import android.content.Intent
 import android.content.pm.PackageManager
+import android.net.ConnectivityManager
+import android.net.NetworkCapabilities
+import android.net.Uri
 import android.os.Build
+import android.provider.Settings
 import android.util.Log
 import androidx.core.app.ActivityCompat
+import androidx.core.app.NotificationManagerCompat
 import androidx.core.content.ContextCompat
 import app.tauri.annotation.Command
+import app.tauri.annotation.InvokeArg
 import app.tauri.annotation.TauriPlugin
+import app.tauri.plugin.Channel
 import app.tauri.plugin.Invoke
 import app.tauri.plugin.JSObject
 import app.tauri.plugin.Plugin
+import com.google.android.play.core.review.ReviewManagerFactory
+import com.google.firebase.FirebaseApp
 import com.google.firebase.messaging.FirebaseMessaging
+import org.unifiedpush.android.connector.UnifiedPush
 
 private const val TAG = "FcmPlugin"
 private const val PERMISSION_REQUEST_CODE = 9877
+private const val PERMISSION_PREFS_NAME = "fcm_permission"
+private const val KEY_PERMISSION_REQUESTED = "notification_permission_requested"
+// Rejection codes; they match the Rust Error codes, which mobile.rs maps them to
+private const val ERROR_PERMISSION_DENIED = "PERMISSION_DENIED"
+private const val ERROR_TOKEN = "TOKEN_ERROR"
+
+@InvokeArg
+class TopicArgs {
+    lateinit var topic: String
+}
+
+@InvokeArg
+class AppearanceArgs {
+    var smallIcon: String? = null
+    lateinit var accentColor: String
+}
+
+@InvokeArg
+class ProgressArgs {
+    lateinit var id: String
+    lateinit var title: String
+    var progress: Int = 0
+    var indeterminate: Boolean = false
+}
+
+@InvokeArg
+class CompleteProgressArgs {
+    lateinit var id: String
+    lateinit var title: String
+    var message: String? = null
+}
+
+@InvokeArg
+class VibrationArgs {
+    var pattern: LongArray = LongArray(0)
+}
+
+@InvokeArg
+class GroupKeyFieldArgs {
+    lateinit var field: String
+}
+
+@InvokeArg
+class InterruptionLevelArgs {
+    lateinit var level: String
+}
+
+@InvokeArg
+class SessionArgs {
+    lateinit var sessionId: String
+}
+
+@InvokeArg
+class AutoInitArgs {
+    var enabled: Boolean = true
+}
+
+@InvokeArg
+class ShowNotificationArgs {
+    lateinit var id: String
+    lateinit var title: String
+    lateinit var body: String
+    var data: Map<String, String> = emptyMap()
+    var groupKey: String? = null
+    var interruptionLevel: String? = null
+}
+
+@InvokeArg
+class ScheduleNotificationArgs {
+    lateinit var id: String
+    var at: Long = 0
+    lateinit var title: String
+    lateinit var body: String
+    var data: Map<String, String> = emptyMap()
+}
+
+@InvokeArg
+class ScheduledIdArgs {
+    lateinit var id: String
+}
+
+@InvokeArg
+class ForegroundPresentationArgs {
+    var alert: Boolean = false
+    var sound: Boolean = false
+}
+
+@InvokeArg
+class CreateChannelArgs {
+    lateinit var id: String
+    lateinit var name: String
+    var importance: String = "default"
+    var sound: String? = null
+}
+
+@InvokeArg
+class ChannelIdArgs {
+    lateinit var id: String
+}
+
+@InvokeArg
+class BadgeArgs {
+    var count: Int = 0
+}
+
+@InvokeArg
+class AppearanceDefaultsArgs {
+    var defaultChannel: String? = null
+    var smallIcon: String? = null
+    var accentColor: String? = null
+}
+
+@InvokeArg
+class ImageLimitsArgs {
+    var maxBytes: Long = 0
+    var timeoutMs: Long = 0
+}
+
+@InvokeArg
+class DedupTtlArgs {
+    var ttlMs: Long = 0
+}
+
+@InvokeArg
+class PushDistributorArgs {
+    lateinit var distributor: String
+}
+
+@InvokeArg
+class QuietHoursWindow {
+    lateinit var start: String
+    lateinit var end: String
+    var days: List<Int> = emptyList()
+}
+
+@InvokeArg
+class QuietHoursArgs {
+    var quietHours: QuietHoursWindow? = null
+}
+
+@InvokeArg
+class CacheKeyArgs {
+    lateinit var source: String
+    var create: Boolean = false
+}
+
+@InvokeArg
+class SetEventHandlerArgs {
+    lateinit var handler: Channel
+}
 
 @TauriPlugin
 class FcmPlugin(private val activity: android.app.Activity) : Plugin(activity) {
@@ -23,16 +187,173 @@ class FcmPlugin(private val activity: android.app.Activity) : Plugin(activity) {
         @Volatile
         var instance: FcmPlugin? = null
             private set
+
+        // Kept static so the Rust-side handler survives plugin re-instantiation
+        private var eventHandler: Channel? = null
+        private val pendingEvents = mutableListOf<JSObject>()
+
+        /** A received message in the shape the Rust side's messageReceived expects. */
+        fun messagePayload(
+            messageId: String?,
+            title: String?,
+            body: String?,
+            data: Map<String, String>,
+            sentTime: Long,
+            collapseKey: String? = null
+        ): JSObject {
+            val obj = JSObject()
+            messageId?.let { obj.put("messageId", it) }
+            collapseKey?.let { obj.put("collapseKey", it) }
+            obj.put("title", title ?: "")
+            obj.put("body", body ?: "")
+            // 0 when the sender's timestamp is unknown
+            if (sentTime > 0) obj.put("sentTime", sentTime)
+            val dataObj = JSObject()
+            for ((key, value) in data) {
+                dataObj.put(key, value)
+            }
+            obj.put("data", dataObj)
+            return obj
+        }
     }
 
     override fun load(webView: android.webkit.WebView) {
         super.load(webView)
         instance = this
         Log.d(TAG, "FCM plugin loaded")
+        // A tap that cold-started the app arrives as the launch intent, not
+        // through onNewIntent; the event is buffered until Rust listens
+        activity.intent?.let { onNotificationIntent(it) }
+        ActionReceiver.takePending(activity).forEach { onNotificationAction(it) }
+        SilentMessages.take(activity)?.let { queued ->
+            val payload = JSObject()
+            payload.put("messages", queued)
+            sendEvent("backgroundMessages", payload)
+        }
+    }
+
+    override fun onPause() {
+        super.onPause()
+        sendEvent("paused", JSObject())
+    }
+
+    override fun onResume() {
+        super.onResume()
+        sendEvent("resumed", JSObject())
+    }
+
+    override fun onNewIntent(intent: Intent) {
+        super.onNewIntent(intent)
+        onNotificationIntent(intent)
+    }
+
+    private fun onNotificationIntent(intent: Intent) {
+        val data = notificationData(intent) ?: return
+        val payload = JSObject()
+        payload.put("id", intent.getStringExtra("google.message_id"))
+        payload.put("data", data)
+        sendEvent("notificationOpened", payload)
+        // The activity keeps its launch intent, so don't report the tap again
+        // if the webview and this plugin are recreated
+        intent.removeExtra("google.message_id")
+    }
+
+    /**
+     * Extracts the data payload from an intent launched by tapping an FCM
+     * notification, or null if the intent didn't come from one. Firebase
+     * copies the message data into the extras next to its own bookkeeping keys.
+     */
+    private fun notificationData(intent: Intent): JSObject? {
+        val extras = intent.extras ?: return null
+        if (!extras.containsKey("google.message_id")) return null
+        val data = JSObject()
+        for (key in extras.keySet()) {
+            if (key.startsWith("google.") || key.startsWith("gcm.") ||
+                key == "from" || key == "collapse_key") continue
+            extras.getString(key)?.let { data.put(key, it) }
+        }
+        return data
+    }
+
+    /**
+     * Registers the channel used to forward native events to Rust.
+     * Events sent before a handler exists are buffered and flushed here.
+     */
+    @Command
+    fun setEventHandler(invoke: Invoke) {
+        val args = invoke.parseArgs(SetEventHandlerArgs::class.java)
+        val pending = synchronized(pendingEvents) {
+            eventHandler = args.handler
+            pendingEvents.toList().also { pendingEvents.clear() }
+        }
+        pending.forEach { args.handler.send(it) }
+        invoke.resolve()
+    }
+
+    private fun sendEvent(event: String, payload: JSObject) {
+        val obj = JSObject()
+        obj.put("event", event)
+        obj.put("payload", payload)
+        val handler = synchronized(pendingEvents) {
+            eventHandler ?: run {
+                pendingEvents.add(obj)
+                null
+            }
+        }
+        handler?.send(obj)
+    }
+
+    /** Resolves immediately; used to measure bridge round-trip latency. */
+    @Command
+    fun ping(invoke: Invoke) {
+        invoke.resolve()
+    }
+
+    /**
+     * Whether any of our active notifications is in a group, including the
+     * bundle the system forms on its own once an app posts several.
+     */
+    @Command
+    fun notificationsGrouped(invoke: Invoke) {
+        val manager = activity.getSystemService(Context.NOTIFICATION_SERVICE) as NotificationManager
+        val ret = JSObject()
+        ret.put("grouped", manager.activeNotifications.any { it.isGroup })
+        invoke.resolve(ret)
+    }
+
+    @Command
+    fun clearNotificationsForSession(invoke: Invoke) {
+        val args = invoke.parseArgs(SessionArgs::class.java)
+        val ret = JSObject()
+        ret.put("cleared", LocalNotifications.clearForSession(activity, args.sessionId))
+        invoke.resolve(ret)
+    }
+
+    @Command
+    fun clearDeliveredNotifications(invoke: Invoke) {
+        NotificationManagerCompat.from(activity).cancelAll()
+        invoke.resolve()
     }
 
     @Command
     fun getToken(invoke: Invoke) {
+        if (PushDistributor.isUnifiedPush(activity)) {
+            val endpoint = PushDistributor.endpoint(activity)
+            // Registration was dropped, e.g. by deleteToken; the endpoint follows as a tokenRefresh
+            if (endpoint == null) UnifiedPush.registerApp(activity)
+            val ret = JSObject()
+            ret.put("token", endpoint ?: org.json.JSONObject.NULL)
+            invoke.resolve(ret)
+            return
+        }
+        if (PushDistributor.isHms(activity)) {
+            Thread {
+                val ret = JSObject()
+                ret.put("token", HmsPush.token(activity) ?: org.json.JSONObject.NULL)
+                invoke.resolve(ret)
+            }.start()
+            return
+        }
         FirebaseMessaging.getInstance().token
             .addOnSuccessListener { token ->
                 Log.d(TAG, "FCM token: ${token.take(20)}...")
@@ -41,43 +362,137 @@ class FcmPlugin(private val activity: android.app.Activity) : Plugin(activity) {
                 invoke.resolve(ret)
             }
             .addOnFailureListener { e ->
+                // e.g. Play Services missing or out of date
                 Log.e(TAG, "Failed to get FCM token", e)
-                val ret = JSObject()
-                ret.put("token", org.json.JSONObject.NULL)
-                invoke.resolve(ret)
+                invoke.reject(e.message ?: "Failed to get FCM token", ERROR_TOKEN)
             }
     }
 
     @Command
-    fun requestPermission(invoke: Invoke) {
-        if (Build.VERSION.SDK_INT >= Build.VERSION_CODES.TIRAMISU) {
-            val granted = ContextCompat.checkSelfPermission(
-                activity,
-                Manifest.permission.POST_NOTIFICATIONS
-            ) == PackageManager.PERMISSION_GRANTED
+    fun getCacheKey(invoke: Invoke) {
+        val args = invoke.parseArgs(CacheKeyArgs::class.java)
+        val key = try {
+            CacheKeys.get(activity, args.source, args.create)
+        } catch (e: java.security.GeneralSecurityException) {
+            Log.e(TAG, "Failed to create cache key ${args.source}", e)
+            invoke.reject(e.message ?: "Failed to create cache key")
+            return
+        }
+        val ret = JSObject()
+        ret.put("key", key ?: org.json.JSONObject.NULL)
+        invoke.resolve(ret)
+    }
 
-            if (granted) {
-                val ret = JSObject()
-                ret.put("granted", true)
-                invoke.resolve(ret)
-            } else {
-                ActivityCompat.requestPermissions(
-                    activity,
-                    arrayOf(Manifest.permission.POST_NOTIFICATIONS),
-                    PERMISSION_REQUEST_CODE
-                )
-                // For simplicity, resolve immediately — the permission dialog is async.
-                // The web client should call isPermissionGranted after a delay to verify.
-                val ret = JSObject()
-                ret.put("granted", false)
-                invoke.resolve(ret)
+    /** Sender and project of the default Firebase app, which FCM tokens belong to. */
+    @Command
+    fun getSenderId(invoke: Invoke) {
+        val options = FirebaseApp.getInstance().options
+        val ret = JSObject()
+        ret.put("senderId", options.gcmSenderId ?: org.json.JSONObject.NULL)
+        ret.put("projectId", options.projectId ?: org.json.JSONObject.NULL)
+        invoke.resolve(ret)
+    }
+
+    /**
+     * Firebase persists this itself, so it also governs the next launch, before
+     * any app code runs. getToken still generates a token while disabled.
+     */
+    @Command
+    fun setAutoInitEnabled(invoke: Invoke) {
+        val args = invoke.parseArgs(AutoInitArgs::class.java)
+        FirebaseMessaging.getInstance().isAutoInitEnabled = args.enabled
+        invoke.resolve()
+    }
+
+    @Command
+    fun deleteToken(invoke: Invoke) {
+        if (PushDistributor.isUnifiedPush(activity)) {
+            UnifiedPush.unregisterApp(activity)
+            PushDistributor.saveEndpoint(activity, null)
+            invoke.resolve()
+            return
+        }
+        if (PushDistributor.isHms(activity)) {
+            Thread {
+                if (HmsPush.deleteToken(activity)) {
+                    invoke.resolve()
+                } else {
+                    invoke.reject("Failed to delete HMS token")
+                }
+            }.start()
+            return
+        }
+        FirebaseMessaging.getInstance().deleteToken()
+            .addOnSuccessListener { invoke.resolve() }
+            .addOnFailureListener { e ->
+                Log.e(TAG, "Failed to delete FCM token", e)
+                invoke.reject(e.message ?: "Failed to delete FCM token")
             }
-        } else {
+    }
+
+    /**
+     * "granted", "blocked" or "prompt". Android offers no direct "don't ask
+     * again" check: after a denial the rationale flag is set, and it is cleared
+     * again once the user blocks the prompt, which is indistinguishable from
+     * never having asked. So a request made earlier is remembered here.
+     */
+    private fun permissionStatus(): String {
+        if (Build.VERSION.SDK_INT < Build.VERSION_CODES.TIRAMISU) {
             // Pre-Android 13: notifications are allowed by default
-            val ret = JSObject()
-            ret.put("granted", true)
-            invoke.resolve(ret)
+            return "granted"
+        }
+        val granted = ContextCompat.checkSelfPermission(
+            activity,
+            Manifest.permission.POST_NOTIFICATIONS
+        ) == PackageManager.PERMISSION_GRANTED
+        if (granted) return "granted"
+
+        val requested = activity.getSharedPreferences(PERMISSION_PREFS_NAME, Context.MODE_PRIVATE)
+            .getBoolean(KEY_PERMISSION_REQUESTED, false)
+        val rationale = ActivityCompat.shouldShowRequestPermissionRationale(
+            activity,
+            Manifest.permission.POST_NOTIFICATIONS
+        )
+        return if (requested && !rationale) "blocked" else "prompt"
+    }
+
+    /**
+     * Rejects [invoke] with [ERROR_PERMISSION_DENIED] if notifications can't
+     * be posted; returns whether it did.
+     */
+    private fun rejectUnlessPermitted(invoke: Invoke): Boolean {
+        if (permissionStatus() == "granted") return false
+        invoke.reject("Notification permission denied", ERROR_PERMISSION_DENIED)
+        return true
+    }
+
+    @Command
+    fun requestPermission(invoke: Invoke) {
+        var status = permissionStatus()
+        if (status == "prompt") {
+            activity.getSharedPreferences(PERMISSION_PREFS_NAME, Context.MODE_PRIVATE)
+                .edit().putBoolean(KEY_PERMISSION_REQUESTED, true).apply()
+            ActivityCompat.requestPermissions(
+                activity,
+                arrayOf(Manifest.permission.POST_NOTIFICATIONS),
+                PERMISSION_REQUEST_CODE
+            )
+            // For simplicity, resolve immediately — the permission dialog is async.
+            // The web client should call isPermissionGranted after a delay to verify.
+            status = "requested"
         }
+        // A blocked request would no-op instantly, so it isn't made
+        val ret = JSObject()
+        ret.put("granted", status == "granted")
+        ret.put("status", status)
+        invoke.resolve(ret)
+    }
+
+    @Command
+    fun canRequestPermission(invoke: Invoke) {
+        val ret = JSObject()
+        ret.put("canRequest", permissionStatus() == "prompt")
+        invoke.resolve(ret)
     }
 
     @Command
@@ -95,6 +510,340 @@ class FcmPlugin(private val activity: android.app.Activity) : Plugin(activity) {
         invoke.resolve(ret)
     }
 
+    /** The app's notification settings page, or its app info page before Android 8. */
+    @Command
+    fun openNotificationSettings(invoke: Invoke) {
+        val intent = if (Build.VERSION.SDK_INT >= Build.VERSION_CODES.O) {
+            Intent(Settings.ACTION_APP_NOTIFICATION_SETTINGS)
+                .putExtra(Settings.EXTRA_APP_PACKAGE, activity.packageName)
+        } else {
+            Intent(Settings.ACTION_APPLICATION_DETAILS_SETTINGS)
+                .setData(Uri.fromParts("package", activity.packageName, null))
+        }
+        try {
+            activity.startActivity(intent)
+            invoke.resolve()
+        } catch (e: ActivityNotFoundException) {
+            Log.e(TAG, "No notification settings screen", e)
+            invoke.reject("No notification settings screen")
+        }
+    }
+
+    @Command
+    fun getConnectivity(invoke: Invoke) {
+        val manager = activity.getSystemService(Context.CONNECTIVITY_SERVICE) as ConnectivityManager
+        val capabilities = manager.getNetworkCapabilities(manager.activeNetwork)
+        val ret = JSObject()
+        ret.put(
+            "online",
+            capabilities?.hasCapability(NetworkCapabilities.NET_CAPABILITY_VALIDATED) == true
+        )
+        invoke.resolve(ret)
+    }
+
+    @Command
+    fun requestInAppReview(invoke: Invoke) {
+        val manager = ReviewManagerFactory.create(activity)
+        manager.requestReviewFlow().addOnCompleteListener { request ->
+            if (!request.isSuccessful) {
+                Log.w(TAG, "In-app review unavailable", request.exception)
+                val ret = JSObject()
+                ret.put("dispatched", false)
+                invoke.resolve(ret)
+                return@addOnCompleteListener
+            }
+            // Completion says nothing about whether the user reviewed (by design)
+            manager.launchReviewFlow(activity, request.result).addOnCompleteListener {
+                val ret = JSObject()
+                ret.put("dispatched", true)
+                invoke.resolve(ret)
+            }
+        }
+    }
+
+    @Command
+    fun configureNotificationAppearance(invoke: Invoke) {
+        val args = invoke.parseArgs(AppearanceArgs::class.java)
+        if (NotificationAppearance.save(activity, args.smallIcon, args.accentColor)) {
+            invoke.resolve()
+        } else {
+            invoke.reject("Unknown drawable resource: ${args.smallIcon}")
+        }
+    }
+
+    @Command
+    fun getNotificationChannelInfo(invoke: Invoke) {
+        val ret = JSObject()
+        if (Build.VERSION.SDK_INT >= Build.VERSION_CODES.O) {
+            val channelId = NotificationAppearance.defaultChannelId(activity)
+            val manager = activity.getSystemService(Context.NOTIFICATION_SERVICE) as NotificationManager
+            ret.put("defaultChannel", channelId)
+            // Not created until the first notification; it will get the default sound
+            val sound = manager.getNotificationChannel(channelId)?.sound?.toString()
+            ret.put("sound", sound ?: org.json.JSONObject.NULL)
+        } else {
+            ret.put("defaultChannel", org.json.JSONObject.NULL)
+            ret.put("sound", org.json.JSONObject.NULL)
+        }
+        invoke.resolve(ret)
+    }
+
+    @Command
+    fun createNotificationChannel(invoke: Invoke) {
+        val args = invoke.parseArgs(CreateChannelArgs::class.java)
+        if (LocalNotifications.createChannel(activity, args.id, args.name, args.importance, args.sound)) {
+            invoke.resolve()
+        } else {
+            invoke.reject("No sound named ${args.sound}")
+        }
+    }
+
+    @Command
+    fun getAvailableSounds(invoke: Invoke) {
+        val ret = JSObject()
+        ret.put("sounds", org.json.JSONArray(LocalNotifications.availableSounds(activity)))
+        invoke.resolve(ret)
+    }
+
+    @Command
+    fun deleteNotificationChannel(invoke: Invoke) {
+        val args = invoke.parseArgs(ChannelIdArgs::class.java)
+        val ret = JSObject()
+        ret.put("deleted", LocalNotifications.deleteChannel(activity, args.id))
+        invoke.resolve(ret)
+    }
+
+    @Command
+    fun showNotification(invoke: Invoke) {
+        val args = invoke.parseArgs(ShowNotificationArgs::class.java)
+        LocalNotifications.show(
+            activity,
+            args.id,
+            args.title,
+            args.body,
+            args.data,
+            args.groupKey,
+            args.interruptionLevel
+        )
+        invoke.resolve()
+    }
+
+    @Command
+    fun scheduleNotification(invoke: Invoke) {
+        if (rejectUnlessPermitted(invoke)) return
+        val args = invoke.parseArgs(ScheduleNotificationArgs::class.java)
+        ScheduledNotifications.schedule(activity, args.id, args.at, args.title, args.body, args.data)
+        invoke.resolve()
+    }
+
+    @Command
+    fun cancelScheduledNotification(invoke: Invoke) {
+        val args = invoke.parseArgs(ScheduledIdArgs::class.java)
+        val ret = JSObject()
+        ret.put("cancelled", ScheduledNotifications.cancel(activity, args.id))
+        invoke.resolve(ret)
+    }
+
+    @Command
+    fun getScheduledCount(invoke: Invoke) {
+        val ret = JSObject()
+        ret.put("count", ScheduledNotifications.count(activity))
+        invoke.resolve(ret)
+    }
+
+    @Command
+    fun showProgressNotification(invoke: Invoke) {
+        if (rejectUnlessPermitted(invoke)) return
+        val args = invoke.parseArgs(ProgressArgs::class.java)
+        LocalNotifications.showProgress(activity, args.id, args.title, args.progress, args.indeterminate)
+        invoke.resolve()
+    }
+
+    @Command
+    fun completeProgressNotification(invoke: Invoke) {
+        val args = invoke.parseArgs(CompleteProgressArgs::class.java)
+        LocalNotifications.completeProgress(activity, args.id, args.title, args.message)
+        invoke.resolve()
+    }
+
+    @Command
+    fun setVibrationPattern(invoke: Invoke) {
+        val args = invoke.parseArgs(VibrationArgs::class.java)
+        NotificationAppearance.saveVibrationPattern(activity, args.pattern)
+        invoke.resolve()
+    }
+
+    @Command
+    fun setGroupKeyField(invoke: Invoke) {
+        val args = invoke.parseArgs(GroupKeyFieldArgs::class.java)
+        NotificationAppearance.saveGroupKeyField(activity, args.field)
+        invoke.resolve()
+    }
+
+    @Command
+    fun setInterruptionLevel(invoke: Invoke) {
+        val args = invoke.parseArgs(InterruptionLevelArgs::class.java)
+        NotificationAppearance.saveInterruptionLevel(activity, args.level)
+        invoke.resolve()
+    }
+
+    @Command
+    fun setForegroundPresentation(invoke: Invoke) {
+        val args = invoke.parseArgs(ForegroundPresentationArgs::class.java)
+        NotificationAppearance.saveForegroundPresentation(activity, args.alert, args.sound)
+        invoke.resolve()
+    }
+
+    /** Saved for [LocalNotifications] to set as each notification's number. */
+    @Command
+    fun setBadgeCount(invoke: Invoke) {
+        val args = invoke.parseArgs(BadgeArgs::class.java)
+        NotificationAppearance.saveBadgeCount(activity, args.count)
+        invoke.resolve()
+    }
+
+    @Command
+    fun setImageLimits(invoke: Invoke) {
+        val args = invoke.parseArgs(ImageLimitsArgs::class.java)
+        NotificationAppearance.saveImageLimits(activity, args.maxBytes, args.timeoutMs)
+        invoke.resolve()
+    }
+
+    @Command
+    fun setAppearanceDefaults(invoke: Invoke) {
+        val args = invoke.parseArgs(AppearanceDefaultsArgs::class.java)
+        if (NotificationAppearance.saveDefaults(activity, args.defaultChannel, args.smallIcon, args.accentColor)) {
+            invoke.resolve()
+        } else {
+            invoke.reject("Unknown drawable resource: ${args.smallIcon}")
+        }
+    }
+
+    @Command
+    fun setDedupTtl(invoke: Invoke) {
+        val args = invoke.parseArgs(DedupTtlArgs::class.java)
+        RecentMessages.saveTtl(activity, args.ttlMs)
+        invoke.resolve()
+    }
+
+    @Command
+    fun getPushDistributor(invoke: Invoke) {
+        val ret = JSObject()
+        ret.put("distributor", PushDistributor.selected(activity))
+        invoke.resolve(ret)
+    }
+
+    @Command
+    fun setPushDistributor(invoke: Invoke) {
+        val args = invoke.parseArgs(PushDistributorArgs::class.java)
+        when (args.distributor) {
+            DISTRIBUTOR_UNIFIED_PUSH -> {
+                if (!PushDistributor.useUnifiedPush(activity)) {
+                    invoke.reject("No UnifiedPush distributor installed")
+                    return
+                }
+            }
+            DISTRIBUTOR_HMS -> {
+                if (!PushDistributor.useHms(activity)) {
+                    invoke.reject("HMS Push Kit is not available")
+                    return
+                }
+                refreshHmsToken()
+            }
+            DISTRIBUTOR_FCM -> {
+                PushDistributor.useFcm(activity)
+                FirebaseMessaging.getInstance().token
+                    .addOnSuccessListener { onTokenRefresh(it) }
+                    .addOnFailureListener { e -> Log.e(TAG, "Failed to get FCM token", e) }
+            }
+            else -> {
+                invoke.reject("Unknown distributor: ${args.distributor}")
+                return
+            }
+        }
+        invoke.resolve()
+    }
+
+    /**
+     * Switch to HMS on a device that has HMS Core but no Google Play
+     * services, unless a distributor other than FCM was picked.
+     */
+    @Command
+    fun fallBackToHms(invoke: Invoke) {
+        val switched = PushDistributor.isFcm(activity) &&
+            !HmsPush.googleServicesAvailable(activity) &&
+            PushDistributor.useHms(activity)
+        if (switched) refreshHmsToken()
+        val ret = JSObject()
+        ret.put("switched", switched)
+        invoke.resolve(ret)
+    }
+
+    private fun refreshHmsToken() {
+        Thread { HmsPush.token(activity)?.let { onTokenRefresh(it) } }.start()
+    }
+
+    /** Rust validates the window before handing it over. */
+    @Command
+    fun setQuietHours(invoke: Invoke) {
+        val window = invoke.parseArgs(QuietHoursArgs::class.java).quietHours
+        if (window == null) {
+            QuietHours.clear(activity)
+        } else {
+            QuietHours.save(activity, window.start, window.end, window.days)
+        }
+        invoke.resolve()
+    }
+
+    @Command
+    fun takeDeferredNotifications(invoke: Invoke) {
+        val ret = JSObject()
+        ret.put("notifications", QuietHours.takeDeferred(activity))
+        invoke.resolve(ret)
+    }
+
+    @Command
+    fun subscribeToTopic(invoke: Invoke) {
+        val args = invoke.parseArgs(TopicArgs::class.java)
+        FirebaseMessaging.getInstance().subscribeToTopic(args.topic)
+            .addOnSuccessListener { invoke.resolve() }
+            .addOnFailureListener { e ->
+                Log.e(TAG, "Failed to subscribe to ${args.topic}", e)
+                invoke.reject(e.message ?: "Failed to subscribe to ${args.topic}")
+            }
+    }
+
+    @Command
+    fun unsubscribeFromTopic(invoke: Invoke) {
+        val args = invoke.parseArgs(TopicArgs::class.java)
+        FirebaseMessaging.getInstance().unsubscribeFromTopic(args.topic)
+            .addOnSuccessListener { invoke.resolve() }
+            .addOnFailureListener { e ->
+                Log.e(TAG, "Failed to unsubscribe from ${args.topic}", e)
+                invoke.reject(e.message ?: "Failed to unsubscribe from ${args.topic}")
+            }
+    }
+
+    /** Called by [DismissReceiver] when a notification the app posted is swiped away. */
+    fun onNotificationDismissed(id: String) {
+        val payload = JSObject()
+        payload.put("id", id)
+        sendEvent("notificationDismissed", payload)
+    }
+
+    /** Called by [ActionReceiver] when a notification's action button is pressed. */
+    fun onNotificationAction(payload: org.json.JSONObject) {
+        sendEvent("notificationAction", JSObject(payload.toString()))
+    }
+
+    /** Called by [LocalNotifications] when Android would drop a notification. */
+    fun onNotificationSuppressed(id: String, reason: String) {
+        val payload = JSObject()
+        payload.put("id", id)
+        payload.put("reason", reason)
+        sendEvent("notificationSuppressed", payload)
+    }
+
     /**
      * Called from FcmService when a new token is issued.
      * Emits a "tokenRefresh" event to the web client.
@@ -103,21 +852,23 @@ class FcmPlugin(private val activity: android.app.Activity) : Plugin(activity) {
         val data = JSObject()
         data.put("token", token)
         trigger("tokenRefresh", data)
+        sendEvent("tokenRefresh", data)
     }
 
     /**
      * Called from FcmService when a push notification is received in the foreground.
      * Emits a "notificationReceived" event to the web client.
      */
-    fun onNotificationReceived(title: String?, body: String?, data: Map<String, String>) {
-        val obj = JSObject()
-        obj.put("title", title ?: "")
-        obj.put("body", body ?: "")
-        val dataObj = JSObject()
-        for ((key, value) in data) {
-            dataObj.put(key, value)
-        }
-        obj.put("data", dataObj)
+    fun onNotificationReceived(
+        messageId: String?,
+        title: String?,
+        body: String?,
+        data: Map<String, String>,
+        sentTime: Long,
+        collapseKey: String?
+    ) {
+        val obj = messagePayload(messageId, title, body, data, sentTime, collapseKey)
         trigger("notificationReceived", obj)
+        sendEvent("messageReceived", obj)
     }
 }
@@ -1,5 +1,6 @@
 package com.hexidecibel.companion.fcm
 
+import android.content.Context
 import android.util.Log
 import com.google.firebase.messaging.FirebaseMessagingService
 import com.google.firebase.messaging.RemoteMessage
@@ -10,6 +11,10 @@ private const val TAG = "FcmService"
  * Firebase Messaging Service that handles:
  * 1. Token refresh events — forwards to FcmPlugin for re-registration with daemon
  * 2. Foreground message receipt — forwards to FcmPlugin for web client event
+ * 3. Data-only messages carrying a title/body while backgrounded — displayed
+ *    here, with the configured NotificationAppearance
+ * 4. Other data-only messages while backgrounded — queued in SilentMessages
+ *    for the Rust side
  *
  * Background/notification messages are handled automatically by Firebase
  * and shown in the system tray.
@@ -18,6 +23,7 @@ class FcmService : FirebaseMessagingService() {
 
     override fun onNewToken(token: String) {
         super.onNewToken(token)
+        if (!PushDistributor.isFcm(this)) return
         Log.d(TAG, "FCM token refreshed: ${token.take(20)}...")
         FcmPlugin.instance?.onTokenRefresh(token)
     }
@@ -25,12 +31,83 @@ class FcmService : FirebaseMessagingService() {
     override fun onMessageReceived(message: RemoteMessage) {
         super.onMessageReceived(message)
         Log.d(TAG, "FCM message received from: ${message.from}")
+        if (!PushDistributor.isFcm(this)) {
+            Log.d(TAG, "Ignoring FCM message while another distributor is selected")
+            return
+        }
 
-        val title = message.notification?.title
-        val body = message.notification?.body
-        val data = message.data
+        deliver(
+            this,
+            message.messageId,
+            message.notification?.title,
+            message.notification?.body,
+            message.data,
+            message.sentTime,
+            message.notification != null,
+            message.collapseKey
+        )
+    }
 
-        // Forward to plugin for foreground handling
-        FcmPlugin.instance?.onNotificationReceived(title, body, data)
+    companion object {
+        /**
+         * Hands a received push to the plugin, or shows or queues it when the
+         * app isn't running. Shared with [UnifiedPushReceiver] and [HmsService].
+         * [hasNotification] means the system already displayed it.
+         * Redeliveries of a recent [messageId] are dropped; see
+         * [RecentMessages]. A later message with the same [collapseKey]
+         * replaces the notification shown for the earlier one.
+         */
+        fun deliver(
+            context: Context,
+            messageId: String?,
+            title: String?,
+            body: String?,
+            data: Map<String, String>,
+            sentTime: Long,
+            hasNotification: Boolean,
+            collapseKey: String? = null
+        ) {
+            if (RecentMessages.isDuplicate(context, messageId)) {
+                Log.d(TAG, "Dropping duplicate message $messageId")
+                FcmPlugin.instance?.onNotificationSuppressed(messageId ?: "", "duplicate")
+                return
+            }
+            // Android reports the package name when the sender set no collapse key
+            val replaces = collapseKey?.takeIf { it != context.packageName }
+            val plugin = FcmPlugin.instance
+            if (plugin != null) {
+                // Forward to plugin for foreground handling
+                plugin.onNotificationReceived(messageId, title, body, data, sentTime, collapseKey)
+                if (NotificationAppearance.foregroundAlert(context)) {
+                    // Without sound it goes on the quiet channel
+                    val level = if (NotificationAppearance.foregroundSound(context)) null else "passive"
+                    LocalNotifications.show(
+                        context,
+                        messageId ?: "",
+                        title ?: data["title"],
+                        body ?: data["body"],
+                        data,
+                        interruptionLevel = level,
+                        collapseKey = replaces
+                    )
+                }
+            } else if (!hasNotification && data.containsKey("title")) {
+                // Firebase only displays messages with a notification payload
+                LocalNotifications.show(
+                    context,
+                    messageId ?: "",
+                    data["title"],
+                    data["body"],
+                    data,
+                    collapseKey = replaces
+                )
+            } else if (!hasNotification) {
+                // Silent data-only message, e.g. session state to sync
+                SilentMessages.enqueue(
+                    context,
+                    FcmPlugin.messagePayload(messageId, null, null, data, sentTime, collapseKey)
+                )
+            }
+        }
     }
 }
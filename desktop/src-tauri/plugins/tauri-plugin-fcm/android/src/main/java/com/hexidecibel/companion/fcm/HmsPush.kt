@@ -0,0 +1,63 @@
+package com.hexidecibel.companion.fcm
+
+import android.content.Context
+import android.util.Log
+import com.google.android.gms.common.ConnectionResult
+import com.google.android.gms.common.GoogleApiAvailability
+import com.huawei.agconnect.AGConnectOptionsBuilder
+import com.huawei.hms.aaid.HmsInstanceId
+import com.huawei.hms.api.HuaweiApiAvailability
+import com.huawei.hms.common.ApiException
+
+private const val TAG = "HmsPush"
+private const val TOKEN_SCOPE = "HCM"
+
+/**
+ * Huawei Push Kit, for devices with HMS Core but without Google Play
+ * services. The SDK is only a compile-time dependency: apps built with the
+ * plugin's `hms` feature add com.huawei.hms:push and their
+ * agconnect-services.json, and without them HMS is reported unavailable.
+ */
+object HmsPush {
+
+    private val sdkPresent: Boolean by lazy {
+        try {
+            Class.forName("com.huawei.hms.push.HmsMessageService")
+            true
+        } catch (e: ClassNotFoundException) {
+            false
+        }
+    }
+
+    fun isAvailable(context: Context): Boolean =
+        sdkPresent && HuaweiApiAvailability.getInstance()
+            .isHuaweiMobileServicesAvailable(context) == com.huawei.hms.api.ConnectionResult.SUCCESS
+
+    fun googleServicesAvailable(context: Context): Boolean =
+        GoogleApiAvailability.getInstance()
+            .isGooglePlayServicesAvailable(context) == ConnectionResult.SUCCESS
+
+    /**
+     * Blocks on the network, so call it off the main thread. Null on failure,
+     * and on older EMUI versions that hand the token to
+     * [HmsService.onNewToken] instead.
+     */
+    fun token(context: Context): String? = try {
+        HmsInstanceId.getInstance(context).getToken(appId(context), TOKEN_SCOPE)?.ifEmpty { null }
+    } catch (e: ApiException) {
+        Log.e(TAG, "Failed to get HMS token", e)
+        null
+    }
+
+    /** Blocks on the network, so call it off the main thread. */
+    fun deleteToken(context: Context): Boolean = try {
+        HmsInstanceId.getInstance(context).deleteToken(appId(context), TOKEN_SCOPE)
+        true
+    } catch (e: ApiException) {
+        Log.e(TAG, "Failed to delete HMS token", e)
+        false
+    }
+
+    private fun appId(context: Context): String =
+        AGConnectOptionsBuilder().build(context).getString("client/app_id")
+}
@@ -0,0 +1,42 @@
+package com.hexidecibel.companion.fcm
+
+import android.util.Log
+import com.huawei.hms.push.HmsMessageService
+import com.huawei.hms.push.RemoteMessage
+
+private const val TAG = "HmsService"
+
+/**
+ * Push Kit counterpart of [FcmService]: token updates and messages from HMS
+ * Core, handed on the same way while HMS is the selected distributor.
+ */
+class HmsService : HmsMessageService() {
+
+    override fun onNewToken(token: String?) {
+        super.onNewToken(token)
+        if (token.isNullOrEmpty() || !PushDistributor.isHms(this)) return
+        Log.d(TAG, "HMS token refreshed: ${token.take(20)}...")
+        FcmPlugin.instance?.onTokenRefresh(token)
+    }
+
+    override fun onMessageReceived(message: RemoteMessage?) {
+        super.onMessageReceived(message)
+        if (message == null) return
+        Log.d(TAG, "HMS message received from: ${message.from}")
+        if (!PushDistributor.isHms(this)) {
+            Log.d(TAG, "Ignoring HMS message while another distributor is selected")
+            return
+        }
+
+        FcmService.deliver(
+            this,
+            message.messageId,
+            message.notification?.title,
+            message.notification?.body,
+            message.dataOfMap ?: emptyMap(),
+            message.sentTime,
+            message.notification != null,
+            message.collapseKey
+        )
+    }
+}
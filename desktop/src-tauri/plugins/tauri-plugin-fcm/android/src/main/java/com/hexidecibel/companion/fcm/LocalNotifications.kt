@@ -0,0 +1,458 @@
+package com.hexidecibel.companion.fcm
+
+import android.app.Notification
+import android.app.NotificationChannel
+import android.app.NotificationManager
+import android.app.PendingIntent
+import android.content.Context
+import android.content.res.Resources
+import android.graphics.Bitmap
+import android.media.AudioAttributes
+import android.net.Uri
+import android.os.Build
+import android.os.Bundle
+import android.os.Looper
+import android.util.Log
+import androidx.core.app.NotificationCompat
+import androidx.core.app.NotificationManagerCompat
+import androidx.core.app.RemoteInput
+import org.json.JSONArray
+import org.json.JSONException
+
+private const val TAG = "LocalNotifications"
+private const val SESSION_KEY = "sessionId"
+private const val GROUP_KEY = "groupKey"
+private const val THREAD_KEY = "threadId"
+private const val FALLBACK_GROUP = "default"
+private const val PROGRESS_CHANNEL_ID = "progress"
+private const val URGENT_CHANNEL_ID = "urgent"
+private const val CRITICAL_KEY = "critical"
+private const val PASSIVE_CHANNEL_ID = "passive"
+private const val SOUND_KEY = "sound"
+private const val SOUND_CHANNEL_PREFIX = "default_sound_"
+private const val INTERRUPTION_LEVEL_KEY = "interruptionLevel"
+private const val ACTIONS_KEY = "actions"
+private const val IMAGE_URL_KEY = "image_url"
+private const val MAX_ACTIONS = 3
+/** The package id aapt gives the app's own resources. */
+private const val APP_PACKAGE_ID = 0x7f000000
+private const val MAX_SUMMARY_LINES = 5
+private val INTERRUPTION_LEVELS = setOf("passive", "active", "timeSensitive", "critical")
+
+/**
+ * Notifications the app posts itself rather than Firebase: data-only pushes
+ * received in the background and notifications queued from the frontend.
+ * Taps open the app with the data in the launch intent, next to the
+ * "google.message_id" key FcmPlugin uses to recognize notification taps.
+ */
+object LocalNotifications {
+
+    fun show(
+        context: Context,
+        id: String,
+        title: String?,
+        body: String?,
+        data: Map<String, String>,
+        groupKey: String? = null,
+        interruptionLevel: String? = null,
+        collapseKey: String? = null
+    ) {
+        // A newer message under the same collapse key replaces the earlier one
+        val notificationId = (collapseKey?.let { "collapse:$it" } ?: id).hashCode()
+        val level = interruptionLevel ?: interruptionLevel(context, data)
+        // Critical messages always use the urgent channel, the only one allowed past DND
+        val critical = data[CRITICAL_KEY] == "true" || level == "critical"
+        val channelId = when {
+            critical -> URGENT_CHANNEL_ID
+            data["channelId"] != null -> data.getValue("channelId")
+            level == "passive" -> PASSIVE_CHANNEL_ID
+            else -> soundChannelId(context, data[SOUND_KEY]) ?: NotificationAppearance.defaultChannelId(context)
+        }
+        ensureChannel(context, channelId)
+        suppressionReason(context, channelId)?.let {
+            reportSuppressed(id, it)
+            return
+        }
+        if (critical) logDndBypass(context, id)
+        if (!critical && QuietHours.isQuiet(context)) {
+            QuietHours.defer(context, id, title, body, data)
+            return
+        }
+
+        val intent = context.packageManager.getLaunchIntentForPackage(context.packageName) ?: return
+        intent.putExtra("google.message_id", id)
+        for ((key, value) in data) {
+            intent.putExtra(key, value)
+        }
+        val pendingIntent = PendingIntent.getActivity(
+            context,
+            notificationId,
+            intent,
+            PendingIntent.FLAG_UPDATE_CURRENT or PendingIntent.FLAG_IMMUTABLE
+        )
+
+        val group = groupKey(context, groupKey, data)
+        val builder = NotificationCompat.Builder(context, channelId)
+            .setSmallIcon(NotificationAppearance.smallIcon(context))
+            .setContentTitle(title)
+            .setContentText(body)
+            .setAutoCancel(true)
+            .setContentIntent(pendingIntent)
+            .setDeleteIntent(DismissReceiver.intent(context, id))
+            .setGroup(group)
+        when {
+            critical -> builder.setPriority(NotificationCompat.PRIORITY_MAX)
+            level == "timeSensitive" -> builder.setPriority(NotificationCompat.PRIORITY_HIGH)
+            level == "passive" -> builder.setPriority(NotificationCompat.PRIORITY_LOW)
+        }
+        NotificationAppearance.accentColor(context)?.let { builder.setColor(it) }
+        // Before O the sound is per notification rather than per channel
+        if (channelId.startsWith(SOUND_CHANNEL_PREFIX)) {
+            rawSoundUri(context, channelId.removePrefix(SOUND_CHANNEL_PREFIX))?.let { builder.setSound(it) }
+        }
+        NotificationAppearance.badgeCount(context).takeIf { it > 0 }?.let { builder.setNumber(it) }
+        addActions(context, builder, id, notificationId, data)
+        // Lets clearForSession find it again; a PendingIntent's extras can't be read back
+        data[SESSION_KEY]?.let { builder.addExtras(Bundle().apply { putString(SESSION_KEY, it) }) }
+
+        fun post() {
+            try {
+                NotificationManagerCompat.from(context)
+                    .notify(data[SESSION_KEY], notificationId, builder.build())
+                notifyGroupSummary(context, channelId, group)
+            } catch (e: SecurityException) {
+                Log.w(TAG, "Notification permission not granted", e)
+                reportSuppressed(id, "permissionDenied")
+            }
+        }
+
+        val imageUrl = data[IMAGE_URL_KEY]
+        when {
+            imageUrl == null -> post()
+            // showNotification runs on the main thread, where network calls aren't allowed
+            Looper.myLooper() == Looper.getMainLooper() -> Thread {
+                attachImage(context, builder, imageUrl)
+                post()
+            }.start()
+            else -> {
+                attachImage(context, builder, imageUrl)
+                post()
+            }
+        }
+    }
+
+    private fun attachImage(context: Context, builder: NotificationCompat.Builder, url: String) {
+        val bitmap = RichImages.fetch(context, url) ?: return
+        builder.setLargeIcon(bitmap)
+            .setStyle(
+                NotificationCompat.BigPictureStyle()
+                    .bigPicture(bitmap)
+                    .bigLargeIcon(null as Bitmap?)
+            )
+    }
+
+    /**
+     * Adds the buttons declared in the "actions" data field, a JSON array of
+     * {"id", "title", "reply"}. A malformed field is logged and ignored, so
+     * the notification still shows.
+     */
+    private fun addActions(
+        context: Context,
+        builder: NotificationCompat.Builder,
+        id: String,
+        notificationId: Int,
+        data: Map<String, String>
+    ) {
+        val json = data[ACTIONS_KEY] ?: return
+        val actions = try {
+            JSONArray(json)
+        } catch (e: JSONException) {
+            Log.w(TAG, "Ignoring malformed actions on $id", e)
+            return
+        }
+        for (i in 0 until minOf(actions.length(), MAX_ACTIONS)) {
+            val action = actions.optJSONObject(i) ?: continue
+            val actionId = action.optString("id")
+            if (actionId.isEmpty()) continue
+            val title = action.optString("title").ifEmpty { actionId }
+            val intent = ActionReceiver.intent(context, id, notificationId, data[SESSION_KEY], actionId, data)
+            val button = NotificationCompat.Action.Builder(0, title, intent)
+            if (action.optBoolean("reply")) {
+                button.addRemoteInput(RemoteInput.Builder(REPLY_KEY).setLabel(title).build())
+            }
+            builder.addAction(button.build())
+        }
+    }
+
+    /**
+     * Why Android would silently drop a notification on [channelId], or null
+     * if it will be shown. Names match the Rust SuppressionReason.
+     */
+    private fun suppressionReason(context: Context, channelId: String): String? {
+        if (!NotificationManagerCompat.from(context).areNotificationsEnabled()) {
+            return "notificationsDisabled"
+        }
+        if (Build.VERSION.SDK_INT >= Build.VERSION_CODES.O) {
+            val manager = context.getSystemService(Context.NOTIFICATION_SERVICE) as NotificationManager
+            if (manager.getNotificationChannel(channelId)?.importance == NotificationManager.IMPORTANCE_NONE) {
+                return "channelMuted"
+            }
+        }
+        return null
+    }
+
+    /** Suppressions while the app isn't running only reach the log. */
+    private fun reportSuppressed(id: String, reason: String) {
+        Log.i(TAG, "Notification $id suppressed: $reason")
+        FcmPlugin.instance?.onNotificationSuppressed(id, reason)
+    }
+
+    /**
+     * The group a notification joins: an explicit [groupKey], then the
+     * payload's "groupKey", the configured group key field, the session id,
+     * and finally a shared default group. Mirrors grouping::resolve_key.
+     */
+    fun groupKey(context: Context, groupKey: String?, data: Map<String, String>): String {
+        val field = NotificationAppearance.groupKeyField(context) ?: SESSION_KEY
+        return sequenceOf(groupKey, data[GROUP_KEY], data[THREAD_KEY], data[field], data[SESSION_KEY])
+            .firstOrNull { !it.isNullOrEmpty() } ?: FALLBACK_GROUP
+    }
+
+    /**
+     * The message's own "interruptionLevel" if it names a known level, else
+     * the configured default. Mirrors interruption::resolve. Android has no
+     * Focus, so levels map onto channel importance and priority instead:
+     * passive is a low-importance channel, timeSensitive a high priority,
+     * critical the urgent channel.
+     */
+    private fun interruptionLevel(context: Context, data: Map<String, String>): String =
+        data[INTERRUPTION_LEVEL_KEY]?.takeIf { it in INTERRUPTION_LEVELS }
+            ?: NotificationAppearance.interruptionLevel(context)
+
+    /**
+     * Android only bundles a group under a summary notification, so post (or
+     * refresh) one per group. It auto-cancels with the last child. Collapsed,
+     * or where the system doesn't bundle, it shows the newest message and
+     * lists the rest.
+     */
+    private fun notifyGroupSummary(context: Context, channelId: String, group: String) {
+        val manager = context.getSystemService(Context.NOTIFICATION_SERVICE) as NotificationManager
+        val children = manager.activeNotifications
+            .filter { it.notification.group == group }
+            .filter { (it.notification.flags and Notification.FLAG_GROUP_SUMMARY) == 0 }
+            .sortedByDescending { it.postTime }
+        val style = NotificationCompat.InboxStyle()
+        for (child in children.take(MAX_SUMMARY_LINES)) {
+            val extras = child.notification.extras
+            val title = extras.getCharSequence(NotificationCompat.EXTRA_TITLE)
+            val text = extras.getCharSequence(NotificationCompat.EXTRA_TEXT)
+            style.addLine(listOfNotNull(title, text).joinToString(": "))
+        }
+        if (children.size > 1) style.setSummaryText("${children.size} messages")
+
+        val newest = children.firstOrNull()?.notification?.extras
+        val builder = NotificationCompat.Builder(context, channelId)
+            .setSmallIcon(NotificationAppearance.smallIcon(context))
+            .setContentTitle(newest?.getCharSequence(NotificationCompat.EXTRA_TITLE))
+            .setContentText(newest?.getCharSequence(NotificationCompat.EXTRA_TEXT))
+            .setStyle(style)
+            .setNumber(children.size)
+            .setGroup(group)
+            .setGroupSummary(true)
+            .setAutoCancel(true)
+            .setOnlyAlertOnce(true)
+        NotificationAppearance.accentColor(context)?.let { builder.setColor(it) }
+        NotificationManagerCompat.from(context).notify("group:$group", group.hashCode(), builder.build())
+    }
+
+    /**
+     * Cancel every shown notification for a session: ours carry the session id
+     * as tag and extra, and notifications Firebase displays match when the
+     * server sets the session id as their android.notification.tag.
+     * Returns how many were cancelled.
+     */
+    fun clearForSession(context: Context, sessionId: String): Int {
+        val manager = context.getSystemService(Context.NOTIFICATION_SERVICE) as NotificationManager
+        val matching = manager.activeNotifications.filter {
+            it.tag == sessionId || it.notification.extras.getString(SESSION_KEY) == sessionId
+        }
+        for (notification in matching) {
+            manager.cancel(notification.tag, notification.id)
+        }
+        return matching.size
+    }
+
+    /**
+     * Post or update an ongoing notification with a progress bar. It goes to a
+     * low-importance channel and only alerts once, so updates don't buzz.
+     */
+    fun showProgress(context: Context, id: String, title: String, progress: Int, indeterminate: Boolean) {
+        if (Build.VERSION.SDK_INT >= Build.VERSION_CODES.O) {
+            val manager = context.getSystemService(Context.NOTIFICATION_SERVICE) as NotificationManager
+            if (manager.getNotificationChannel(PROGRESS_CHANNEL_ID) == null) {
+                manager.createNotificationChannel(
+                    NotificationChannel(PROGRESS_CHANNEL_ID, "Progress", NotificationManager.IMPORTANCE_LOW)
+                )
+            }
+        }
+        val builder = NotificationCompat.Builder(context, PROGRESS_CHANNEL_ID)
+            .setSmallIcon(NotificationAppearance.smallIcon(context))
+            .setContentTitle(title)
+            .setProgress(100, progress, indeterminate)
+            .setOngoing(true)
+            .setOnlyAlertOnce(true)
+        NotificationAppearance.accentColor(context)?.let { builder.setColor(it) }
+        notify(context, id, builder)
+    }
+
+    /** Replace a progress notification with a dismissable [message], or remove it. */
+    fun completeProgress(context: Context, id: String, title: String, message: String?) {
+        if (message == null) {
+            NotificationManagerCompat.from(context).cancel(id.hashCode())
+            return
+        }
+        val builder = NotificationCompat.Builder(context, PROGRESS_CHANNEL_ID)
+            .setSmallIcon(NotificationAppearance.smallIcon(context))
+            .setContentTitle(title)
+            .setContentText(message)
+            .setAutoCancel(true)
+        NotificationAppearance.accentColor(context)?.let { builder.setColor(it) }
+        notify(context, id, builder)
+    }
+
+    private fun notify(context: Context, id: String, builder: NotificationCompat.Builder) {
+        try {
+            NotificationManagerCompat.from(context).notify(id.hashCode(), builder.build())
+        } catch (e: SecurityException) {
+            Log.w(TAG, "Notification permission not granted", e)
+        }
+    }
+
+    /**
+     * Log when a critical notification is shown while Do Not Disturb is on and
+     * the urgent channel may break through it. Android only honours the
+     * channel's bypass once the user has allowed it in the channel settings
+     * (or granted the app Do Not Disturb access).
+     */
+    private fun logDndBypass(context: Context, id: String) {
+        if (Build.VERSION.SDK_INT < Build.VERSION_CODES.O) return
+        val manager = context.getSystemService(Context.NOTIFICATION_SERVICE) as NotificationManager
+        val dndOn = manager.currentInterruptionFilter > NotificationManager.INTERRUPTION_FILTER_ALL
+        if (dndOn && manager.getNotificationChannel(URGENT_CHANNEL_ID)?.canBypassDnd() == true) {
+            Log.i(TAG, "Critical notification $id bypassed Do Not Disturb")
+        }
+    }
+
+    /**
+     * Create a channel for messages to name in "channelId". [sound] is a raw
+     * resource name, null for the system default, or empty for silence.
+     * Returns false if there is no such sound.
+     */
+    fun createChannel(context: Context, id: String, name: String, importance: String, sound: String?): Boolean {
+        if (Build.VERSION.SDK_INT < Build.VERSION_CODES.O) return true
+        val channel = NotificationChannel(id, name, channelImportance(importance))
+        when {
+            sound == null -> {}
+            sound.isEmpty() -> channel.setSound(null, null)
+            else -> channel.setSound(rawSoundUri(context, sound) ?: return false, soundAttributes())
+        }
+        val manager = context.getSystemService(Context.NOTIFICATION_SERVICE) as NotificationManager
+        manager.createNotificationChannel(channel)
+        return true
+    }
+
+    /** Returns whether the channel existed. */
+    fun deleteChannel(context: Context, id: String): Boolean {
+        if (Build.VERSION.SDK_INT < Build.VERSION_CODES.O) return false
+        val manager = context.getSystemService(Context.NOTIFICATION_SERVICE) as NotificationManager
+        if (manager.getNotificationChannel(id) == null) return false
+        manager.deleteNotificationChannel(id)
+        return true
+    }
+
+    /**
+     * Names of the app's raw resources, which notifications can name in
+     * "sound". Walks the resource table rather than the generated R class,
+     * which R8 strips from release builds.
+     */
+    fun availableSounds(context: Context): List<String> {
+        val resources = context.resources
+        val rawType = (1..0xff).map { APP_PACKAGE_ID or (it shl 16) }.firstOrNull {
+            try {
+                resources.getResourceTypeName(it) == "raw"
+            } catch (e: Resources.NotFoundException) {
+                false
+            }
+        } ?: return emptyList()
+        val sounds = mutableListOf<String>()
+        for (entry in 0..0xffff) {
+            try {
+                sounds.add(resources.getResourceEntryName(rawType or entry))
+            } catch (e: Resources.NotFoundException) {
+                break
+            }
+        }
+        return sounds.sorted()
+    }
+
+    /**
+     * The default channel's variant that plays [sound], or null if the app
+     * has no such raw resource. Android fixes a channel's sound once it
+     * exists, so each sound gets its own.
+     */
+    private fun soundChannelId(context: Context, sound: String?): String? {
+        if (sound.isNullOrEmpty() || rawSoundUri(context, sound) == null) return null
+        return SOUND_CHANNEL_PREFIX + sound
+    }
+
+    private fun rawSoundUri(context: Context, sound: String): Uri? {
+        val resId = context.resources.getIdentifier(sound, "raw", context.packageName)
+        if (resId == 0) return null
+        return Uri.parse("android.resource://${context.packageName}/$resId")
+    }
+
+    private fun soundAttributes(): AudioAttributes = AudioAttributes.Builder()
+        .setUsage(AudioAttributes.USAGE_NOTIFICATION)
+        .build()
+
+    private fun channelImportance(importance: String): Int = when (importance) {
+        "min" -> NotificationManager.IMPORTANCE_MIN
+        "low" -> NotificationManager.IMPORTANCE_LOW
+        "high" -> NotificationManager.IMPORTANCE_HIGH
+        else -> NotificationManager.IMPORTANCE_DEFAULT
+    }
+
+    private fun ensureChannel(context: Context, channelId: String) {
+        if (Build.VERSION.SDK_INT < Build.VERSION_CODES.O) return
+        val manager = context.getSystemService(Context.NOTIFICATION_SERVICE) as NotificationManager
+        if (channelId == URGENT_CHANNEL_ID && manager.getNotificationChannel(channelId) == null) {
+            val channel = NotificationChannel(channelId, "Urgent", NotificationManager.IMPORTANCE_HIGH)
+            channel.setBypassDnd(true)
+            manager.createNotificationChannel(channel)
+            return
+        }
+        if (channelId == PASSIVE_CHANNEL_ID && manager.getNotificationChannel(channelId) == null) {
+            manager.createNotificationChannel(
+                NotificationChannel(channelId, "Quiet", NotificationManager.IMPORTANCE_LOW)
+            )
+            return
+        }
+        if (manager.getNotificationChannel(channelId) == null) {
+            val channel = NotificationChannel(channelId, "Notifications", NotificationManager.IMPORTANCE_HIGH)
+            if (channelId.startsWith(SOUND_CHANNEL_PREFIX)) {
+                val sound = channelId.removePrefix(SOUND_CHANNEL_PREFIX)
+                channel.name = "Notifications ($sound)"
+                rawSoundUri(context, sound)?.let { channel.setSound(it, soundAttributes()) }
+            }
+            if (channelId == NotificationAppearance.defaultChannelId(context) ||
+                channelId.startsWith(SOUND_CHANNEL_PREFIX)
+            ) {
+                NotificationAppearance.vibrationPattern(context)?.let { pattern ->
+                    channel.enableVibration(pattern.isNotEmpty())
+                    if (pattern.isNotEmpty()) channel.vibrationPattern = pattern
+                }
+            }
+            manager.createNotificationChannel(channel)
+        }
+    }
+}
@@ -0,0 +1,187 @@
+package com.hexidecibel.companion.fcm
+
+import android.app.NotificationManager
+import android.content.Context
+import android.graphics.Color
+import android.os.Build
+
+private const val PREFS_NAME = "fcm_notification_appearance"
+private const val KEY_SMALL_ICON = "small_icon"
+private const val KEY_ACCENT_COLOR = "accent_color"
+private const val KEY_VIBRATION_PATTERN = "vibration_pattern"
+private const val KEY_GROUP_KEY_FIELD = "group_key_field"
+private const val KEY_INTERRUPTION_LEVEL = "interruption_level"
+private const val KEY_FOREGROUND_ALERT = "foreground_alert"
+private const val KEY_FOREGROUND_SOUND = "foreground_sound"
+private const val KEY_BADGE_COUNT = "badge_count"
+private const val KEY_IMAGE_MAX_BYTES = "image_max_bytes"
+private const val KEY_IMAGE_TIMEOUT_MS = "image_timeout_ms"
+private const val KEY_DEFAULTS_CHANNEL = "defaults_channel"
+private const val KEY_DEFAULTS_SMALL_ICON = "defaults_small_icon"
+private const val KEY_DEFAULTS_ACCENT_COLOR = "defaults_accent_color"
+// Match the Rust defaults for messages that arrive before the plugin sets them
+private const val DEFAULT_IMAGE_MAX_BYTES = 2L * 1024 * 1024
+private const val DEFAULT_IMAGE_TIMEOUT_MS = 5_000L
+private const val DEFAULT_CHANNEL_ID = "default"
+private const val VIBRATION_CHANNEL_PREFIX = "${DEFAULT_CHANNEL_ID}_vibration_"
+
+/**
+ * Status-bar icon and accent color for notifications the app displays itself.
+ * Stored in SharedPreferences so FcmService can apply it even when the
+ * message arrives before the webview (and the Rust side) has started.
+ */
+object NotificationAppearance {
+
+    /**
+     * Returns false if [smallIcon] is not a drawable in this app. A null
+     * [smallIcon] keeps the one saved previously.
+     */
+    fun save(context: Context, smallIcon: String?, accentColor: String): Boolean {
+        if (smallIcon != null && drawableId(context, smallIcon) == 0) return false
+        val editor = context.getSharedPreferences(PREFS_NAME, Context.MODE_PRIVATE).edit()
+        if (smallIcon != null) editor.putString(KEY_SMALL_ICON, smallIcon)
+        editor.putInt(KEY_ACCENT_COLOR, Color.parseColor(accentColor)).apply()
+        return true
+    }
+
+    /**
+     * Save the channel, icon and color set on the plugin builder, which apply
+     * until [save] configures others. Replaced wholesale at every startup, so
+     * ones dropped from the builder go too. Returns false if [smallIcon] is
+     * not a drawable in this app.
+     */
+    fun saveDefaults(context: Context, channel: String?, smallIcon: String?, accentColor: String?): Boolean {
+        if (smallIcon != null && drawableId(context, smallIcon) == 0) return false
+        val editor = context.getSharedPreferences(PREFS_NAME, Context.MODE_PRIVATE).edit()
+            .putString(KEY_DEFAULTS_CHANNEL, channel)
+            .putString(KEY_DEFAULTS_SMALL_ICON, smallIcon)
+        if (accentColor != null) {
+            editor.putInt(KEY_DEFAULTS_ACCENT_COLOR, Color.parseColor(accentColor))
+        } else {
+            editor.remove(KEY_DEFAULTS_ACCENT_COLOR)
+        }
+        editor.apply()
+        return true
+    }
+
+    /** Configured small icon, falling back to the builder's and then the app icon. */
+    fun smallIcon(context: Context): Int {
+        val prefs = context.getSharedPreferences(PREFS_NAME, Context.MODE_PRIVATE)
+        val id = (prefs.getString(KEY_SMALL_ICON, null) ?: prefs.getString(KEY_DEFAULTS_SMALL_ICON, null))
+            ?.let { drawableId(context, it) } ?: 0
+        return if (id != 0) id else context.applicationInfo.icon
+    }
+
+    fun accentColor(context: Context): Int? {
+        val prefs = context.getSharedPreferences(PREFS_NAME, Context.MODE_PRIVATE)
+        return when {
+            prefs.contains(KEY_ACCENT_COLOR) -> prefs.getInt(KEY_ACCENT_COLOR, 0)
+            prefs.contains(KEY_DEFAULTS_ACCENT_COLOR) -> prefs.getInt(KEY_DEFAULTS_ACCENT_COLOR, 0)
+            else -> null
+        }
+    }
+
+    /**
+     * Save the vibration pattern (ms, alternating off/on) for notifications the
+     * app displays itself; empty disables vibration. A channel's vibration is
+     * fixed once it exists, so each pattern gets its own channel and the one
+     * for the previous pattern is deleted.
+     */
+    fun saveVibrationPattern(context: Context, pattern: LongArray) {
+        val previous = defaultChannelId(context)
+        context.getSharedPreferences(PREFS_NAME, Context.MODE_PRIVATE).edit()
+            .putString(KEY_VIBRATION_PATTERN, pattern.joinToString(","))
+            .apply()
+        if (previous.startsWith(VIBRATION_CHANNEL_PREFIX) && previous != defaultChannelId(context) &&
+            Build.VERSION.SDK_INT >= Build.VERSION_CODES.O
+        ) {
+            val manager = context.getSystemService(Context.NOTIFICATION_SERVICE) as NotificationManager
+            manager.deleteNotificationChannel(previous)
+        }
+    }
+
+    /** The saved pattern, or null to leave vibration to the system default. */
+    fun vibrationPattern(context: Context): LongArray? {
+        val prefs = context.getSharedPreferences(PREFS_NAME, Context.MODE_PRIVATE)
+        val saved = prefs.getString(KEY_VIBRATION_PATTERN, null) ?: return null
+        return if (saved.isEmpty()) LongArray(0) else saved.split(",").map { it.toLong() }.toLongArray()
+    }
+
+    /**
+     * Channel for notifications that don't name one: the builder's if it set
+     * one, else the plugin's, matching the vibration pattern.
+     */
+    fun defaultChannelId(context: Context): String {
+        val prefs = context.getSharedPreferences(PREFS_NAME, Context.MODE_PRIVATE)
+        prefs.getString(KEY_DEFAULTS_CHANNEL, null)?.let { return it }
+        val pattern = vibrationPattern(context) ?: return DEFAULT_CHANNEL_ID
+        return "$VIBRATION_CHANNEL_PREFIX${pattern.joinToString("_").ifEmpty { "off" }}"
+    }
+
+    /** Save the payload field notifications are grouped by; validated on the Rust side. */
+    fun saveGroupKeyField(context: Context, field: String) {
+        context.getSharedPreferences(PREFS_NAME, Context.MODE_PRIVATE).edit()
+            .putString(KEY_GROUP_KEY_FIELD, field)
+            .apply()
+    }
+
+    /** The configured group key field, or null to group by session. */
+    fun groupKeyField(context: Context): String? =
+        context.getSharedPreferences(PREFS_NAME, Context.MODE_PRIVATE).getString(KEY_GROUP_KEY_FIELD, null)
+
+    fun saveInterruptionLevel(context: Context, level: String) {
+        context.getSharedPreferences(PREFS_NAME, Context.MODE_PRIVATE).edit()
+            .putString(KEY_INTERRUPTION_LEVEL, level)
+            .apply()
+    }
+
+    /** Level for messages that don't set one; "active" until configured. */
+    fun interruptionLevel(context: Context): String =
+        context.getSharedPreferences(PREFS_NAME, Context.MODE_PRIVATE)
+            .getString(KEY_INTERRUPTION_LEVEL, null) ?: "active"
+
+    fun saveForegroundPresentation(context: Context, alert: Boolean, sound: Boolean) {
+        context.getSharedPreferences(PREFS_NAME, Context.MODE_PRIVATE).edit()
+            .putBoolean(KEY_FOREGROUND_ALERT, alert)
+            .putBoolean(KEY_FOREGROUND_SOUND, sound)
+            .apply()
+    }
+
+    /** Whether pushes received while the app is running also post a notification. */
+    fun foregroundAlert(context: Context): Boolean =
+        context.getSharedPreferences(PREFS_NAME, Context.MODE_PRIVATE)
+            .getBoolean(KEY_FOREGROUND_ALERT, false)
+
+    fun foregroundSound(context: Context): Boolean =
+        context.getSharedPreferences(PREFS_NAME, Context.MODE_PRIVATE)
+            .getBoolean(KEY_FOREGROUND_SOUND, false)
+
+    fun saveBadgeCount(context: Context, count: Int) {
+        context.getSharedPreferences(PREFS_NAME, Context.MODE_PRIVATE).edit()
+            .putInt(KEY_BADGE_COUNT, count)
+            .apply()
+    }
+
+    /** Count set by the app for launchers that show one; 0 when unset. */
+    fun badgeCount(context: Context): Int =
+        context.getSharedPreferences(PREFS_NAME, Context.MODE_PRIVATE)
+            .getInt(KEY_BADGE_COUNT, 0)
+
+    fun saveImageLimits(context: Context, maxBytes: Long, timeoutMs: Long) {
+        context.getSharedPreferences(PREFS_NAME, Context.MODE_PRIVATE).edit()
+            .putLong(KEY_IMAGE_MAX_BYTES, maxBytes)
+            .putLong(KEY_IMAGE_TIMEOUT_MS, timeoutMs)
+            .apply()
+    }
+
+    fun imageMaxBytes(context: Context): Long =
+        context.getSharedPreferences(PREFS_NAME, Context.MODE_PRIVATE)
+            .getLong(KEY_IMAGE_MAX_BYTES, DEFAULT_IMAGE_MAX_BYTES)
+
+    fun imageTimeoutMs(context: Context): Long =
+        context.getSharedPreferences(PREFS_NAME, Context.MODE_PRIVATE)
+            .getLong(KEY_IMAGE_TIMEOUT_MS, DEFAULT_IMAGE_TIMEOUT_MS)
+
+    private fun drawableId(context: Context, name: String): Int =
+        context.resources.getIdentifier(name, "drawable", context.packageName)
+}
@@ -0,0 +1,86 @@
+package com.hexidecibel.companion.fcm
+
+import android.content.Context
+import org.unifiedpush.android.connector.UnifiedPush
+
+private const val PREFS_NAME = "fcm_push_distributor"
+private const val KEY_DISTRIBUTOR = "distributor"
+private const val KEY_ENDPOINT = "endpoint"
+const val DISTRIBUTOR_FCM = "fcm"
+const val DISTRIBUTOR_UNIFIED_PUSH = "unifiedPush"
+const val DISTRIBUTOR_HMS = "hms"
+
+/**
+ * Which service delivers pushes. With UnifiedPush the endpoint URL the
+ * distributor app hands out stands in for the FCM token, with HMS the Push
+ * Kit token does, and FCM messages that still arrive are ignored.
+ */
+object PushDistributor {
+
+    fun selected(context: Context): String =
+        context.getSharedPreferences(PREFS_NAME, Context.MODE_PRIVATE)
+            .getString(KEY_DISTRIBUTOR, null) ?: DISTRIBUTOR_FCM
+
+    fun isFcm(context: Context): Boolean = selected(context) == DISTRIBUTOR_FCM
+
+    fun isUnifiedPush(context: Context): Boolean = selected(context) == DISTRIBUTOR_UNIFIED_PUSH
+
+    fun isHms(context: Context): Boolean = selected(context) == DISTRIBUTOR_HMS
+
+    /**
+     * Registers with the distributor the user picked before, or else the
+     * first one installed. Returns false if there is none. The endpoint
+     * arrives later in [UnifiedPushReceiver.onNewEndpoint].
+     */
+    fun useUnifiedPush(context: Context): Boolean {
+        val distributor = UnifiedPush.getAckDistributor(context)
+            ?: UnifiedPush.getDistributors(context).firstOrNull()
+            ?: return false
+        leaveHms(context)
+        UnifiedPush.saveDistributor(context, distributor)
+        save(context, DISTRIBUTOR_UNIFIED_PUSH)
+        UnifiedPush.registerApp(context)
+        return true
+    }
+
+    fun useFcm(context: Context) {
+        if (isUnifiedPush(context)) UnifiedPush.unregisterApp(context)
+        leaveHms(context)
+        save(context, DISTRIBUTOR_FCM)
+        saveEndpoint(context, null)
+    }
+
+    /**
+     * Returns false if HMS Core or the Push Kit SDK is missing. The token
+     * follows as a tokenRefresh.
+     */
+    fun useHms(context: Context): Boolean {
+        if (!HmsPush.isAvailable(context)) return false
+        if (isUnifiedPush(context)) UnifiedPush.unregisterApp(context)
+        save(context, DISTRIBUTOR_HMS)
+        saveEndpoint(context, null)
+        return true
+    }
+
+    private fun leaveHms(context: Context) {
+        if (!isHms(context)) return
+        val appContext = context.applicationContext
+        Thread { HmsPush.deleteToken(appContext) }.start()
+    }
+
+    fun endpoint(context: Context): String? =
+        context.getSharedPreferences(PREFS_NAME, Context.MODE_PRIVATE)
+            .getString(KEY_ENDPOINT, null)
+
+    fun saveEndpoint(context: Context, endpoint: String?) {
+        context.getSharedPreferences(PREFS_NAME, Context.MODE_PRIVATE).edit()
+            .putString(KEY_ENDPOINT, endpoint)
+            .apply()
+    }
+
+    private fun save(context: Context, distributor: String) {
+        context.getSharedPreferences(PREFS_NAME, Context.MODE_PRIVATE).edit()
+            .putString(KEY_DISTRIBUTOR, distributor)
+            .apply()
+    }
+}
@@ -0,0 +1,84 @@
+package com.hexidecibel.companion.fcm
+
+import android.content.Context
+import org.json.JSONArray
+import org.json.JSONObject
+import java.util.Calendar
+
+private const val PREFS_NAME = "fcm_quiet_hours"
+private const val KEY_START = "start"
+private const val KEY_END = "end"
+private const val KEY_DAYS = "days"
+private const val KEY_DEFERRED = "deferred"
+private const val CAPACITY = 100
+
+/**
+ * The quiet hours set on the Rust side (see quiet_hours.rs, whose window
+ * logic this mirrors), and the notifications held back during them until
+ * Rust collects them for the digest. The oldest go first past [CAPACITY].
+ */
+object QuietHours {
+
+    fun save(context: Context, start: String, end: String, days: List<Int>) {
+        context.getSharedPreferences(PREFS_NAME, Context.MODE_PRIVATE).edit()
+            .putInt(KEY_START, minutes(start))
+            .putInt(KEY_END, minutes(end))
+            .putString(KEY_DAYS, days.joinToString(","))
+            .apply()
+    }
+
+    fun clear(context: Context) {
+        context.getSharedPreferences(PREFS_NAME, Context.MODE_PRIVATE).edit()
+            .remove(KEY_START)
+            .remove(KEY_END)
+            .remove(KEY_DAYS)
+            .apply()
+    }
+
+    fun isQuiet(context: Context, now: Calendar = Calendar.getInstance()): Boolean {
+        val prefs = context.getSharedPreferences(PREFS_NAME, Context.MODE_PRIVATE)
+        val days = prefs.getString(KEY_DAYS, null)?.split(",")?.mapNotNull { it.toIntOrNull() }
+            ?: return false
+        val start = prefs.getInt(KEY_START, 0)
+        val end = prefs.getInt(KEY_END, 0)
+        val minute = now.get(Calendar.HOUR_OF_DAY) * 60 + now.get(Calendar.MINUTE)
+        // Calendar counts Sunday as 1
+        val weekday = now.get(Calendar.DAY_OF_WEEK) - 1
+        val yesterday = (weekday + 6) % 7
+        return when {
+            start == end -> weekday in days
+            start < end -> weekday in days && minute >= start && minute < end
+            else -> (weekday in days && minute >= start) || (yesterday in days && minute < end)
+        }
+    }
+
+    fun defer(context: Context, id: String, title: String?, body: String?, data: Map<String, String>) {
+        val entry = JSONObject()
+            .put("id", id)
+            .put("title", title ?: "")
+            .put("body", body ?: "")
+            .put("data", JSONObject(data))
+            .put("deferredAt", System.currentTimeMillis())
+        val prefs = context.getSharedPreferences(PREFS_NAME, Context.MODE_PRIVATE)
+        synchronized(this) {
+            val queue = JSONArray(prefs.getString(KEY_DEFERRED, null) ?: "[]").put(entry)
+            while (queue.length() > CAPACITY) queue.remove(0)
+            prefs.edit().putString(KEY_DEFERRED, queue.toString()).apply()
+        }
+    }
+
+    /** The held-back notifications, oldest first, emptying the queue. */
+    fun takeDeferred(context: Context): JSONArray {
+        val prefs = context.getSharedPreferences(PREFS_NAME, Context.MODE_PRIVATE)
+        synchronized(this) {
+            val saved = prefs.getString(KEY_DEFERRED, null) ?: return JSONArray()
+            prefs.edit().remove(KEY_DEFERRED).apply()
+            return JSONArray(saved)
+        }
+    }
+
+    private fun minutes(time: String): Int {
+        val (hours, mins) = time.split(":").map { it.toInt() }
+        return hours * 60 + mins
+    }
+}
@@ -0,0 +1,53 @@
+package com.hexidecibel.companion.fcm
+
+import android.content.Context
+import org.json.JSONArray
+import org.json.JSONObject
+
+private const val PREFS_NAME = "fcm_recent_messages"
+private const val KEY_SEEN = "seen"
+private const val KEY_TTL_MS = "ttl_ms"
+private const val CAPACITY = 200
+// Matches the Rust default for messages that arrive before the plugin sets one
+private const val DEFAULT_TTL_MS = 60 * 60 * 1000L
+
+/**
+ * Messages delivered recently, keyed by message id, so the redeliveries that
+ * arrive in a burst after a reconnect are displayed once. The collapse key is
+ * left out: a newer message under it replaces the notification instead. Mirrors dedup.rs; the oldest go first past [CAPACITY].
+ */
+object RecentMessages {
+
+    /** 0 turns de-duplication off. */
+    fun saveTtl(context: Context, ttlMs: Long) {
+        context.getSharedPreferences(PREFS_NAME, Context.MODE_PRIVATE).edit()
+            .putLong(KEY_TTL_MS, ttlMs)
+            .apply()
+    }
+
+    /**
+     * Whether the message was already delivered within the TTL; remembers it
+     * if not. Messages without an id are never duplicates.
+     */
+    fun isDuplicate(context: Context, messageId: String?): Boolean {
+        val key = messageId?.let { "id:$it" } ?: return false
+        val prefs = context.getSharedPreferences(PREFS_NAME, Context.MODE_PRIVATE)
+        val ttlMs = prefs.getLong(KEY_TTL_MS, DEFAULT_TTL_MS)
+        if (ttlMs == 0L) return false
+        val now = System.currentTimeMillis()
+        synchronized(this) {
+            val saved = JSONArray(prefs.getString(KEY_SEEN, null) ?: "[]")
+            val seen = JSONArray()
+            for (i in 0 until saved.length()) {
+                val entry = saved.getJSONObject(i)
+                if (entry.getLong("seenAt") + ttlMs <= now) continue
+                if (entry.getString("key") == key) return true
+                seen.put(entry)
+            }
+            seen.put(JSONObject().put("key", key).put("seenAt", now))
+            while (seen.length() > CAPACITY) seen.remove(0)
+            prefs.edit().putString(KEY_SEEN, seen.toString()).apply()
+            return false
+        }
+    }
+}
@@ -0,0 +1,75 @@
+package com.hexidecibel.companion.fcm
+
+import android.content.Context
+import android.graphics.Bitmap
+import android.graphics.BitmapFactory
+import android.os.SystemClock
+import android.util.Log
+import java.io.ByteArrayOutputStream
+import java.io.IOException
+import java.io.InputStream
+import java.net.HttpURLConnection
+import java.net.URL
+
+private const val TAG = "RichImages"
+
+/**
+ * Downloads the image named by a message's "image_url" field. Any failure,
+ * including going over the limits set with NotificationAppearance.saveImageLimits,
+ * returns null and the notification is posted without the image.
+ */
+object RichImages {
+
+    fun fetch(context: Context, url: String): Bitmap? {
+        val maxBytes = NotificationAppearance.imageMaxBytes(context)
+        val timeoutMs = NotificationAppearance.imageTimeoutMs(context)
+        val deadline = SystemClock.elapsedRealtime() + timeoutMs
+        return try {
+            val connection = URL(url).openConnection() as HttpURLConnection
+            connection.connectTimeout = timeoutMs.toInt()
+            connection.readTimeout = timeoutMs.toInt()
+            try {
+                if (connection.responseCode != HttpURLConnection.HTTP_OK) {
+                    Log.w(TAG, "Image request failed with ${connection.responseCode}")
+                    return null
+                }
+                if (connection.contentLengthLong > maxBytes) {
+                    Log.w(TAG, "Image is ${connection.contentLengthLong} bytes, over the $maxBytes limit")
+                    return null
+                }
+                val bytes = connection.inputStream.use { read(it, maxBytes, deadline) } ?: return null
+                BitmapFactory.decodeByteArray(bytes, 0, bytes.size)
+            } finally {
+                connection.disconnect()
+            }
+        } catch (e: IOException) {
+            Log.w(TAG, "Failed to download notification image", e)
+            null
+        } catch (e: IllegalArgumentException) {
+            Log.w(TAG, "Invalid notification image URL", e)
+            null
+        }
+    }
+
+    /**
+     * The read timeout only bounds each read, so a slow trickle is also cut
+     * off at [deadline]. Servers that don't send a length are capped here.
+     */
+    private fun read(input: InputStream, maxBytes: Long, deadline: Long): ByteArray? {
+        val out = ByteArrayOutputStream()
+        val buffer = ByteArray(8192)
+        while (true) {
+            val n = input.read(buffer)
+            if (n < 0) return out.toByteArray()
+            out.write(buffer, 0, n)
+            if (out.size() > maxBytes) {
+                Log.w(TAG, "Image is over the $maxBytes byte limit")
+                return null
+            }
+            if (SystemClock.elapsedRealtime() > deadline) {
+                Log.w(TAG, "Image download timed out")
+                return null
+            }
+        }
+    }
+}
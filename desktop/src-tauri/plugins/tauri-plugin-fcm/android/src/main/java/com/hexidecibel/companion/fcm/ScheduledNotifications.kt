@@ -0,0 +1,82 @@
+package com.hexidecibel.companion.fcm
+
+import android.app.AlarmManager
+import android.content.Context
+import android.os.Build
+import org.json.JSONObject
+
+private const val PREFS_NAME = "fcm_scheduled_notifications"
+
+/**
+ * Notifications to post at a set time, kept in SharedPreferences by id
+ * because alarms don't survive a reboot; [BootReceiver] arms them again.
+ * Exact alarms are used when the app may schedule them (declared
+ * SCHEDULE_EXACT_ALARM, or before Android 12), otherwise the system may
+ * deliver a few minutes late to save battery.
+ */
+object ScheduledNotifications {
+
+    fun schedule(
+        context: Context,
+        id: String,
+        at: Long,
+        title: String,
+        body: String,
+        data: Map<String, String>
+    ) {
+        val entry = JSONObject()
+            .put("at", at)
+            .put("title", title)
+            .put("body", body)
+            .put("data", JSONObject(data))
+        context.getSharedPreferences(PREFS_NAME, Context.MODE_PRIVATE).edit()
+            .putString(id, entry.toString())
+            .apply()
+        arm(context, id, at)
+    }
+
+    /** Returns whether it was still scheduled. */
+    fun cancel(context: Context, id: String): Boolean {
+        val prefs = context.getSharedPreferences(PREFS_NAME, Context.MODE_PRIVATE)
+        if (!prefs.contains(id)) return false
+        prefs.edit().remove(id).apply()
+        alarmManager(context).cancel(ScheduledReceiver.intent(context, id))
+        return true
+    }
+
+    /** How many are scheduled and not yet posted. */
+    fun count(context: Context): Int =
+        context.getSharedPreferences(PREFS_NAME, Context.MODE_PRIVATE).all.size
+
+    fun rearmAll(context: Context) {
+        val prefs = context.getSharedPreferences(PREFS_NAME, Context.MODE_PRIVATE)
+        for ((id, saved) in prefs.all) {
+            val at = (saved as? String)?.let { JSONObject(it).optLong("at") } ?: continue
+            arm(context, id, at)
+        }
+    }
+
+    /** Post a due notification and forget it; an unknown id was cancelled meanwhile. */
+    fun fire(context: Context, id: String) {
+        val prefs = context.getSharedPreferences(PREFS_NAME, Context.MODE_PRIVATE)
+        val entry = prefs.getString(id, null)?.let { JSONObject(it) } ?: return
+        prefs.edit().remove(id).apply()
+        val data = entry.optJSONObject("data")
+        val payload = data?.keys()?.asSequence()?.associateWith { data.getString(it) } ?: emptyMap()
+        LocalNotifications.show(context, id, entry.optString("title"), entry.optString("body"), payload)
+    }
+
+    private fun arm(context: Context, id: String, at: Long) {
+        val manager = alarmManager(context)
+        val intent = ScheduledReceiver.intent(context, id)
+        val exact = Build.VERSION.SDK_INT < Build.VERSION_CODES.S || manager.canScheduleExactAlarms()
+        if (exact) {
+            manager.setExactAndAllowWhileIdle(AlarmManager.RTC_WAKEUP, at, intent)
+        } else {
+            manager.setAndAllowWhileIdle(AlarmManager.RTC_WAKEUP, at, intent)
+        }
+    }
+
+    private fun alarmManager(context: Context): AlarmManager =
+        context.getSystemService(Context.ALARM_SERVICE) as AlarmManager
+}
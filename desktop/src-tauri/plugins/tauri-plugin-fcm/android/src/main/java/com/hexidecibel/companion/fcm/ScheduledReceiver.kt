@@ -0,0 +1,29 @@
+package com.hexidecibel.companion.fcm
+
+import android.app.PendingIntent
+import android.content.BroadcastReceiver
+import android.content.Context
+import android.content.Intent
+
+private const val EXTRA_ID = "id"
+
+/** Fires the alarms set by [ScheduledNotifications]. */
+class ScheduledReceiver : BroadcastReceiver() {
+
+    override fun onReceive(context: Context, intent: Intent) {
+        val id = intent.getStringExtra(EXTRA_ID) ?: return
+        ScheduledNotifications.fire(context, id)
+    }
+
+    companion object {
+        fun intent(context: Context, id: String): PendingIntent {
+            val intent = Intent(context, ScheduledReceiver::class.java).putExtra(EXTRA_ID, id)
+            return PendingIntent.getBroadcast(
+                context,
+                id.hashCode(),
+                intent,
+                PendingIntent.FLAG_UPDATE_CURRENT or PendingIntent.FLAG_IMMUTABLE
+            )
+        }
+    }
+}
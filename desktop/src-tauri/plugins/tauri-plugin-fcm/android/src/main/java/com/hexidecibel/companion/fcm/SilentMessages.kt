@@ -0,0 +1,36 @@
+package com.hexidecibel.companion.fcm
+
+import android.content.Context
+import org.json.JSONArray
+import org.json.JSONObject
+
+private const val PREFS_NAME = "fcm_silent_messages"
+private const val KEY_QUEUE = "queue"
+private const val CAPACITY = 100
+
+/**
+ * Data-only messages without a title that arrived while the app wasn't
+ * running. Nothing is shown for them, so they are kept until [FcmPlugin]
+ * loads and hands them to Rust. The oldest go first past [CAPACITY].
+ */
+object SilentMessages {
+
+    fun enqueue(context: Context, message: JSONObject) {
+        val prefs = context.getSharedPreferences(PREFS_NAME, Context.MODE_PRIVATE)
+        synchronized(this) {
+            val queue = JSONArray(prefs.getString(KEY_QUEUE, null) ?: "[]").put(message)
+            while (queue.length() > CAPACITY) queue.remove(0)
+            prefs.edit().putString(KEY_QUEUE, queue.toString()).apply()
+        }
+    }
+
+    /** The queued messages, oldest first, or null if there are none. */
+    fun take(context: Context): JSONArray? {
+        val prefs = context.getSharedPreferences(PREFS_NAME, Context.MODE_PRIVATE)
+        synchronized(this) {
+            val saved = prefs.getString(KEY_QUEUE, null) ?: return null
+            prefs.edit().remove(KEY_QUEUE).apply()
+            return JSONArray(saved)
+        }
+    }
+}
@@ -0,0 +1,53 @@
+package com.hexidecibel.companion.fcm
+
+import android.content.Context
+import android.util.Log
+import org.json.JSONException
+import org.json.JSONObject
+import org.unifiedpush.android.connector.MessagingReceiver
+
+private const val TAG = "UnifiedPushReceiver"
+
+/**
+ * Endpoint updates and messages from a UnifiedPush distributor. A message is
+ * a JSON object treated like an FCM data payload: "title" and "body" are
+ * read from it, and "messageId", "collapseKey" and "sentTime" if the
+ * server sets them.
+ */
+class UnifiedPushReceiver : MessagingReceiver() {
+
+    override fun onNewEndpoint(context: Context, endpoint: String, instance: String) {
+        Log.d(TAG, "UnifiedPush endpoint updated")
+        PushDistributor.saveEndpoint(context, endpoint)
+        FcmPlugin.instance?.onTokenRefresh(endpoint)
+    }
+
+    override fun onRegistrationFailed(context: Context, instance: String) {
+        Log.w(TAG, "UnifiedPush registration failed")
+    }
+
+    override fun onUnregistered(context: Context, instance: String) {
+        Log.d(TAG, "UnifiedPush unregistered")
+        PushDistributor.saveEndpoint(context, null)
+    }
+
+    override fun onMessage(context: Context, message: ByteArray, instance: String) {
+        val data = try {
+            val json = JSONObject(String(message, Charsets.UTF_8))
+            json.keys().asSequence().associateWith { json.get(it).toString() }
+        } catch (e: JSONException) {
+            Log.w(TAG, "Ignoring UnifiedPush message that isn't a JSON object", e)
+            return
+        }
+        FcmService.deliver(
+            context,
+            data["messageId"],
+            null,
+            null,
+            data,
+            data["sentTime"]?.toLongOrNull() ?: 0,
+            hasNotification = false,
+            collapseKey = data["collapseKey"]
+        )
+    }
+}
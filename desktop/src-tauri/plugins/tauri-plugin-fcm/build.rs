@@ -1,7 +1,89 @@
 const COMMANDS: &[&str] = &[
     "get_fcm_token",
     "request_notification_permission",
+    "can_request_notification_permission",
     "is_notification_permission_granted",
+    "subscribe_to_topics",
+    "unsubscribe_from_topics",
+    "get_subscribed_topics",
+    "clear_all_topic_subscriptions",
+    "request_in_app_review",
+    "configure_token_upload",
+    "set_token_upload_headers",
+    "fcm_shutdown",
+    "get_default_notification_action",
+    "set_default_notification_action",
+    "configure_notification_appearance",
+    "replay_sticky_events",
+    "fcm_ping",
+    "pause_token_upload",
+    "resume_token_upload",
+    "get_token_upload_state",
+    "export_message_cache",
+    "clear_message_cache",
+    "get_last_message_age",
+    "set_throttling_threshold",
+    "get_sender_policy",
+    "set_sender_policy",
+    "set_message_batching",
+    "notifications_are_grouped",
+    "notify_on_background",
+    "cancel_background_notification",
+    "get_fcm_auto_init_enabled",
+    "set_fcm_auto_init_enabled",
+    "get_missed_count",
+    "clear_missed_count",
+    "reinitialize_fcm",
+    "get_metrics",
+    "reset_metrics",
+    "set_metrics_enabled",
+    "record_metric",
+    "clear_notifications_for_session",
+    "set_vibration_pattern",
+    "get_install_id",
+    "reset_install_id",
+    "show_progress_notification",
+    "update_progress_notification",
+    "complete_progress_notification",
+    "get_notification_appearance",
+    "get_notification_history",
+    "clear_notification_history",
+    "get_fcm_sender_id",
+    "get_default_group_key_field",
+    "set_default_group_key_field",
+    "get_suppressed_notifications",
+    "get_default_interruption_level",
+    "set_default_interruption_level",
+    "get_clock_offset",
+    "init_fcm_listeners",
+    "get_cache_encryption",
+    "set_cache_encryption",
+    "get_foreground_presentation_options",
+    "set_foreground_presentation_options",
+    "subscribe_to_topic",
+    "unsubscribe_from_topic",
+    "delete_fcm_token",
+    "create_notification_channel",
+    "delete_notification_channel",
+    "set_badge_count",
+    "clear_badge",
+    "get_desktop_push_endpoint",
+    "set_desktop_push_endpoint",
+    "get_push_distributor",
+    "set_push_distributor",
+    "open_notification_settings",
+    "get_quiet_hours",
+    "set_quiet_hours",
+    "clear_quiet_hours",
+    "set_dedup_ttl",
+    "get_push_public_key",
+    "schedule_notification",
+    "cancel_scheduled",
+    "get_scheduled_limits",
+    "list_notifications",
+    "mark_read",
+    "clear_all",
+    "get_available_sounds",
 ];
 
 fn main() {
@@ -0,0 +1,71 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use tauri::{AppHandle, Runtime};
+
+use crate::{
+    history::{self, NotificationState},
+    metrics, sticky,
+};
+
+/// Emitted with a [`NotificationAction`] when the user presses one of a
+/// notification's action buttons.
+pub const NOTIFICATION_ACTION_EVENT: &str = "fcm://notification-action";
+
+/// A pressed action button. Messages declare their buttons in the `actions`
+/// data field, a JSON-encoded array of `{ "id", "title", "reply" }` where
+/// `reply` asks for inline text; Android renders up to three.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NotificationAction {
+    /// The notification's message id.
+    pub id: String,
+    /// The `id` of the pressed action.
+    pub action: String,
+    /// Text typed into an inline reply action.
+    #[serde(default)]
+    pub reply: Option<String>,
+    #[serde(default)]
+    pub data: Map<String, Value>,
+}
+
+/// Handle an action the native side reported. Actions pressed while the app
+/// wasn't running are reported once it starts, so the event is sticky.
+#[cfg(mobile)]
+pub(crate) fn on_native<R: Runtime>(app: &AppHandle<R>, payload: &Value) {
+    match NotificationAction::deserialize(payload) {
+        Ok(action) => on_action(app, action),
+        Err(e) => log::warn!("FCM plugin: malformed notificationAction event: {e}"),
+    }
+}
+
+#[cfg_attr(not(mobile), allow(dead_code))]
+fn on_action<R: Runtime>(app: &AppHandle<R>, action: NotificationAction) {
+    history::mark(app, &action.id, NotificationState::Tapped);
+    metrics::count(app, metrics::NOTIFICATION_TAPPED);
+    sticky::emit_sticky(app, NOTIFICATION_ACTION_EVENT, action);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn parses_native_actions() {
+        let action = NotificationAction::deserialize(json!({
+            "id": "m1",
+            "action": "reply",
+            "reply": "on it",
+            "data": { "sessionId": "abc" },
+        }))
+        .unwrap();
+        assert_eq!(action.action, "reply");
+        assert_eq!(action.reply.as_deref(), Some("on it"));
+        assert_eq!(action.data["sessionId"], "abc");
+
+        let action =
+            NotificationAction::deserialize(json!({ "id": "m1", "action": "approve" })).unwrap();
+        assert_eq!(action.reply, None);
+        assert!(action.data.is_empty());
+    }
+}
@@ -0,0 +1,233 @@
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::{channels, grouping, store, Error};
+
+const STORE_NAME: &str = "appearance";
+
+/// What the OS will use for notifications the app displays itself, for a
+/// settings preview. Android-only fields are `None` elsewhere.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct NotificationAppearance {
+    /// Drawable resource name; `None` is the app's default icon.
+    pub small_icon: Option<String>,
+    pub accent_color: Option<String>,
+    /// `None` leaves vibration to the channel default; empty is off.
+    pub vibration_pattern: Option<Vec<u64>>,
+    /// Sound URI of the default channel; `None` is the system default sound.
+    pub sound: Option<String>,
+    /// Channel used when a message doesn't name one.
+    pub default_channel: Option<String>,
+    /// Whether the OS is bundling the app's notifications into a group.
+    pub grouping: Option<bool>,
+}
+
+/// The appearance settings applied so far, mirrored from the native side so
+/// they can be read back without a round trip.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct AppearanceConfig {
+    small_icon: Option<String>,
+    accent_color: Option<String>,
+    vibration_pattern: Option<Vec<u64>>,
+}
+
+/// What the embedding app set on [`crate::Builder`], used until the
+/// frontend configures its own. Android only.
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct AppearanceDefaults {
+    pub(crate) default_channel: Option<String>,
+    pub(crate) small_icon: Option<String>,
+    pub(crate) accent_color: Option<String>,
+}
+
+impl AppearanceDefaults {
+    pub(crate) fn validate(&self) -> crate::Result<()> {
+        if let Some(channel) = &self.default_channel {
+            channels::validate_id(channel)?;
+        }
+        if let Some(icon) = &self.small_icon {
+            validate_icon(icon)?;
+        }
+        if let Some(color) = &self.accent_color {
+            validate_color(color)?;
+        }
+        Ok(())
+    }
+}
+
+pub(crate) struct AppearanceState {
+    config: Mutex<AppearanceConfig>,
+    defaults: AppearanceDefaults,
+}
+
+impl AppearanceState {
+    pub(crate) fn load<R: Runtime>(app: &AppHandle<R>, defaults: AppearanceDefaults) -> Self {
+        Self {
+            config: Mutex::new(store::load(app, STORE_NAME)),
+            defaults,
+        }
+    }
+
+    #[cfg_attr(not(mobile), allow(dead_code))]
+    fn update<R: Runtime>(&self, app: &AppHandle<R>, change: impl FnOnce(&mut AppearanceConfig)) {
+        let mut config = self.config.lock().unwrap();
+        change(&mut config);
+        if let Err(e) = store::save(app, STORE_NAME, &*config) {
+            log::warn!("FCM plugin: failed to persist notification appearance: {e}");
+        }
+    }
+}
+
+/// Record an icon and accent color once the native side applied them. Without
+/// an icon the previous one stays, as it does natively.
+#[cfg_attr(not(mobile), allow(dead_code))]
+pub(crate) fn record_style<R: Runtime>(
+    app: &AppHandle<R>,
+    small_icon: Option<String>,
+    accent_color: String,
+) {
+    app.state::<AppearanceState>().update(app, |config| {
+        if small_icon.is_some() {
+            config.small_icon = small_icon;
+        }
+        config.accent_color = Some(accent_color);
+    });
+}
+
+#[cfg_attr(not(mobile), allow(dead_code))]
+pub(crate) fn record_vibration_pattern<R: Runtime>(app: &AppHandle<R>, pattern: Vec<u64>) {
+    app.state::<AppearanceState>()
+        .update(app, |config| config.vibration_pattern = Some(pattern));
+}
+
+/// Hand the [`crate::Builder`] defaults to the native side at startup.
+#[cfg(mobile)]
+pub(crate) fn apply_defaults<R: Runtime>(app: &AppHandle<R>) {
+    use crate::FcmExt;
+    let state = app.state::<AppearanceState>();
+    if let Err(e) = app.fcm().set_appearance_defaults(&state.defaults) {
+        log::warn!("FCM plugin: failed to apply notification defaults: {e}");
+    }
+}
+
+/// The recorded settings, falling back to the [`crate::Builder`] defaults,
+/// plus what only the native side can resolve.
+pub(crate) fn get<R: Runtime>(app: &AppHandle<R>) -> crate::Result<NotificationAppearance> {
+    let state = app.state::<AppearanceState>();
+    let config = state.config.lock().unwrap().clone();
+    let defaults = state.defaults.clone();
+    #[cfg(mobile)]
+    let (default_channel, sound) = {
+        use crate::FcmExt;
+        app.fcm().notification_channel_info()?
+    };
+    #[cfg(not(mobile))]
+    let (default_channel, sound) = (None, None);
+    Ok(NotificationAppearance {
+        small_icon: config.small_icon.or(defaults.small_icon),
+        accent_color: config.accent_color.or(defaults.accent_color),
+        vibration_pattern: config.vibration_pattern,
+        sound,
+        default_channel,
+        grouping: grouping::get(app),
+    })
+}
+
+/// Check an accent color is `#RRGGBB` or `#AARRGGBB`, the forms Android's
+/// `Color.parseColor` accepts without relying on named colors.
+pub(crate) fn validate_color(color: &str) -> crate::Result<()> {
+    let valid = color.strip_prefix('#').is_some_and(|hex| {
+        matches!(hex.len(), 6 | 8) && hex.chars().all(|c| c.is_ascii_hexdigit())
+    });
+    if valid {
+        Ok(())
+    } else {
+        Err(Error::InvalidColor(color.to_string()))
+    }
+}
+
+/// Check a small-icon name could be an Android drawable resource name.
+pub(crate) fn validate_icon(name: &str) -> crate::Result<()> {
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(Error::InvalidIcon(name.to_string()))
+    }
+}
+
+/// Longest pattern accepted, in entries.
+const MAX_PATTERN_LEN: usize = 32;
+/// Longest single off or on step, in ms.
+const MAX_PATTERN_STEP: u64 = 10_000;
+
+/// Check a vibration pattern (alternating off/on durations in ms) is short
+/// enough to be a notification buzz rather than a ringtone.
+pub(crate) fn validate_vibration_pattern(pattern: &[u64]) -> crate::Result<()> {
+    if pattern.len() > MAX_PATTERN_LEN {
+        return Err(Error::InvalidVibrationPattern(format!(
+            "{} entries (at most {MAX_PATTERN_LEN})",
+            pattern.len()
+        )));
+    }
+    if let Some(step) = pattern.iter().find(|&&ms| ms > MAX_PATTERN_STEP) {
+        return Err(Error::InvalidVibrationPattern(format!(
+            "{step} ms step (at most {MAX_PATTERN_STEP})"
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vibration_pattern_bounds() {
+        assert!(validate_vibration_pattern(&[]).is_ok());
+        assert!(validate_vibration_pattern(&[0, 250, 100, 250]).is_ok());
+        assert!(validate_vibration_pattern(&[0, 60_000]).is_err());
+        assert!(validate_vibration_pattern(&[100; 33]).is_err());
+    }
+
+    #[test]
+    fn accepts_rgb_and_argb_hex() {
+        assert!(validate_color("#1E88E5").is_ok());
+        assert!(validate_color("#ff1e88e5").is_ok());
+    }
+
+    #[test]
+    fn builder_defaults_are_validated() {
+        let defaults = AppearanceDefaults {
+            default_channel: Some("mentions".to_string()),
+            small_icon: Some("ic_stat_companion".to_string()),
+            accent_color: Some("#1E88E5".to_string()),
+        };
+        assert!(defaults.validate().is_ok());
+        assert!(AppearanceDefaults::default().validate().is_ok());
+
+        let builtin_channel = AppearanceDefaults {
+            default_channel: Some("urgent".to_string()),
+            ..defaults.clone()
+        };
+        assert!(builtin_channel.validate().is_err());
+        let bad_icon = AppearanceDefaults {
+            small_icon: Some("Icon.png".to_string()),
+            ..defaults
+        };
+        assert!(bad_icon.validate().is_err());
+    }
+
+    #[test]
+    fn rejects_other_color_forms() {
+        for color in ["1E88E5", "#1E88E", "#GGGGGG", "blue", "#1e88e5ff00", ""] {
+            assert!(validate_color(color).is_err(), "{color}");
+        }
+    }
+}
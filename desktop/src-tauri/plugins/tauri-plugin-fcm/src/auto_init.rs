@@ -0,0 +1,46 @@
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Runtime};
+
+use crate::store;
+
+const STORE_NAME: &str = "auto_init";
+
+#[derive(Default, Serialize, Deserialize)]
+struct AutoInitConfig {
+    /// `None` leaves the SDK default (or the manifest's meta-data) alone.
+    enabled: Option<bool>,
+}
+
+/// Persist whether FCM may generate a token on its own and apply it.
+pub(crate) fn set<R: Runtime>(app: &AppHandle<R>, enabled: bool) -> crate::Result<()> {
+    store::save(
+        app,
+        STORE_NAME,
+        &AutoInitConfig {
+            enabled: Some(enabled),
+        },
+    )?;
+    #[cfg(mobile)]
+    {
+        use crate::FcmExt;
+        app.fcm().set_auto_init_enabled(enabled)?;
+    }
+    Ok(())
+}
+
+pub(crate) fn get<R: Runtime>(app: &AppHandle<R>) -> Option<bool> {
+    store::load::<_, AutoInitConfig>(app, STORE_NAME).enabled
+}
+
+/// Re-apply the saved choice at startup, in case the SDK's own copy of it was
+/// lost (e.g. app data restored from a backup without it). Without one,
+/// `default` from the plugin builder applies.
+#[cfg(mobile)]
+pub(crate) fn reapply<R: Runtime>(app: &AppHandle<R>, default: Option<bool>) {
+    use crate::FcmExt;
+    if let Some(enabled) = get(app).or(default) {
+        if let Err(e) = app.fcm().set_auto_init_enabled(enabled) {
+            log::warn!("FCM plugin: failed to apply auto-init setting: {e}");
+        }
+    }
+}
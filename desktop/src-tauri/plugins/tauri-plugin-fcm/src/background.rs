@@ -0,0 +1,193 @@
+use std::{collections::HashMap, sync::Mutex};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::{
+    history::{self, NotificationSource},
+    metrics, now_ms, quiet_hours, store,
+};
+
+const STORE_NAME: &str = "background_notifications";
+
+/// A notification to show if the app leaves the foreground before it expires.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct PendingNotification {
+    id: String,
+    title: String,
+    body: String,
+    payload: HashMap<String, String>,
+    /// Overrides the group key taken from the payload.
+    #[serde(default)]
+    group_key: Option<String>,
+    expires_at: u64,
+}
+
+/// Notifications queued with `notify_on_background`. Persisted so one queued
+/// before the app was killed still fires the next time it is backgrounded.
+pub(crate) struct BackgroundNotifications(Mutex<Vec<PendingNotification>>);
+
+impl BackgroundNotifications {
+    pub(crate) fn load<R: Runtime>(app: &AppHandle<R>) -> Self {
+        let mut pending: Vec<PendingNotification> = store::load(app, STORE_NAME);
+        let now = now_ms();
+        pending.retain(|n| n.expires_at > now);
+        Self(Mutex::new(pending))
+    }
+
+    fn persist<R: Runtime>(
+        &self,
+        app: &AppHandle<R>,
+        pending: &[PendingNotification],
+    ) -> crate::Result<()> {
+        store::save(app, STORE_NAME, &pending)
+    }
+}
+
+/// Queue a notification for the next time the app is backgrounded within
+/// `ttl_ms`. Returns its id for [`cancel`].
+pub(crate) fn queue<R: Runtime>(
+    app: &AppHandle<R>,
+    title: String,
+    body: String,
+    payload: HashMap<String, String>,
+    group_key: Option<String>,
+    ttl_ms: u64,
+) -> crate::Result<String> {
+    let now = now_ms();
+    let state = app.state::<BackgroundNotifications>();
+    let mut pending = state.0.lock().unwrap();
+    pending.retain(|n| n.expires_at > now);
+    let id = format!("background-{}", uuid::Uuid::new_v4());
+    pending.push(PendingNotification {
+        id: id.clone(),
+        title,
+        body,
+        payload,
+        group_key,
+        expires_at: now.saturating_add(ttl_ms),
+    });
+    state.persist(app, &pending)?;
+    Ok(id)
+}
+
+/// Drop a queued notification. Returns whether it was still pending.
+pub(crate) fn cancel<R: Runtime>(app: &AppHandle<R>, id: &str) -> crate::Result<bool> {
+    let state = app.state::<BackgroundNotifications>();
+    let mut pending = state.0.lock().unwrap();
+    let before = pending.len();
+    pending.retain(|n| n.id != id);
+    if pending.len() == before {
+        return Ok(false);
+    }
+    state.persist(app, &pending)?;
+    Ok(true)
+}
+
+/// Drop queued notifications whose payload belongs to a session. Returns how many.
+pub(crate) fn cancel_for_session<R: Runtime>(
+    app: &AppHandle<R>,
+    session_id: &str,
+) -> crate::Result<u32> {
+    let state = app.state::<BackgroundNotifications>();
+    let mut pending = state.0.lock().unwrap();
+    let before = pending.len();
+    pending.retain(|n| n.payload.get("sessionId").map(String::as_str) != Some(session_id));
+    let removed = before - pending.len();
+    if removed > 0 {
+        state.persist(app, &pending)?;
+    }
+    Ok(removed as u32)
+}
+
+/// Show every unexpired queued notification; each fires at most once.
+pub(crate) fn on_paused<R: Runtime>(app: &AppHandle<R>) {
+    let state = app.state::<BackgroundNotifications>();
+    let due: Vec<PendingNotification> = {
+        let mut pending = state.0.lock().unwrap();
+        if pending.is_empty() {
+            return;
+        }
+        let now = now_ms();
+        let due = pending.drain(..).filter(|n| n.expires_at > now).collect();
+        if let Err(e) = state.persist(app, &pending) {
+            log::warn!("FCM plugin: failed to persist background notifications: {e}");
+        }
+        due
+    };
+
+    // Native calls block on the main thread, so hop off the caller's thread first
+    let app = app.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        for notification in due {
+            let deferred = quiet_hours::defer(
+                &app,
+                &notification.id,
+                &notification.title,
+                &notification.body,
+                &notification.payload,
+            );
+            if deferred {
+                continue;
+            }
+            match show(&app, &notification) {
+                Ok(()) => {
+                    metrics::count(&app, metrics::NOTIFICATION_SHOWN);
+                    let data = notification
+                        .payload
+                        .into_iter()
+                        .map(|(key, value)| (key, value.into()))
+                        .collect();
+                    history::record(
+                        &app,
+                        notification.id,
+                        NotificationSource::Local,
+                        notification.title,
+                        notification.body,
+                        data,
+                    );
+                }
+                Err(e) => log::warn!("FCM plugin: failed to show background notification: {e}"),
+            }
+        }
+    });
+}
+
+#[cfg(mobile)]
+fn show<R: Runtime>(app: &AppHandle<R>, notification: &PendingNotification) -> crate::Result<()> {
+    use crate::{grouping, interruption, FcmExt};
+    let group_key = grouping::resolve_key(
+        notification.group_key.as_deref(),
+        &grouping::default_field(app),
+        |key| notification.payload.get(key).map(String::as_str),
+    );
+    let level = interruption::resolve(
+        notification
+            .payload
+            .get(interruption::INTERRUPTION_LEVEL_KEY)
+            .map(String::as_str),
+        interruption::default_level(app),
+    );
+    app.fcm().show_notification(
+        &notification.id,
+        &notification.title,
+        &notification.body,
+        &notification.payload,
+        &group_key,
+        level,
+    )
+}
+
+/// Desktop notifications carry no payload, so a click just focuses the app.
+#[cfg(desktop)]
+fn show<R: Runtime>(app: &AppHandle<R>, notification: &PendingNotification) -> crate::Result<()> {
+    use tauri_plugin_notification::NotificationExt;
+    let builder = app
+        .notification()
+        .builder()
+        .title(&notification.title)
+        .body(&notification.body);
+    crate::sounds::apply(builder, &notification.payload)
+        .show()
+        .map_err(|e| crate::Error::PluginInvoke(e.to_string()))
+}
@@ -0,0 +1,24 @@
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Runtime};
+
+/// Emitted with a [`BadgeCount`] whenever the badge changes, on every
+/// platform. The desktop app mirrors it onto the dock badge and tray.
+pub const BADGE_EVENT: &str = "fcm://badge";
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct BadgeCount {
+    pub count: u32,
+}
+
+/// Set the app icon badge; zero clears it. Android has no launcher badge API,
+/// so the count is attached to the notifications the plugin posts, which
+/// launchers that show counts pick up.
+pub(crate) fn set<R: Runtime>(app: &AppHandle<R>, count: u32) -> crate::Result<()> {
+    #[cfg(mobile)]
+    {
+        use crate::FcmExt;
+        app.fcm().set_badge_count(count)?;
+    }
+    let _ = app.emit(BADGE_EVENT, BadgeCount { count });
+    Ok(())
+}
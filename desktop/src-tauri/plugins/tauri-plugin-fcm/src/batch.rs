@@ -0,0 +1,73 @@
+use std::{sync::Mutex, time::Duration};
+
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+
+use crate::{messages::CachedMessage, Error};
+
+/// Emitted per received message unless batching is enabled.
+pub const MESSAGE_EVENT: &str = "fcm://message";
+/// Emitted with every message received during one batching window.
+pub const MESSAGES_BATCH_EVENT: &str = "fcm://messages-batch";
+
+/// Windows longer than this would make notifications feel late.
+const MAX_WINDOW: Duration = Duration::from_secs(10);
+
+/// Coalesces message events during bursts, such as a backlog replayed after
+/// reconnecting. Off by default; the setting is not persisted.
+#[derive(Default)]
+pub(crate) struct MessageBatching {
+    window: Mutex<Option<Duration>>,
+    pending: Mutex<Vec<CachedMessage>>,
+}
+
+impl MessageBatching {
+    fn take_pending(&self) -> Vec<CachedMessage> {
+        std::mem::take(&mut *self.pending.lock().unwrap())
+    }
+}
+
+fn flush<R: Runtime>(app: &AppHandle<R>) {
+    let batch = app.state::<MessageBatching>().take_pending();
+    if !batch.is_empty() {
+        let _ = app.emit(MESSAGES_BATCH_EVENT, batch);
+    }
+}
+
+/// Turn batching on with the given window, or off. Turning it off delivers
+/// anything still waiting right away.
+pub(crate) fn configure<R: Runtime>(
+    app: &AppHandle<R>,
+    enabled: bool,
+    window_ms: u64,
+) -> crate::Result<()> {
+    let window = Duration::from_millis(window_ms);
+    if enabled && (window.is_zero() || window > MAX_WINDOW) {
+        return Err(Error::InvalidBatchWindow(window_ms));
+    }
+    *app.state::<MessageBatching>().window.lock().unwrap() = enabled.then_some(window);
+    if !enabled {
+        flush(app);
+    }
+    Ok(())
+}
+
+/// Emit a received message now, or hold it until the current window closes.
+/// The first message of a burst opens the window.
+#[cfg_attr(not(mobile), allow(dead_code))]
+pub(crate) fn emit<R: Runtime>(app: &AppHandle<R>, message: CachedMessage) {
+    let state = app.state::<MessageBatching>();
+    let Some(window) = *state.window.lock().unwrap() else {
+        let _ = app.emit(MESSAGE_EVENT, message);
+        return;
+    };
+
+    let mut pending = state.pending.lock().unwrap();
+    pending.push(message);
+    if pending.len() == 1 {
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move {
+            tokio::time::sleep(window).await;
+            flush(&app);
+        });
+    }
+}
@@ -0,0 +1,107 @@
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Runtime};
+
+use crate::Error;
+
+/// Channels the plugin creates itself, with settings it relies on. The
+/// default channel also has per-vibration-pattern and per-sound variants.
+const BUILT_IN: [&str; 4] = ["default", "urgent", "passive", "progress"];
+const DEFAULT_VARIANT_PREFIXES: [&str; 2] = ["default_vibration_", "default_sound_"];
+
+/// Android's channel importance, which decides whether a notification makes
+/// sound and peeks onto the screen.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ChannelImportance {
+    /// Only in the shade, collapsed.
+    Min,
+    /// No sound.
+    Low,
+    #[default]
+    Default,
+    /// Sound and heads-up.
+    High,
+}
+
+/// Channel ids are sent by the server in a message's `channelId`, so keep
+/// them to characters that survive a round trip through any payload.
+pub(crate) fn validate_id(id: &str) -> crate::Result<()> {
+    let valid_char = |c: char| c.is_ascii_alphanumeric() || "-_.".contains(c);
+    if id.is_empty() || id.len() > 100 || !id.chars().all(valid_char) {
+        return Err(Error::InvalidChannel(id.to_string()));
+    }
+    let variant = DEFAULT_VARIANT_PREFIXES
+        .iter()
+        .any(|prefix| id.starts_with(prefix));
+    if BUILT_IN.contains(&id) || variant {
+        return Err(Error::InvalidChannel(format!("{id} is built in")));
+    }
+    Ok(())
+}
+
+/// Create a channel messages can name in `channelId`. Android fixes the
+/// importance and sound once a channel exists, and restores them if a deleted
+/// channel is created again, so changing them needs a new id. No-op on iOS
+/// and desktop.
+pub(crate) fn create<R: Runtime>(
+    app: &AppHandle<R>,
+    id: &str,
+    name: &str,
+    importance: ChannelImportance,
+    sound: Option<&str>,
+) -> crate::Result<()> {
+    validate_id(id)?;
+    if name.trim().is_empty() {
+        return Err(Error::InvalidChannel(format!("{id} needs a name")));
+    }
+    #[cfg(mobile)]
+    {
+        use crate::FcmExt;
+        app.fcm()
+            .create_notification_channel(id, name, importance, sound)
+    }
+    #[cfg(not(mobile))]
+    {
+        let _ = (app, name, importance, sound);
+        Ok(())
+    }
+}
+
+/// Delete a channel created with [`create`]. Returns whether it existed.
+pub(crate) fn delete<R: Runtime>(app: &AppHandle<R>, id: &str) -> crate::Result<bool> {
+    validate_id(id)?;
+    #[cfg(mobile)]
+    {
+        use crate::FcmExt;
+        app.fcm().delete_notification_channel(id)
+    }
+    #[cfg(not(mobile))]
+    {
+        let _ = app;
+        Ok(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn built_in_channels_are_off_limits() {
+        for id in [
+            "default",
+            "urgent",
+            "passive",
+            "progress",
+            "default_vibration_0_200",
+            "default_sound_chime",
+        ] {
+            assert!(validate_id(id).is_err(), "{id}");
+        }
+        for id in ["mentions", "session-updates", "system.alerts"] {
+            assert!(validate_id(id).is_ok(), "{id}");
+        }
+        assert!(validate_id("").is_err());
+        assert!(validate_id("with space").is_err());
+    }
+}
@@ -0,0 +1,128 @@
+use std::{sync::Mutex, time::UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+
+use crate::{now_ms, store};
+
+/// Emitted with a [`ClockSkew`] when the device clock is off by more than
+/// [`SKEW_THRESHOLD_MS`].
+pub const CLOCK_SKEW_EVENT: &str = "clock-skew-detected";
+
+const STORE_NAME: &str = "clock";
+
+/// Smaller offsets are noise: the `Date` header only has second resolution
+/// and the request takes time, so they are neither reported nor corrected.
+const SKEW_THRESHOLD_MS: i64 = 60_000;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ClockSkew {
+    /// Server time minus device time; positive when the device is behind.
+    pub offset_ms: i64,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct ClockConfig {
+    offset_ms: Option<i64>,
+}
+
+/// The last measured offset of the device clock from server time. Persisted
+/// so timestamps right after a restart are already corrected.
+pub(crate) struct ClockOffset(Mutex<Option<i64>>);
+
+impl ClockOffset {
+    pub(crate) fn load<R: Runtime>(app: &AppHandle<R>) -> Self {
+        Self(Mutex::new(
+            store::load::<_, ClockConfig>(app, STORE_NAME).offset_ms,
+        ))
+    }
+}
+
+/// Server time minus device time, given a `Date` header received on a
+/// request sent at `sent_ms` and answered at `received_ms` device time.
+fn measure(date: &str, sent_ms: u64, received_ms: u64) -> Option<i64> {
+    let server = httpdate::parse_http_date(date)
+        .ok()?
+        .duration_since(UNIX_EPOCH)
+        .ok()?
+        .as_millis() as i64;
+    // The header truncates to the second and the server stamped it somewhere
+    // during the round trip, so compare the middles of both
+    let local = sent_ms + received_ms.saturating_sub(sent_ms) / 2;
+    Some(server + 500 - local as i64)
+}
+
+/// `now` on the server's clock, once the offset is large enough to matter.
+fn corrected(now: u64, offset_ms: Option<i64>) -> u64 {
+    match offset_ms {
+        Some(offset) if offset.abs() >= SKEW_THRESHOLD_MS => now.saturating_add_signed(offset),
+        _ => now,
+    }
+}
+
+/// Record the offset measured from a server `Date` header and warn the
+/// frontend if the device clock is badly off.
+pub(crate) fn on_server_date<R: Runtime>(
+    app: &AppHandle<R>,
+    date: &str,
+    sent_ms: u64,
+    received_ms: u64,
+) {
+    let Some(offset_ms) = measure(date, sent_ms, received_ms) else {
+        log::debug!("FCM plugin: unparseable Date header {date:?}");
+        return;
+    };
+    *app.state::<ClockOffset>().0.lock().unwrap() = Some(offset_ms);
+    if let Err(e) = store::save(
+        app,
+        STORE_NAME,
+        &ClockConfig {
+            offset_ms: Some(offset_ms),
+        },
+    ) {
+        log::warn!("FCM plugin: failed to persist clock offset: {e}");
+    }
+    if offset_ms.abs() >= SKEW_THRESHOLD_MS {
+        log::warn!("FCM plugin: device clock is off by {offset_ms} ms");
+        let _ = app.emit(CLOCK_SKEW_EVENT, ClockSkew { offset_ms });
+    }
+}
+
+/// The last measured offset, or `None` before the first measurement.
+pub(crate) fn offset<R: Runtime>(app: &AppHandle<R>) -> Option<i64> {
+    *app.state::<ClockOffset>().0.lock().unwrap()
+}
+
+/// The current time on the server's clock as best we know it, for comparing
+/// against server timestamps such as a message's `sentTime`.
+pub(crate) fn server_now_ms<R: Runtime>(app: &AppHandle<R>) -> u64 {
+    corrected(now_ms(), offset(app))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `Sun, 06 Nov 1994 08:49:37 GMT` in ms since the Unix epoch.
+    const DATE_MS: u64 = 784_111_777_000;
+    const DATE: &str = "Sun, 06 Nov 1994 08:49:37 GMT";
+
+    #[test]
+    fn measures_offset_against_the_round_trip_midpoint() {
+        // Device is two minutes behind; the request took 200 ms
+        let sent = DATE_MS - 120_000 - 100;
+        assert_eq!(measure(DATE, sent, sent + 200), Some(120_500));
+        // Device is an hour ahead
+        let sent = DATE_MS + 3_600_000;
+        assert_eq!(measure(DATE, sent, sent), Some(-3_599_500));
+        assert_eq!(measure("yesterday", 0, 0), None);
+    }
+
+    #[test]
+    fn only_significant_offsets_are_corrected() {
+        assert_eq!(corrected(1_000_000, None), 1_000_000);
+        assert_eq!(corrected(1_000_000, Some(5_000)), 1_000_000);
+        assert_eq!(corrected(1_000_000, Some(120_000)), 1_120_000);
+        assert_eq!(corrected(1_000_000, Some(-120_000)), 880_000);
+    }
+}
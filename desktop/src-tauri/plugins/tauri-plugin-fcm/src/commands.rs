@@ -0,0 +1,950 @@
+use std::{collections::HashMap, path::PathBuf};
+
+use tauri::{command, AppHandle, Manager, Runtime};
+
+use crate::{
+    appearance, auto_init, background, badge, batch,
+    channels::{self, ChannelImportance},
+    clock, dedup, e2e,
+    encryption::{self, CacheEncryption},
+    grouping,
+    history::{self, HistoryEntry, NotificationFilter, NotificationPage, PageRequest},
+    install_id,
+    interruption::{self, InterruptionLevel},
+    messages,
+    metrics::{self, Metrics},
+    missed,
+    opened::{self, DefaultAction, NotificationActionState},
+    presentation::{self, ForegroundPresentation},
+    progress,
+    quiet_hours::{self, QuietHours},
+    scheduled,
+    senders::{self, SenderFilter, SenderMode, SenderPolicyState},
+    shutdown::{self, ShutdownState, ShutdownSummary},
+    sounds, sticky,
+    suppressed::{self, SuppressedNotification},
+    throttle,
+    topics::{self, TopicOp, TopicResult},
+    upload::{self, TokenUploadState},
+    FcmListeners, FcmProject, NotificationAppearance, PermissionStatus, PushDistributor,
+};
+
+#[command]
+pub async fn get_fcm_token<R: Runtime>(app: AppHandle<R>) -> crate::Result<Option<String>> {
+    app.state::<ShutdownState>().reset();
+    #[cfg(mobile)]
+    {
+        use crate::FcmExt;
+        app.fcm().get_token()
+    }
+    #[cfg(all(not(mobile), feature = "mock"))]
+    {
+        Ok(crate::mock::token(&app).or_else(|| crate::desktop_push::device_id(&app)))
+    }
+    #[cfg(all(not(mobile), not(feature = "mock")))]
+    {
+        Ok(crate::desktop_push::device_id(&app))
+    }
+}
+
+/// Invalidate the device token on sign-out, before clearing the server-side
+/// registration. A token still queued for upload is dropped. Returns whether
+/// there was a token to delete; always `false` on desktop.
+#[command]
+pub async fn delete_fcm_token<R: Runtime>(app: AppHandle<R>) -> crate::Result<bool> {
+    let deleted = shutdown::delete_token(&app)?;
+    upload::discard_pending(&app);
+    Ok(deleted)
+}
+
+/// Ask for notification permission. `blocked` means the user chose "don't ask
+/// again", so the frontend should send them to the system settings instead.
+#[command]
+pub async fn request_notification_permission<R: Runtime>(
+    app: AppHandle<R>,
+) -> crate::Result<PermissionStatus> {
+    #[cfg(mobile)]
+    {
+        use crate::FcmExt;
+        app.fcm().request_permission()
+    }
+    #[cfg(all(not(mobile), feature = "mock"))]
+    {
+        Ok(crate::mock::permission(&app))
+    }
+    #[cfg(all(not(mobile), not(feature = "mock")))]
+    {
+        let _ = app;
+        Ok(PermissionStatus::Granted)
+    }
+}
+
+/// Whether `request_notification_permission` would show the system prompt.
+#[command]
+pub async fn can_request_notification_permission<R: Runtime>(
+    app: AppHandle<R>,
+) -> crate::Result<bool> {
+    #[cfg(mobile)]
+    {
+        use crate::FcmExt;
+        app.fcm().can_request_permission()
+    }
+    #[cfg(all(not(mobile), feature = "mock"))]
+    {
+        Ok(crate::mock::permission(&app) == PermissionStatus::Requested)
+    }
+    #[cfg(all(not(mobile), not(feature = "mock")))]
+    {
+        let _ = app;
+        Ok(false)
+    }
+}
+
+/// Send the user to the system notification settings, the only way left to
+/// grant a `blocked` permission. Not available on desktop.
+#[command]
+pub async fn open_notification_settings<R: Runtime>(app: AppHandle<R>) -> crate::Result<()> {
+    #[cfg(mobile)]
+    {
+        use crate::FcmExt;
+        app.fcm().open_notification_settings()
+    }
+    #[cfg(not(mobile))]
+    {
+        let _ = app;
+        Err(crate::Error::NotAvailable)
+    }
+}
+
+#[command]
+pub async fn is_notification_permission_granted<R: Runtime>(
+    app: AppHandle<R>,
+) -> crate::Result<bool> {
+    #[cfg(mobile)]
+    {
+        use crate::FcmExt;
+        app.fcm().is_permission_granted()
+    }
+    #[cfg(all(not(mobile), feature = "mock"))]
+    {
+        Ok(crate::mock::permission(&app) == PermissionStatus::Granted)
+    }
+    #[cfg(all(not(mobile), not(feature = "mock")))]
+    {
+        let _ = app;
+        Ok(true)
+    }
+}
+
+/// Subscribe to a single topic, so the server can reach every device
+/// subscribed to it without tracking their tokens.
+#[command]
+pub async fn subscribe_to_topic<R: Runtime>(app: AppHandle<R>, topic: String) -> crate::Result<()> {
+    topics::apply_one(&app, topic, TopicOp::Subscribe).await
+}
+
+#[command]
+pub async fn unsubscribe_from_topic<R: Runtime>(
+    app: AppHandle<R>,
+    topic: String,
+) -> crate::Result<()> {
+    topics::apply_one(&app, topic, TopicOp::Unsubscribe).await
+}
+
+/// Subscribe to several topics at once. Partial failures are reported per topic.
+#[command]
+pub async fn subscribe_to_topics<R: Runtime>(
+    app: AppHandle<R>,
+    topics: Vec<String>,
+) -> crate::Result<Vec<TopicResult>> {
+    Ok(topics::apply(&app, topics, TopicOp::Subscribe).await)
+}
+
+/// Unsubscribe from several topics at once. Partial failures are reported per topic.
+#[command]
+pub async fn unsubscribe_from_topics<R: Runtime>(
+    app: AppHandle<R>,
+    topics: Vec<String>,
+) -> crate::Result<Vec<TopicResult>> {
+    Ok(topics::apply(&app, topics, TopicOp::Unsubscribe).await)
+}
+
+/// Topics the client believes it is subscribed to. FCM offers no authoritative
+/// read of a device's subscriptions, so this is the locally tracked set.
+#[command]
+pub async fn get_subscribed_topics<R: Runtime>(app: AppHandle<R>) -> crate::Result<Vec<String>> {
+    Ok(app.state::<topics::Topics>().snapshot())
+}
+
+/// Unsubscribe from every tracked topic, e.g. when switching accounts.
+#[command]
+pub async fn clear_all_topic_subscriptions<R: Runtime>(
+    app: AppHandle<R>,
+) -> crate::Result<Vec<TopicResult>> {
+    let tracked = app.state::<topics::Topics>().snapshot();
+    Ok(topics::apply(&app, tracked, TopicOp::Unsubscribe).await)
+}
+
+/// Prompt for a store review via the native in-app review flow (mobile only).
+#[command]
+pub async fn request_in_app_review<R: Runtime>(app: AppHandle<R>) -> crate::Result<bool> {
+    #[cfg(mobile)]
+    {
+        crate::review::request(&app)
+    }
+    #[cfg(not(mobile))]
+    {
+        let _ = app;
+        Err(crate::Error::NotAvailable)
+    }
+}
+
+/// Configure where refreshed tokens are uploaded. Headers are kept in memory only.
+#[command]
+pub async fn configure_token_upload<R: Runtime>(
+    app: AppHandle<R>,
+    endpoint: String,
+    headers: Option<HashMap<String, String>>,
+) -> crate::Result<()> {
+    app.state::<ShutdownState>().reset();
+    upload::configure(&app, endpoint, headers)
+}
+
+/// Replace the token-upload headers (e.g. a rotated bearer token) without
+/// reconfiguring the endpoint. The next attempt uses the new headers.
+#[command]
+pub async fn set_token_upload_headers<R: Runtime>(
+    app: AppHandle<R>,
+    headers: HashMap<String, String>,
+) -> crate::Result<()> {
+    upload::set_headers(&app, headers)
+}
+
+/// Stop all FCM activity on logout and report what was torn down. Idempotent.
+#[command]
+pub async fn fcm_shutdown<R: Runtime>(app: AppHandle<R>) -> crate::Result<ShutdownSummary> {
+    Ok(shutdown::shutdown(&app).await)
+}
+
+/// What tapping a notification without its own target does.
+#[command]
+pub async fn get_default_notification_action<R: Runtime>(
+    app: AppHandle<R>,
+) -> crate::Result<DefaultAction> {
+    Ok(app.state::<NotificationActionState>().get())
+}
+
+/// Persist what tapping a notification without its own target does.
+#[command]
+pub async fn set_default_notification_action<R: Runtime>(
+    app: AppHandle<R>,
+    action: DefaultAction,
+) -> crate::Result<()> {
+    opened::set_default(&app, action)
+}
+
+/// Set the Android notification small icon (drawable resource name) and accent
+/// color (`#RRGGBB`). Omitting the icon keeps the current one. Ignored on iOS
+/// and desktop.
+#[command]
+pub async fn configure_notification_appearance<R: Runtime>(
+    app: AppHandle<R>,
+    small_icon: Option<String>,
+    accent_color: String,
+) -> crate::Result<()> {
+    if let Some(small_icon) = &small_icon {
+        appearance::validate_icon(small_icon)?;
+    }
+    appearance::validate_color(&accent_color)?;
+    #[cfg(mobile)]
+    {
+        use crate::FcmExt;
+        app.fcm()
+            .configure_notification_appearance(small_icon, accent_color)
+    }
+    #[cfg(not(mobile))]
+    {
+        let _ = app;
+        Ok(())
+    }
+}
+
+/// Re-emit `events` that fired before the webview attached their listeners
+/// (token refresh, notification opened). Call right after attaching them;
+/// each pending payload is emitted once, and later ones arrive directly.
+#[command]
+pub async fn replay_sticky_events<R: Runtime>(
+    app: AppHandle<R>,
+    events: Vec<String>,
+) -> crate::Result<Vec<String>> {
+    Ok(sticky::replay(&app, &events))
+}
+
+/// Round-trip latency to the native plugin in milliseconds, to tell a slow
+/// native bridge apart from a slow network. `None` without a native plugin.
+#[command]
+pub async fn fcm_ping<R: Runtime>(app: AppHandle<R>) -> crate::Result<Option<f64>> {
+    #[cfg(mobile)]
+    {
+        use crate::FcmExt;
+        app.fcm()
+            .ping()
+            .map(|elapsed| elapsed.map(|d| d.as_secs_f64() * 1000.0))
+    }
+    #[cfg(not(mobile))]
+    {
+        let _ = app;
+        Ok(None)
+    }
+}
+
+/// Hold token uploads (e.g. during backend maintenance). The queued token is kept.
+#[command]
+pub async fn pause_token_upload<R: Runtime>(app: AppHandle<R>) -> crate::Result<()> {
+    upload::pause(&app);
+    Ok(())
+}
+
+/// Resume token uploads with a fresh backoff.
+#[command]
+pub async fn resume_token_upload<R: Runtime>(app: AppHandle<R>) -> crate::Result<()> {
+    upload::resume(&app);
+    Ok(())
+}
+
+#[command]
+pub async fn get_token_upload_state<R: Runtime>(
+    app: AppHandle<R>,
+) -> crate::Result<TokenUploadState> {
+    Ok(upload::state(&app))
+}
+
+/// Write the received-message cache to a JSON file for debugging and return its
+/// path. With `redact`, bodies and token/PII-looking data values are masked.
+#[command]
+pub async fn export_message_cache<R: Runtime>(
+    app: AppHandle<R>,
+    redact: Option<bool>,
+) -> crate::Result<PathBuf> {
+    messages::export(&app, redact.unwrap_or(true))
+}
+
+#[command]
+pub async fn clear_message_cache<R: Runtime>(app: AppHandle<R>) -> crate::Result<()> {
+    messages::clear(&app)
+}
+
+/// Seconds since the last push message arrived, or `None` if none ever has.
+#[command]
+pub async fn get_last_message_age<R: Runtime>(app: AppHandle<R>) -> crate::Result<Option<u64>> {
+    Ok(throttle::last_message_age(&app))
+}
+
+/// How long without a message (in seconds) before `possible-throttling` is emitted.
+#[command]
+pub async fn set_throttling_threshold<R: Runtime>(
+    app: AppHandle<R>,
+    seconds: u64,
+) -> crate::Result<()> {
+    throttle::set_threshold(&app, seconds);
+    Ok(())
+}
+
+#[command]
+pub async fn get_sender_policy<R: Runtime>(app: AppHandle<R>) -> crate::Result<SenderPolicyState> {
+    Ok(app.state::<SenderFilter>().state())
+}
+
+/// Restrict which `sender_id`s may deliver messages. Blocked messages are
+/// dropped before they are cached or emitted and are counted in
+/// `get_sender_policy`.
+#[command]
+pub async fn set_sender_policy<R: Runtime>(
+    app: AppHandle<R>,
+    mode: SenderMode,
+    ids: Vec<String>,
+) -> crate::Result<()> {
+    senders::set_policy(&app, mode, ids)
+}
+
+/// Coalesce `fcm://message` events into one `fcm://messages-batch` event per
+/// `window_ms` (default 500) while enabled, for accounts that receive bursts.
+#[command]
+pub async fn set_message_batching<R: Runtime>(
+    app: AppHandle<R>,
+    enabled: bool,
+    window_ms: Option<u64>,
+) -> crate::Result<()> {
+    batch::configure(&app, enabled, window_ms.unwrap_or(500))
+}
+
+/// Whether the OS was grouping this app's notifications, refreshed on resume.
+/// `None` on platforms that can't tell.
+#[command]
+pub async fn notifications_are_grouped<R: Runtime>(
+    app: AppHandle<R>,
+) -> crate::Result<Option<bool>> {
+    Ok(grouping::get(&app))
+}
+
+/// Show a notification only if the app is backgrounded within `ttl_ms`, e.g.
+/// to remind the user of unsaved work. `group_key` overrides the group taken
+/// from the payload. Returns an id for cancelling it.
+#[command]
+pub async fn notify_on_background<R: Runtime>(
+    app: AppHandle<R>,
+    title: String,
+    body: String,
+    payload: Option<HashMap<String, String>>,
+    group_key: Option<String>,
+    ttl_ms: u64,
+) -> crate::Result<String> {
+    background::queue(
+        &app,
+        title,
+        body,
+        payload.unwrap_or_default(),
+        group_key,
+        ttl_ms,
+    )
+}
+
+/// Returns whether the notification was still pending.
+#[command]
+pub async fn cancel_background_notification<R: Runtime>(
+    app: AppHandle<R>,
+    id: String,
+) -> crate::Result<bool> {
+    background::cancel(&app, &id)
+}
+
+/// The saved auto-init choice, or `None` if it was never set.
+#[command]
+pub async fn get_fcm_auto_init_enabled<R: Runtime>(
+    app: AppHandle<R>,
+) -> crate::Result<Option<bool>> {
+    Ok(auto_init::get(&app))
+}
+
+/// Gate token generation on consent: while disabled, FCM creates no token
+/// until `get_fcm_token` asks for one. Persisted; a no-op on iOS and desktop.
+#[command]
+pub async fn set_fcm_auto_init_enabled<R: Runtime>(
+    app: AppHandle<R>,
+    enabled: bool,
+) -> crate::Result<()> {
+    auto_init::set(&app, enabled)
+}
+
+/// Messages delivered from the backlog (sent before the app last came to the
+/// foreground) since the count was last cleared.
+#[command]
+pub async fn get_missed_count<R: Runtime>(app: AppHandle<R>) -> crate::Result<u64> {
+    Ok(missed::count(&app))
+}
+
+/// Reset the missed count once the user has seen those messages.
+#[command]
+pub async fn clear_missed_count<R: Runtime>(app: AppHandle<R>) -> crate::Result<()> {
+    missed::clear(&app);
+    Ok(())
+}
+
+/// Register the native plugin again, for when calls into it keep failing.
+/// This also happens on its own after a few consecutive failures.
+#[command]
+pub async fn reinitialize_fcm<R: Runtime>(app: AppHandle<R>) -> crate::Result<()> {
+    #[cfg(mobile)]
+    {
+        use crate::FcmExt;
+        app.fcm().reinitialize()
+    }
+    #[cfg(not(mobile))]
+    {
+        let _ = app;
+        Err(crate::Error::NotAvailable)
+    }
+}
+
+/// Local usage counters and whether counting is on. Never sent anywhere.
+#[command]
+pub async fn get_metrics<R: Runtime>(app: AppHandle<R>) -> crate::Result<Metrics> {
+    Ok(metrics::get(&app))
+}
+
+#[command]
+pub async fn reset_metrics<R: Runtime>(app: AppHandle<R>) -> crate::Result<()> {
+    metrics::reset(&app)
+}
+
+/// Opt in to local usage counting (off by default). Opting out discards the counts.
+#[command]
+pub async fn set_metrics_enabled<R: Runtime>(
+    app: AppHandle<R>,
+    enabled: bool,
+) -> crate::Result<()> {
+    metrics::set_enabled(&app, enabled)
+}
+
+/// Count a frontend event such as `session_opened`, if metrics are enabled.
+#[command]
+pub async fn record_metric<R: Runtime>(app: AppHandle<R>, name: String) -> crate::Result<()> {
+    metrics::record(&app, &name)
+}
+
+/// Once a session has been read in the app, remove its notifications from the
+/// shade (which also lowers the launcher badge on Android) and drop any queued
+/// for when the app is backgrounded. Returns how many were removed. Desktop
+/// notifications can't be withdrawn once shown.
+#[command]
+pub async fn clear_notifications_for_session<R: Runtime>(
+    app: AppHandle<R>,
+    session_id: String,
+) -> crate::Result<u32> {
+    let queued = background::cancel_for_session(&app, &session_id)?;
+    #[cfg(mobile)]
+    {
+        use crate::FcmExt;
+        let shown = app.fcm().clear_notifications_for_session(&session_id)?;
+        Ok(queued + shown)
+    }
+    #[cfg(not(mobile))]
+    {
+        Ok(queued)
+    }
+}
+
+/// Vibration pattern (alternating off/on ms) for notifications the app shows
+/// itself on Android; empty disables vibration. Android fixes a channel's
+/// vibration when the channel is created, so each pattern gets a fresh default
+/// channel, and channels named by the server keep their own. No-op on iOS and desktop.
+#[command]
+pub async fn set_vibration_pattern<R: Runtime>(
+    app: AppHandle<R>,
+    pattern: Vec<u64>,
+) -> crate::Result<()> {
+    appearance::validate_vibration_pattern(&pattern)?;
+    #[cfg(mobile)]
+    {
+        use crate::FcmExt;
+        app.fcm().set_vibration_pattern(pattern)
+    }
+    #[cfg(not(mobile))]
+    {
+        let _ = app;
+        Ok(())
+    }
+}
+
+/// Random per-install identifier, generated on first run and persisted, for
+/// keying device records independently of the FCM token.
+#[command]
+pub async fn get_install_id<R: Runtime>(app: AppHandle<R>) -> crate::Result<String> {
+    Ok(install_id::get(&app))
+}
+
+/// Regenerate the install id, e.g. on logout. On mobile this also replaces the
+/// FCM token and queues the new one for upload. Returns the new id.
+#[command]
+pub async fn reset_install_id<R: Runtime>(app: AppHandle<R>) -> crate::Result<String> {
+    install_id::reset(&app)
+}
+
+/// Show an ongoing notification with a progress bar (Android), e.g. for a
+/// sync. Every platform also gets `fcm://progress`, which the desktop app
+/// shows in the tray tooltip. Showing an existing id replaces it.
+#[command]
+pub async fn show_progress_notification<R: Runtime>(
+    app: AppHandle<R>,
+    id: String,
+    title: String,
+    progress: u8,
+    indeterminate: bool,
+) -> crate::Result<()> {
+    progress::show(&app, id, title, progress, indeterminate)
+}
+
+#[command]
+pub async fn update_progress_notification<R: Runtime>(
+    app: AppHandle<R>,
+    id: String,
+    progress: u8,
+    indeterminate: bool,
+) -> crate::Result<()> {
+    progress::update(&app, id, progress, indeterminate)
+}
+
+/// End a progress notification, leaving `message` in its place if given.
+#[command]
+pub async fn complete_progress_notification<R: Runtime>(
+    app: AppHandle<R>,
+    id: String,
+    message: Option<String>,
+) -> crate::Result<()> {
+    progress::complete(&app, id, message)
+}
+
+/// The configured notification icon, accent color and vibration, with the
+/// default channel, its sound and the grouping state, for a preview that
+/// mirrors what the OS will show. Only grouping is known on desktop.
+#[command]
+pub async fn get_notification_appearance<R: Runtime>(
+    app: AppHandle<R>,
+) -> crate::Result<NotificationAppearance> {
+    appearance::get(&app)
+}
+
+/// Notifications delivered to the app, newest first, with whether each was
+/// tapped or dismissed. Kept across restarts, up to the last 1000.
+#[command]
+pub async fn get_notification_history<R: Runtime>(
+    app: AppHandle<R>,
+    limit: Option<usize>,
+) -> crate::Result<Vec<HistoryEntry>> {
+    history::get(&app, limit)
+}
+
+#[command]
+pub async fn clear_notification_history<R: Runtime>(app: AppHandle<R>) -> crate::Result<()> {
+    history::clear(&app)
+}
+
+/// The FCM sender and project id tokens are issued for, to diagnose tokens
+/// going to the wrong project. `None` on desktop and iOS.
+#[command]
+pub async fn get_fcm_sender_id<R: Runtime>(app: AppHandle<R>) -> crate::Result<Option<FcmProject>> {
+    #[cfg(mobile)]
+    {
+        use crate::FcmExt;
+        app.fcm().get_sender_id()
+    }
+    #[cfg(not(mobile))]
+    {
+        let _ = app;
+        Ok(None)
+    }
+}
+
+/// The payload field notifications are grouped by when a message has no
+/// `groupKey` of its own.
+#[command]
+pub async fn get_default_group_key_field<R: Runtime>(app: AppHandle<R>) -> crate::Result<String> {
+    Ok(grouping::default_field(&app))
+}
+
+/// Group notifications by another payload field than `sessionId`, for apps
+/// whose grouping isn't per session. Messages without the field still fall
+/// back to `sessionId`, then to a shared "default" group.
+#[command]
+pub async fn set_default_group_key_field<R: Runtime>(
+    app: AppHandle<R>,
+    field: String,
+) -> crate::Result<()> {
+    grouping::set_default_field(&app, field)
+}
+
+/// Why recent messages were dropped before reaching the user, newest first.
+#[command]
+pub async fn get_suppressed_notifications<R: Runtime>(
+    app: AppHandle<R>,
+    limit: Option<usize>,
+) -> crate::Result<Vec<SuppressedNotification>> {
+    Ok(suppressed::get(&app, limit))
+}
+
+#[command]
+pub async fn get_default_interruption_level<R: Runtime>(
+    app: AppHandle<R>,
+) -> crate::Result<InterruptionLevel> {
+    Ok(interruption::default_level(&app))
+}
+
+/// How far notifications may break through Focus or Do Not Disturb, unless a
+/// message sets its own `interruptionLevel`. Ignored on desktop.
+#[command]
+pub async fn set_default_interruption_level<R: Runtime>(
+    app: AppHandle<R>,
+    level: InterruptionLevel,
+) -> crate::Result<()> {
+    interruption::set_default_level(&app, level)
+}
+
+/// How far the device clock is behind server time in ms (negative: ahead), as
+/// last measured from the upload endpoint. `None` until it has answered once.
+#[command]
+pub async fn get_clock_offset<R: Runtime>(app: AppHandle<R>) -> crate::Result<Option<i64>> {
+    Ok(clock::offset(&app))
+}
+
+/// The one call the frontend makes once its listeners are attached: hooks up
+/// the native event channel and replays the sticky events among
+/// `sticky_events` it now listens for. Others stay pending until their own
+/// listener asks. Safe to call again, e.g. on resume; the channel is replaced
+/// and only pending events replay.
+#[command]
+pub async fn init_fcm_listeners<R: Runtime>(
+    app: AppHandle<R>,
+    sticky_events: Option<Vec<String>>,
+) -> crate::Result<FcmListeners> {
+    #[cfg(mobile)]
+    let native = {
+        use crate::FcmExt;
+        app.fcm().register_event_handler(&app)?
+    };
+    #[cfg(not(mobile))]
+    let native = false;
+
+    Ok(FcmListeners {
+        token_listener: native,
+        message_listener: native,
+        opened_listener: native,
+        lifecycle: native || cfg!(desktop),
+        replayed: sticky::replay(&app, &sticky_events.unwrap_or_default()),
+    })
+}
+
+#[command]
+pub async fn get_cache_encryption<R: Runtime>(app: AppHandle<R>) -> crate::Result<CacheEncryption> {
+    Ok(encryption::config(&app))
+}
+
+/// Encrypt the message cache, suppression log and the notification history's
+/// titles, bodies and data at rest with a key kept in the OS keychain (the
+/// Android Keystore on mobile) under `key_source`. The existing contents are
+/// rewritten in the new format.
+#[command]
+pub async fn set_cache_encryption<R: Runtime>(
+    app: AppHandle<R>,
+    enabled: bool,
+    key_source: Option<String>,
+) -> crate::Result<()> {
+    encryption::configure(&app, enabled, key_source)
+}
+
+#[command]
+pub async fn get_foreground_presentation_options<R: Runtime>(
+    app: AppHandle<R>,
+) -> crate::Result<ForegroundPresentation> {
+    Ok(presentation::get(&app))
+}
+
+/// Whether pushes arriving while the app is open also post a system
+/// notification, on top of the `fcm://message` event. Ignored on desktop.
+#[command]
+pub async fn set_foreground_presentation_options<R: Runtime>(
+    app: AppHandle<R>,
+    options: ForegroundPresentation,
+) -> crate::Result<()> {
+    presentation::set(&app, options)
+}
+
+/// Create an Android notification channel, e.g. separate ones for mentions
+/// and session updates, which messages select with `channelId`. `sound` names
+/// a raw resource; `None` uses the system default and `""` makes it silent.
+/// No-op on iOS and desktop.
+#[command]
+pub async fn create_notification_channel<R: Runtime>(
+    app: AppHandle<R>,
+    id: String,
+    name: String,
+    importance: Option<ChannelImportance>,
+    sound: Option<String>,
+) -> crate::Result<()> {
+    channels::create(
+        &app,
+        &id,
+        &name,
+        importance.unwrap_or_default(),
+        sound.as_deref(),
+    )
+}
+
+#[command]
+pub async fn delete_notification_channel<R: Runtime>(
+    app: AppHandle<R>,
+    id: String,
+) -> crate::Result<bool> {
+    channels::delete(&app, &id)
+}
+
+/// Show `count` on the app icon: the launcher badge on Android, the dock badge
+/// on macOS. Zero clears it.
+#[command]
+pub async fn set_badge_count<R: Runtime>(app: AppHandle<R>, count: u32) -> crate::Result<()> {
+    badge::set(&app, count)
+}
+
+#[command]
+pub async fn clear_badge<R: Runtime>(app: AppHandle<R>) -> crate::Result<()> {
+    badge::set(&app, 0)
+}
+
+/// The WebSocket endpoint desktop reads pushes from; always `None` on mobile.
+#[command]
+pub async fn get_desktop_push_endpoint<R: Runtime>(
+    app: AppHandle<R>,
+) -> crate::Result<Option<String>> {
+    #[cfg(desktop)]
+    {
+        Ok(crate::desktop_push::endpoint(&app))
+    }
+    #[cfg(not(desktop))]
+    {
+        let _ = app;
+        Ok(None)
+    }
+}
+
+/// Point desktop at a `ws://` or `wss://` endpoint that delivers pushes as
+/// `fcm://message` events, or clear it with `None`. Once set,
+/// `get_fcm_token` returns this device's id instead of `None`.
+#[command]
+pub async fn set_desktop_push_endpoint<R: Runtime>(
+    app: AppHandle<R>,
+    endpoint: Option<String>,
+) -> crate::Result<()> {
+    #[cfg(desktop)]
+    {
+        crate::desktop_push::configure(&app, endpoint)
+    }
+    #[cfg(not(desktop))]
+    {
+        let _ = (app, endpoint);
+        Err(crate::Error::NotAvailable)
+    }
+}
+
+#[command]
+pub async fn get_push_distributor<R: Runtime>(app: AppHandle<R>) -> crate::Result<PushDistributor> {
+    #[cfg(mobile)]
+    {
+        use crate::FcmExt;
+        app.fcm().get_push_distributor()
+    }
+    #[cfg(not(mobile))]
+    {
+        let _ = app;
+        Ok(PushDistributor::Fcm)
+    }
+}
+
+/// Receive pushes through UnifiedPush (or, with the `hms` feature, HMS Push
+/// Kit) instead of FCM, or back. Android only; listen for
+/// `fcm://token-refreshed` to learn the new token.
+#[command]
+pub async fn set_push_distributor<R: Runtime>(
+    app: AppHandle<R>,
+    distributor: PushDistributor,
+) -> crate::Result<()> {
+    #[cfg(mobile)]
+    {
+        use crate::FcmExt;
+        app.fcm().set_push_distributor(distributor)
+    }
+    #[cfg(not(mobile))]
+    {
+        let _ = (app, distributor);
+        Err(crate::Error::NotAvailable)
+    }
+}
+
+#[command]
+pub async fn get_quiet_hours<R: Runtime>(app: AppHandle<R>) -> crate::Result<Option<QuietHours>> {
+    Ok(quiet_hours::get(&app))
+}
+
+/// Hold back non-critical notifications from `start` to `end` (`HH:MM`,
+/// local time) on the given `days` (0 for Sunday), then deliver them as one
+/// digest and `fcm://quiet-hours-digest`. Notifications Firebase displays
+/// itself, from a message's notification payload, can't be held back.
+#[command]
+pub async fn set_quiet_hours<R: Runtime>(
+    app: AppHandle<R>,
+    start: String,
+    end: String,
+    days: Vec<u8>,
+) -> crate::Result<()> {
+    quiet_hours::set(&app, Some(QuietHours { start, end, days }))
+}
+
+#[command]
+pub async fn clear_quiet_hours<R: Runtime>(app: AppHandle<R>) -> crate::Result<()> {
+    quiet_hours::set(&app, None)
+}
+
+/// Drop a message seen within the last `seconds`, matched by message id, so
+/// redeliveries after a reconnect show once. 0 turns
+/// this off; defaults to an hour.
+#[command]
+pub async fn set_dedup_ttl<R: Runtime>(app: AppHandle<R>, seconds: u64) -> crate::Result<()> {
+    dedup::set_ttl(&app, seconds)
+}
+
+/// The device's X25519 public key, base64, generated on first use. Servers
+/// seal notification content to it and send it as an `enc` field, which is
+/// decrypted before message events are emitted.
+#[command]
+pub async fn get_push_public_key<R: Runtime>(app: AppHandle<R>) -> crate::Result<String> {
+    e2e::public_key(&app)
+}
+
+/// Post a notification at `at` (ms since the Unix epoch), even if the
+/// webview is idle. On Android it also fires if the app was killed; on
+/// desktop it waits for the next launch. Returns an id for `cancel_scheduled`.
+#[command]
+pub async fn schedule_notification<R: Runtime>(
+    app: AppHandle<R>,
+    at: u64,
+    title: String,
+    body: String,
+    data: Option<HashMap<String, String>>,
+) -> crate::Result<String> {
+    scheduled::schedule(&app, at, title, body, data.unwrap_or_default())
+}
+
+/// How many notifications may be pending at once (`max`, null where
+/// unbounded) and how many are, so callers can warn before
+/// `schedule_notification` starts rejecting with `SCHEDULE_LIMIT_REACHED`.
+#[command]
+pub async fn get_scheduled_limits<R: Runtime>(
+    app: AppHandle<R>,
+) -> crate::Result<scheduled::ScheduledLimits> {
+    scheduled::limits(&app)
+}
+
+/// Returns whether the notification was still scheduled.
+#[command]
+pub async fn cancel_scheduled<R: Runtime>(app: AppHandle<R>, id: String) -> crate::Result<bool> {
+    scheduled::cancel(&app, &id)
+}
+
+/// One page of the notification history, push and local, newest first, for
+/// an in-app notification center. `page` defaults to the first 50.
+#[command]
+pub async fn list_notifications<R: Runtime>(
+    app: AppHandle<R>,
+    filter: Option<NotificationFilter>,
+    page: Option<PageRequest>,
+) -> crate::Result<NotificationPage> {
+    history::list(&app, &filter.unwrap_or_default(), page.unwrap_or_default())
+}
+
+/// Returns whether the notification was unread.
+#[command]
+pub async fn mark_read<R: Runtime>(app: AppHandle<R>, id: String) -> crate::Result<bool> {
+    history::mark_read(&app, &id)
+}
+
+/// Empty the notification history, like `clear_notification_history`.
+#[command]
+pub async fn clear_all<R: Runtime>(app: AppHandle<R>) -> crate::Result<()> {
+    history::clear(&app)
+}
+
+/// Sound names a message or scheduled notification can set in its `sound`
+/// data field, e.g. a distinct cue for sessions waiting on input. Android
+/// lists the app's raw resources; desktop lists what the OS plays by name.
+#[command]
+pub async fn get_available_sounds<R: Runtime>(app: AppHandle<R>) -> crate::Result<Vec<String>> {
+    sounds::available(&app)
+}
@@ -0,0 +1,151 @@
+use std::{collections::VecDeque, sync::Mutex, time::Duration};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::{now_ms, store};
+
+const STORE_NAME: &str = "dedup";
+
+/// How many recent messages are remembered; the oldest go first.
+const CAPACITY: usize = 200;
+const DEFAULT_TTL: Duration = Duration::from_secs(60 * 60);
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct SeenMessage {
+    key: String,
+    seen_at: u64,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+struct DedupConfig {
+    ttl_secs: u64,
+    /// Oldest first.
+    seen: VecDeque<SeenMessage>,
+}
+
+impl Default for DedupConfig {
+    fn default() -> Self {
+        Self {
+            ttl_secs: DEFAULT_TTL.as_secs(),
+            seen: VecDeque::new(),
+        }
+    }
+}
+
+/// Messages seen recently, so the burst of redeliveries that follows a
+/// reconnect reaches the user once. Persisted, since the burst often
+/// arrives right after a restart.
+pub(crate) struct Dedup(Mutex<DedupConfig>);
+
+impl Dedup {
+    pub(crate) fn load<R: Runtime>(app: &AppHandle<R>) -> Self {
+        Self(Mutex::new(store::load(app, STORE_NAME)))
+    }
+}
+
+/// What identifies a redelivery: the message id. The collapse key is not
+/// part of it, since a newer message under the same collapse key replaces
+/// the earlier notification rather than repeating it, and Android fills in
+/// the package name when the sender sets none.
+fn key(payload: &Value) -> Option<String> {
+    payload
+        .get("messageId")
+        .and_then(Value::as_str)
+        .map(|id| format!("id:{id}"))
+}
+
+/// Forget entries older than `ttl_ms`, then report whether `key` is still
+/// remembered, remembering it if not.
+fn check(seen: &mut VecDeque<SeenMessage>, key: String, now: u64, ttl_ms: u64) -> bool {
+    seen.retain(|entry| entry.seen_at.saturating_add(ttl_ms) > now);
+    if seen.iter().any(|entry| entry.key == key) {
+        return true;
+    }
+    seen.push_back(SeenMessage { key, seen_at: now });
+    while seen.len() > CAPACITY {
+        seen.pop_front();
+    }
+    false
+}
+
+/// Whether the same logical message was already seen within the TTL.
+/// Messages without an id are never duplicates.
+pub(crate) fn is_duplicate<R: Runtime>(app: &AppHandle<R>, payload: &Value) -> bool {
+    let Some(key) = key(payload) else {
+        return false;
+    };
+    let state = app.state::<Dedup>();
+    let mut config = state.0.lock().unwrap();
+    if config.ttl_secs == 0 {
+        return false;
+    }
+    let ttl_ms = config.ttl_secs.saturating_mul(1000);
+    let duplicate = check(&mut config.seen, key, now_ms(), ttl_ms);
+    if !duplicate {
+        if let Err(e) = store::save(app, STORE_NAME, &*config) {
+            log::warn!("FCM plugin: failed to persist recent messages: {e}");
+        }
+    }
+    duplicate
+}
+
+/// How long a message is remembered; 0 turns de-duplication off. Handed to
+/// the native side too, which drops duplicates before displaying them.
+pub(crate) fn set_ttl<R: Runtime>(app: &AppHandle<R>, seconds: u64) -> crate::Result<()> {
+    #[cfg(mobile)]
+    {
+        use crate::FcmExt;
+        app.fcm().set_dedup_ttl(seconds)?;
+    }
+    let state = app.state::<Dedup>();
+    let mut config = state.0.lock().unwrap();
+    config.ttl_secs = seconds;
+    store::save(app, STORE_NAME, &*config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn keyed_by_message_id_only() {
+        let payload = json!({ "messageId": "m1", "collapseKey": "session-1" });
+        assert_eq!(key(&payload).as_deref(), Some("id:m1"));
+        assert_eq!(key(&json!({ "collapseKey": "session-1" })), None);
+        assert_eq!(key(&json!({ "title": "Hi" })), None);
+    }
+
+    #[test]
+    fn messages_sharing_a_collapse_key_are_not_duplicates() {
+        let mut seen = VecDeque::new();
+        let first = json!({ "messageId": "m1", "collapseKey": "com.example" });
+        let second = json!({ "messageId": "m2", "collapseKey": "com.example" });
+        assert!(!check(&mut seen, key(&first).unwrap(), 1_000, 500));
+        assert!(!check(&mut seen, key(&second).unwrap(), 1_100, 500));
+        assert!(check(&mut seen, key(&first).unwrap(), 1_200, 500));
+    }
+
+    #[test]
+    fn repeats_within_the_ttl_are_duplicates() {
+        let mut seen = VecDeque::new();
+        assert!(!check(&mut seen, "id:a".into(), 1_000, 500));
+        assert!(check(&mut seen, "id:a".into(), 1_400, 500));
+        assert!(!check(&mut seen, "id:b".into(), 1_400, 500));
+        // Past the TTL it shows again
+        assert!(!check(&mut seen, "id:a".into(), 1_600, 500));
+    }
+
+    #[test]
+    fn oldest_entries_are_evicted_past_capacity() {
+        let mut seen = VecDeque::new();
+        for i in 0..=CAPACITY {
+            check(&mut seen, format!("id:{i}"), 0, u64::MAX / 2);
+        }
+        assert_eq!(seen.len(), CAPACITY);
+        assert!(!check(&mut seen, "id:0".into(), 0, u64::MAX / 2));
+    }
+}
@@ -0,0 +1,151 @@
+use std::{sync::Mutex, time::Duration};
+
+use futures_util::{SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tauri::{async_runtime::JoinHandle, AppHandle, Manager, Runtime, Url};
+use tokio_tungstenite::{connect_async, tungstenite::Message};
+
+use crate::{install_id, messages, store, token, Error};
+
+const STORE_NAME: &str = "desktop_push";
+
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct DesktopPushConfig {
+    /// `ws://` or `wss://` URL pushes are read from; `None` leaves desktop
+    /// without remote notifications.
+    endpoint: Option<String>,
+}
+
+/// Desktop stand-in for FCM: a WebSocket the server writes messages to.
+/// After connecting the client sends `{"type": "register", "deviceId": ..}`;
+/// every text frame after that is a message shaped like the native ones
+/// (`messageId`, `collapseKey`, `title`, `body`, `data`, `sentTime`). A
+/// server that sends WebPush today can relay the same payloads over this
+/// socket.
+pub(crate) struct DesktopPush {
+    config: Mutex<DesktopPushConfig>,
+    connection: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl DesktopPush {
+    pub(crate) fn load<R: Runtime>(app: &AppHandle<R>) -> Self {
+        Self {
+            config: Mutex::new(store::load(app, STORE_NAME)),
+            connection: Mutex::new(None),
+        }
+    }
+}
+
+/// Stands in for the FCM token so backends can key registrations the same
+/// way; follows the install id, so resetting that yields a new one. `None`
+/// while no endpoint is configured.
+pub(crate) fn device_id<R: Runtime>(app: &AppHandle<R>) -> Option<String> {
+    endpoint(app).map(|_| format!("desktop:{}", install_id::get(app)))
+}
+
+pub(crate) fn endpoint<R: Runtime>(app: &AppHandle<R>) -> Option<String> {
+    app.state::<DesktopPush>()
+        .config
+        .lock()
+        .unwrap()
+        .endpoint
+        .clone()
+}
+
+/// Set (or with `None`, clear) the endpoint, persist it, and reconnect.
+pub(crate) fn configure<R: Runtime>(
+    app: &AppHandle<R>,
+    endpoint: Option<String>,
+) -> crate::Result<()> {
+    let endpoint = endpoint.filter(|e| !e.is_empty());
+    if let Some(endpoint) = &endpoint {
+        let url: Url = endpoint
+            .parse()
+            .map_err(|_| Error::InvalidEndpoint(endpoint.clone()))?;
+        if !matches!(url.scheme(), "ws" | "wss") {
+            return Err(Error::InvalidEndpoint(endpoint.clone()));
+        }
+    }
+
+    let config = DesktopPushConfig { endpoint };
+    store::save(app, STORE_NAME, &config)?;
+    *app.state::<DesktopPush>().config.lock().unwrap() = config;
+    connect(app);
+    Ok(())
+}
+
+/// Start the connection for the configured endpoint, replacing any open one.
+pub(crate) fn connect<R: Runtime>(app: &AppHandle<R>) {
+    let state = app.state::<DesktopPush>();
+    let mut connection = state.connection.lock().unwrap();
+    if let Some(previous) = connection.take() {
+        previous.abort();
+    }
+    if let Some(endpoint) = state.config.lock().unwrap().endpoint.clone() {
+        *connection = Some(tauri::async_runtime::spawn(run(app.clone(), endpoint)));
+    }
+}
+
+/// Stay connected, reconnecting with exponential backoff. A connection that
+/// got as far as registering resets the backoff.
+async fn run<R: Runtime>(app: AppHandle<R>, endpoint: String) {
+    let mut backoff = INITIAL_BACKOFF;
+    loop {
+        match session(&app, &endpoint).await {
+            Ok(()) => {
+                log::info!("FCM plugin: desktop push connection closed");
+                backoff = INITIAL_BACKOFF;
+            }
+            Err(e) => log::warn!("FCM plugin: desktop push connection failed: {e}"),
+        }
+        tokio::time::sleep(backoff).await;
+        backoff = next_backoff(backoff);
+    }
+}
+
+fn next_backoff(backoff: Duration) -> Duration {
+    (backoff * 2).min(MAX_BACKOFF)
+}
+
+async fn session<R: Runtime>(app: &AppHandle<R>, endpoint: &str) -> crate::Result<()> {
+    let transport = |e: tokio_tungstenite::tungstenite::Error| Error::PushConnection(e.to_string());
+    let (mut socket, _) = connect_async(endpoint).await.map_err(transport)?;
+    let Some(device_id) = device_id(app) else {
+        return Ok(());
+    };
+    let register = json!({ "type": "register", "deviceId": device_id });
+    socket
+        .send(Message::Text(register.to_string()))
+        .await
+        .map_err(transport)?;
+    // Goes through the same debounce and upload queue as a native token
+    token::on_refreshed(app, device_id);
+
+    while let Some(frame) = socket.next().await {
+        match frame.map_err(transport)? {
+            Message::Text(text) => match serde_json::from_str::<Value>(&text) {
+                Ok(payload) => messages::record(app, &payload),
+                Err(e) => log::warn!("FCM plugin: ignoring malformed desktop push: {e}"),
+            },
+            Message::Close(_) => break,
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_doubles_up_to_the_cap() {
+        assert_eq!(next_backoff(INITIAL_BACKOFF), Duration::from_secs(2));
+        assert_eq!(next_backoff(Duration::from_secs(40)), MAX_BACKOFF);
+        assert_eq!(next_backoff(MAX_BACKOFF), MAX_BACKOFF);
+    }
+}
@@ -0,0 +1,209 @@
+use std::sync::Mutex;
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chacha20poly1305::{
+    aead::{Aead, KeyInit},
+    Key, XChaCha20Poly1305, XNonce,
+};
+use hkdf::Hkdf;
+use serde_json::{Map, Value};
+use sha2::Sha256;
+use tauri::{AppHandle, Manager, Runtime};
+use x25519_dalek::{PublicKey, StaticSecret};
+
+use crate::{encryption, Error};
+
+/// Where the device's private key is kept, next to the cache key: the OS
+/// keychain on desktop, wrapped by the Keystore on Android.
+const KEY_SOURCE: &str = "fcm-push-key";
+
+/// The field carrying a sealed payload, in `data` or at the top level.
+const ENC_KEY: &str = "enc";
+
+/// Binds derived keys to this scheme, so a sender can't be tricked into
+/// reusing one elsewhere.
+const INFO: &[u8] = b"companion-push-v1";
+
+const PUBLIC_KEY_LEN: usize = 32;
+const NONCE_LEN: usize = 24;
+
+/// The device's X25519 secret, once fetched. Never written to the store.
+#[derive(Default)]
+pub(crate) struct PushKey(Mutex<Option<StaticSecret>>);
+
+/// The device secret, generating and storing one first if there is none and
+/// `create` is set.
+fn secret<R: Runtime>(app: &AppHandle<R>, create: bool) -> crate::Result<StaticSecret> {
+    let state = app.state::<PushKey>();
+    let mut secret = state.0.lock().unwrap();
+    if let Some(secret) = &*secret {
+        return Ok(secret.clone());
+    }
+    let key = encryption::fetch_key(app, KEY_SOURCE, create)?
+        .ok_or_else(|| Error::PushKeyUnavailable(KEY_SOURCE.to_string()))?;
+    let mut bytes = [0; 32];
+    bytes.copy_from_slice(&key);
+    let fetched = StaticSecret::from(bytes);
+    *secret = Some(fetched.clone());
+    Ok(fetched)
+}
+
+/// The device's public key, base64, for the server to seal payloads to.
+pub(crate) fn public_key<R: Runtime>(app: &AppHandle<R>) -> crate::Result<String> {
+    let secret = secret(app, true)?;
+    Ok(STANDARD.encode(PublicKey::from(&secret).as_bytes()))
+}
+
+fn derive_key(shared: &[u8], ephemeral: &PublicKey, recipient: &PublicKey) -> Key {
+    let mut salt = ephemeral.as_bytes().to_vec();
+    salt.extend_from_slice(recipient.as_bytes());
+    let mut key = Key::default();
+    Hkdf::<Sha256>::new(Some(&salt), shared)
+        .expand(INFO, &mut key)
+        .expect("32 bytes is a valid HKDF-SHA256 output length");
+    key
+}
+
+/// Open `ephemeral public key (32) || nonce (24) || XChaCha20-Poly1305
+/// ciphertext`, keyed by HKDF-SHA256 over the X25519 shared secret. `None`
+/// if it is truncated or sealed to another key.
+fn open(secret: &StaticSecret, sealed: &[u8]) -> Option<Vec<u8>> {
+    if sealed.len() < PUBLIC_KEY_LEN + NONCE_LEN {
+        return None;
+    }
+    let (ephemeral, rest) = sealed.split_at(PUBLIC_KEY_LEN);
+    let (nonce, ciphertext) = rest.split_at(NONCE_LEN);
+    let ephemeral = PublicKey::from(<[u8; PUBLIC_KEY_LEN]>::try_from(ephemeral).ok()?);
+    let shared = secret.diffie_hellman(&ephemeral);
+    let key = derive_key(shared.as_bytes(), &ephemeral, &PublicKey::from(secret));
+    XChaCha20Poly1305::new(&key)
+        .decrypt(XNonce::from_slice(nonce), ciphertext)
+        .ok()
+}
+
+/// Remove the sealed field from the payload, preferring the one in `data`.
+fn take_enc(payload: &mut Value) -> Option<String> {
+    let from_data = payload
+        .get_mut("data")
+        .and_then(Value::as_object_mut)
+        .and_then(|data| data.remove(ENC_KEY));
+    let enc = match from_data {
+        Some(enc) => enc,
+        None => payload.as_object_mut()?.remove(ENC_KEY)?,
+    };
+    enc.as_str().map(str::to_string)
+}
+
+/// Apply the decrypted fields: `title` and `body` replace the payload's and
+/// `data` entries are added to its data.
+fn merge(payload: &mut Value, fields: Map<String, Value>) {
+    let Some(payload) = payload.as_object_mut() else {
+        return;
+    };
+    for (key, value) in fields {
+        match (key.as_str(), value) {
+            ("title" | "body", value @ Value::String(_)) => {
+                payload.insert(key.clone(), value);
+            }
+            ("data", Value::Object(data)) => {
+                let target = payload
+                    .entry("data")
+                    .or_insert_with(|| Value::Object(Map::new()));
+                if let Some(target) = target.as_object_mut() {
+                    target.extend(data);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// The payload with its `enc` field replaced by what it decrypts to, a JSON
+/// object with any of `title`, `body` and `data`. One that can't be opened
+/// is passed on without the field.
+pub(crate) fn decrypt<R: Runtime>(app: &AppHandle<R>, payload: &Value) -> Value {
+    let mut payload = payload.clone();
+    let Some(enc) = take_enc(&mut payload) else {
+        return payload;
+    };
+    let fields = secret(app, false).ok().and_then(|secret| {
+        let sealed = STANDARD.decode(enc).ok()?;
+        serde_json::from_slice::<Map<String, Value>>(&open(&secret, &sealed)?).ok()
+    });
+    match fields {
+        Some(fields) => merge(&mut payload, fields),
+        None => log::warn!("FCM plugin: could not decrypt an encrypted payload"),
+    }
+    payload
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chacha20poly1305::aead::{AeadCore, OsRng};
+    use serde_json::json;
+
+    /// What a sender does with the device's public key.
+    fn seal(recipient: &PublicKey, plaintext: &[u8]) -> Vec<u8> {
+        let ephemeral = StaticSecret::random_from_rng(OsRng);
+        let ephemeral_public = PublicKey::from(&ephemeral);
+        let shared = ephemeral.diffie_hellman(recipient);
+        let key = derive_key(shared.as_bytes(), &ephemeral_public, recipient);
+        let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = XChaCha20Poly1305::new(&key)
+            .encrypt(&nonce, plaintext)
+            .unwrap();
+        let mut sealed = ephemeral_public.as_bytes().to_vec();
+        sealed.extend_from_slice(&nonce);
+        sealed.extend(ciphertext);
+        sealed
+    }
+
+    #[test]
+    fn sealed_payload_opens_only_with_its_key() {
+        let secret = StaticSecret::random_from_rng(OsRng);
+        let sealed = seal(&PublicKey::from(&secret), b"{\"title\":\"hi\"}");
+        assert_eq!(open(&secret, &sealed).unwrap(), b"{\"title\":\"hi\"}");
+
+        let other = StaticSecret::random_from_rng(OsRng);
+        assert_eq!(open(&other, &sealed), None);
+        assert_eq!(
+            open(&secret, &sealed[..PUBLIC_KEY_LEN + NONCE_LEN - 1]),
+            None
+        );
+    }
+
+    #[test]
+    fn decrypted_fields_replace_the_sealed_one() {
+        let mut payload = json!({
+            "messageId": "m1",
+            "title": "New message",
+            "data": { "enc": "c2VhbGVk", "sessionId": "abc" },
+        });
+        assert_eq!(take_enc(&mut payload).as_deref(), Some("c2VhbGVk"));
+
+        let fields = json!({
+            "title": "Waiting for input",
+            "body": "Deploy to prod?",
+            "data": { "type": "waiting_for_input" },
+        });
+        merge(&mut payload, fields.as_object().cloned().unwrap());
+        assert_eq!(
+            payload,
+            json!({
+                "messageId": "m1",
+                "title": "Waiting for input",
+                "body": "Deploy to prod?",
+                "data": { "sessionId": "abc", "type": "waiting_for_input" },
+            })
+        );
+    }
+
+    #[test]
+    fn top_level_enc_is_read_when_data_has_none() {
+        let mut payload = json!({ "enc": "c2VhbGVk", "data": {} });
+        assert_eq!(take_enc(&mut payload).as_deref(), Some("c2VhbGVk"));
+        assert_eq!(payload, json!({ "data": {} }));
+        assert_eq!(take_enc(&mut payload), None);
+    }
+}
@@ -0,0 +1,328 @@
+use std::sync::Mutex;
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chacha20poly1305::{
+    aead::{Aead, AeadCore, KeyInit, OsRng},
+    Key, XChaCha20Poly1305, XNonce,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::{history, messages, store, suppressed, Error};
+
+const STORE_NAME: &str = "cache_encryption";
+
+/// Encrypted stores sit next to the plain ones under this extension.
+const SEALED_EXTENSION: &str = "sealed";
+
+/// Keychain entry used when `key_source` isn't set.
+const DEFAULT_KEY_SOURCE: &str = "fcm-cache";
+
+const NONCE_LEN: usize = 24;
+
+/// Marks a single value sealed by [`FieldCipher`].
+const SEALED_FIELD_PREFIX: &str = "sealed:";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CacheEncryption {
+    pub enabled: bool,
+    /// Name of the keychain entry holding the key; `None` for the default.
+    pub key_source: Option<String>,
+}
+
+/// Whether the message cache, suppression log and notification history are
+/// encrypted at rest, and their key once fetched. The key itself is never
+/// written to the store.
+pub(crate) struct CacheEncryptionState {
+    config: Mutex<CacheEncryption>,
+    key: Mutex<Option<Key>>,
+}
+
+impl CacheEncryptionState {
+    pub(crate) fn load<R: Runtime>(app: &AppHandle<R>) -> Self {
+        Self {
+            config: Mutex::new(store::load(app, STORE_NAME)),
+            key: Mutex::new(None),
+        }
+    }
+}
+
+fn seal(key: &Key, plaintext: &[u8]) -> crate::Result<Vec<u8>> {
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext = XChaCha20Poly1305::new(key)
+        .encrypt(&nonce, plaintext)
+        .map_err(|e| Error::Storage(e.to_string()))?;
+    let mut sealed = nonce.to_vec();
+    sealed.extend(ciphertext);
+    Ok(sealed)
+}
+
+/// `None` if the data is truncated or was sealed with another key.
+fn open(key: &Key, sealed: &[u8]) -> Option<Vec<u8>> {
+    if sealed.len() < NONCE_LEN {
+        return None;
+    }
+    let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+    XChaCha20Poly1305::new(key)
+        .decrypt(XNonce::from_slice(nonce), ciphertext)
+        .ok()
+}
+
+/// Seals single values for a store that keeps its own format, such as the
+/// notification history's database columns. Without a key, i.e. with
+/// encryption off, values pass through as they are.
+#[derive(Default)]
+pub(crate) struct FieldCipher(Option<Key>);
+
+impl FieldCipher {
+    #[cfg(test)]
+    pub(crate) fn generate() -> Self {
+        Self(Some(XChaCha20Poly1305::generate_key(&mut OsRng)))
+    }
+
+    pub(crate) fn seal(&self, plain: String) -> crate::Result<String> {
+        let Some(key) = &self.0 else {
+            return Ok(plain);
+        };
+        let sealed = seal(key, plain.as_bytes())?;
+        Ok(format!("{SEALED_FIELD_PREFIX}{}", STANDARD.encode(sealed)))
+    }
+
+    /// A sealed value that doesn't open, e.g. after its key was lost, reads
+    /// as empty. Values stored in the clear are returned as they are.
+    pub(crate) fn open(&self, stored: String) -> String {
+        let Some(sealed) = stored.strip_prefix(SEALED_FIELD_PREFIX) else {
+            return stored;
+        };
+        self.0
+            .as_ref()
+            .zip(STANDARD.decode(sealed).ok())
+            .and_then(|(key, sealed)| open(key, &sealed))
+            .and_then(|plain| String::from_utf8(plain).ok())
+            .unwrap_or_default()
+    }
+}
+
+/// Fetch the key kept under `source` in the OS keychain, generating and
+/// storing one first if there is none and `create` is set.
+#[cfg(desktop)]
+pub(crate) fn fetch_key<R: Runtime>(
+    app: &AppHandle<R>,
+    source: &str,
+    create: bool,
+) -> crate::Result<Option<Key>> {
+    let unavailable = |e: keyring::Error| Error::CacheKeyUnavailable(e.to_string());
+    let entry = keyring::Entry::new(&app.config().identifier, source).map_err(unavailable)?;
+    match entry.get_secret() {
+        Ok(secret) if secret.len() == 32 => Ok(Some(*Key::from_slice(&secret))),
+        Ok(_) => Err(Error::CacheKeyUnavailable(format!(
+            "keychain entry {source} is not a cache key"
+        ))),
+        Err(keyring::Error::NoEntry) if create => {
+            let key = XChaCha20Poly1305::generate_key(&mut OsRng);
+            entry.set_secret(&key).map_err(unavailable)?;
+            Ok(Some(key))
+        }
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(unavailable(e)),
+    }
+}
+
+/// Android keeps the key wrapped by the Keystore; there is no iOS side yet,
+/// so the key is always missing there.
+#[cfg(mobile)]
+pub(crate) fn fetch_key<R: Runtime>(
+    app: &AppHandle<R>,
+    source: &str,
+    create: bool,
+) -> crate::Result<Option<Key>> {
+    // Not managed yet while the caches load during setup; `reload` catches up
+    let Some(fcm) = app.try_state::<crate::Fcm<R>>() else {
+        return Ok(None);
+    };
+    let key = fcm
+        .get_cache_key(source, create)?
+        .and_then(|hex| from_hex(&hex))
+        .filter(|key| key.len() == 32)
+        .map(|key| *Key::from_slice(&key));
+    Ok(key)
+}
+
+#[cfg_attr(not(mobile), allow(dead_code))]
+fn from_hex(hex: &str) -> Option<Vec<u8>> {
+    if hex.len() % 2 != 0 {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+/// The cached key, fetching it from the configured source on first use.
+fn key<R: Runtime>(app: &AppHandle<R>, create: bool) -> Option<Key> {
+    let state = app.state::<CacheEncryptionState>();
+    let mut key = state.key.lock().unwrap();
+    if key.is_none() {
+        let source = state.config.lock().unwrap().key_source.clone();
+        match fetch_key(app, source.as_deref().unwrap_or(DEFAULT_KEY_SOURCE), create) {
+            Ok(fetched) => *key = fetched,
+            Err(e) => log::warn!("FCM plugin: cache key unavailable: {e}"),
+        }
+    }
+    *key
+}
+
+fn is_enabled<R: Runtime>(app: &AppHandle<R>) -> bool {
+    app.state::<CacheEncryptionState>()
+        .config
+        .lock()
+        .unwrap()
+        .enabled
+}
+
+/// Load a cache store, decrypting it when encryption is on. A missing key or
+/// a file that doesn't decrypt leaves the cache empty rather than failing.
+pub(crate) fn load<R: Runtime, T: DeserializeOwned + Default>(app: &AppHandle<R>, name: &str) -> T {
+    if !is_enabled(app) {
+        return store::load(app, name);
+    }
+    let Some(sealed) = store::read(app, name, SEALED_EXTENSION) else {
+        return T::default();
+    };
+    let Some(key) = key(app, false) else {
+        log::warn!("FCM plugin: no key for encrypted {name}, starting empty");
+        return T::default();
+    };
+    match open(&key, &sealed).and_then(|plain| serde_json::from_slice(&plain).ok()) {
+        Some(value) => value,
+        None => {
+            log::warn!("FCM plugin: could not decrypt {name}, starting empty");
+            T::default()
+        }
+    }
+}
+
+/// The cipher for single values under the current setting. Fails if
+/// encryption is on but its key is unavailable, so nothing is written in
+/// the clear by mistake.
+pub(crate) fn field_cipher<R: Runtime>(app: &AppHandle<R>) -> crate::Result<FieldCipher> {
+    if !is_enabled(app) {
+        return Ok(FieldCipher(None));
+    }
+    key(app, true)
+        .map(|key| FieldCipher(Some(key)))
+        .ok_or_else(|| Error::CacheKeyUnavailable("notification history".to_string()))
+}
+
+/// Persist a cache store, encrypted when encryption is on, and remove any
+/// copy left in the other format.
+pub(crate) fn save<R: Runtime, T: Serialize>(
+    app: &AppHandle<R>,
+    name: &str,
+    value: &T,
+) -> crate::Result<()> {
+    if !is_enabled(app) {
+        store::save(app, name, value)?;
+        store::remove(app, name, SEALED_EXTENSION);
+        return Ok(());
+    }
+    let key = key(app, true).ok_or_else(|| Error::CacheKeyUnavailable(name.to_string()))?;
+    let plain = serde_json::to_vec(value).map_err(|e| Error::Storage(e.to_string()))?;
+    store::write(app, name, SEALED_EXTENSION, &seal(&key, &plain)?)?;
+    store::remove(app, name, "json");
+    Ok(())
+}
+
+pub(crate) fn config<R: Runtime>(app: &AppHandle<R>) -> CacheEncryption {
+    app.state::<CacheEncryptionState>()
+        .config
+        .lock()
+        .unwrap()
+        .clone()
+}
+
+/// Turn encryption on or off and rewrite the caches and the notification
+/// history in the new format. Enabling fails if the key source can't provide
+/// a key, leaving them as they were.
+pub(crate) fn configure<R: Runtime>(
+    app: &AppHandle<R>,
+    enabled: bool,
+    key_source: Option<String>,
+) -> crate::Result<()> {
+    let key_source = key_source.filter(|s| !s.is_empty());
+    let key = if enabled {
+        let source = key_source.as_deref().unwrap_or(DEFAULT_KEY_SOURCE);
+        Some(
+            fetch_key(app, source, true)?
+                .ok_or_else(|| Error::CacheKeyUnavailable(source.to_string()))?,
+        )
+    } else {
+        None
+    };
+
+    // Opened with the current key, which the new setting may replace
+    let history = history::open_all(app)?;
+
+    let config = CacheEncryption {
+        enabled,
+        key_source,
+    };
+    store::save(app, STORE_NAME, &config)?;
+    let state = app.state::<CacheEncryptionState>();
+    *state.config.lock().unwrap() = config;
+    *state.key.lock().unwrap() = key;
+
+    messages::rewrite(app)?;
+    suppressed::rewrite(app)?;
+    history::reseal(app, history)
+}
+
+/// Re-read both caches now that the native side can hand out the key. They
+/// loaded empty during setup, before it could.
+#[cfg(mobile)]
+pub(crate) fn reload<R: Runtime>(app: &AppHandle<R>) {
+    if is_enabled(app) {
+        messages::reload(app);
+        suppressed::reload(app);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sealed_data_opens_only_with_its_key() {
+        let key = XChaCha20Poly1305::generate_key(&mut OsRng);
+        let sealed = seal(&key, b"[{\"title\":\"hi\"}]").unwrap();
+        assert_eq!(open(&key, &sealed).unwrap(), b"[{\"title\":\"hi\"}]");
+
+        let other = XChaCha20Poly1305::generate_key(&mut OsRng);
+        assert_eq!(open(&other, &sealed), None);
+        assert_eq!(open(&key, &sealed[..NONCE_LEN - 1]), None);
+    }
+
+    #[test]
+    fn fields_are_sealed_only_with_a_key() {
+        let plain = FieldCipher::default();
+        assert_eq!(plain.seal("hi".to_string()).unwrap(), "hi");
+
+        let cipher = FieldCipher::generate();
+        let sealed = cipher.seal("hi".to_string()).unwrap();
+        assert!(sealed.starts_with(SEALED_FIELD_PREFIX) && !sealed.contains("hi"));
+        assert_eq!(cipher.open(sealed.clone()), "hi");
+        assert_eq!(cipher.open("in the clear".to_string()), "in the clear");
+
+        let other = FieldCipher::generate();
+        assert_eq!(other.open(sealed.clone()), "");
+        assert_eq!(plain.open(sealed), "");
+    }
+
+    #[test]
+    fn parses_hex_keys() {
+        assert_eq!(from_hex("00ff7a"), Some(vec![0x00, 0xff, 0x7a]));
+        assert_eq!(from_hex("0ff"), None);
+        assert_eq!(from_hex("zz"), None);
+    }
+}
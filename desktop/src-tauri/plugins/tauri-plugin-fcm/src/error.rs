@@ -1,4 +1,7 @@
-use serde::{ser::Serializer, Serialize};
+use serde::{
+    ser::{SerializeStruct, Serializer},
+    Serialize,
+};
 
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
@@ -10,20 +13,139 @@ pub enum Error {
     TokenError(String),
     #[error("Plugin error: {0}")]
     PluginInvoke(String),
+    #[error("Invalid topic name: {0}")]
+    InvalidTopic(String),
+    #[error("Storage error: {0}")]
+    Storage(String),
+    #[error("Invalid upload endpoint: {0}")]
+    InvalidEndpoint(String),
+    #[error("Invalid header: {0}")]
+    InvalidHeader(String),
+    #[error("Invalid route: {0}")]
+    InvalidRoute(String),
+    #[error("Invalid color: {0} (expected #RRGGBB or #AARRGGBB)")]
+    InvalidColor(String),
+    #[error("Invalid notification icon: {0}")]
+    InvalidIcon(String),
+    #[error("Invalid batching window: {0} ms (expected 1 to 10000)")]
+    InvalidBatchWindow(u64),
+    #[error("Invalid metric name: {0}")]
+    InvalidMetric(String),
+    #[error("Invalid vibration pattern: {0}")]
+    InvalidVibrationPattern(String),
+    #[error("Invalid progress: {0} (expected 0 to 100)")]
+    InvalidProgress(u8),
+    #[error("No progress notification with id {0}")]
+    UnknownProgressNotification(String),
+    #[error("Invalid group key field: {0}")]
+    InvalidGroupKeyField(String),
+    #[error("Invalid notification channel: {0}")]
+    InvalidChannel(String),
+    #[error("Cache encryption key unavailable: {0}")]
+    CacheKeyUnavailable(String),
+    #[error("Push connection error: {0}")]
+    PushConnection(String),
+    #[error("Invalid quiet hours: {0}")]
+    InvalidQuietHours(String),
+    #[error("Push payload key unavailable: {0}")]
+    PushKeyUnavailable(String),
+    #[error("Too many scheduled notifications (at most {0} may be pending)")]
+    ScheduleLimitReached(u32),
 }
 
 #[cfg(mobile)]
 impl From<tauri::plugin::mobile::PluginInvokeError> for Error {
     fn from(e: tauri::plugin::mobile::PluginInvokeError) -> Self {
-        Error::PluginInvoke(e.to_string())
+        match e {
+            tauri::plugin::mobile::PluginInvokeError::Rejected(response) => {
+                let message = response.message.unwrap_or_default();
+                Error::from_native(response.code.as_deref(), message)
+            }
+            e => Error::PluginInvoke(e.to_string()),
+        }
     }
 }
 
+impl Error {
+    /// The error for a native rejection, by the code the native side passed
+    /// to `invoke.reject`. Unrecognized or missing codes stay `PluginInvoke`.
+    #[cfg_attr(not(mobile), allow(dead_code))]
+    pub(crate) fn from_native(code: Option<&str>, message: String) -> Self {
+        match code {
+            Some("PERMISSION_DENIED") => Error::PermissionDenied,
+            Some("TOKEN_ERROR") => Error::TokenError(message),
+            _ => Error::PluginInvoke(message),
+        }
+    }
+
+    /// Stable identifier for the frontend to match on; the message may change.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Error::NotAvailable => "NOT_AVAILABLE",
+            Error::PermissionDenied => "PERMISSION_DENIED",
+            Error::TokenError(_) => "TOKEN_ERROR",
+            Error::PluginInvoke(_) => "PLUGIN_INVOKE",
+            Error::InvalidTopic(_) => "INVALID_TOPIC",
+            Error::Storage(_) => "STORAGE",
+            Error::InvalidEndpoint(_) => "INVALID_ENDPOINT",
+            Error::InvalidHeader(_) => "INVALID_HEADER",
+            Error::InvalidRoute(_) => "INVALID_ROUTE",
+            Error::InvalidColor(_) => "INVALID_COLOR",
+            Error::InvalidIcon(_) => "INVALID_ICON",
+            Error::InvalidBatchWindow(_) => "INVALID_BATCH_WINDOW",
+            Error::InvalidMetric(_) => "INVALID_METRIC",
+            Error::InvalidVibrationPattern(_) => "INVALID_VIBRATION_PATTERN",
+            Error::InvalidProgress(_) => "INVALID_PROGRESS",
+            Error::UnknownProgressNotification(_) => "UNKNOWN_PROGRESS_NOTIFICATION",
+            Error::InvalidGroupKeyField(_) => "INVALID_GROUP_KEY_FIELD",
+            Error::InvalidChannel(_) => "INVALID_CHANNEL",
+            Error::CacheKeyUnavailable(_) => "CACHE_KEY_UNAVAILABLE",
+            Error::PushConnection(_) => "PUSH_CONNECTION",
+            Error::InvalidQuietHours(_) => "INVALID_QUIET_HOURS",
+            Error::PushKeyUnavailable(_) => "PUSH_KEY_UNAVAILABLE",
+            Error::ScheduleLimitReached(_) => "SCHEDULE_LIMIT_REACHED",
+        }
+    }
+}
+
+/// Commands reject with `{ code, message }`.
 impl Serialize for Error {
     fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
     where
         S: Serializer,
     {
-        serializer.serialize_str(self.to_string().as_ref())
+        let mut error = serializer.serialize_struct("Error", 2)?;
+        error.serialize_field("code", self.code())?;
+        error.serialize_field("message", &self.to_string())?;
+        error.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serializes_code_and_message() {
+        let value = serde_json::to_value(Error::PermissionDenied).unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!({
+                "code": "PERMISSION_DENIED",
+                "message": "Notification permission denied",
+            })
+        );
+    }
+
+    #[test]
+    fn maps_native_rejection_codes() {
+        let native = |code| Error::from_native(code, "boom".to_string());
+        assert!(matches!(
+            native(Some("PERMISSION_DENIED")),
+            Error::PermissionDenied
+        ));
+        assert!(matches!(native(Some("TOKEN_ERROR")), Error::TokenError(m) if m == "boom"));
+        assert!(matches!(native(Some("OTHER")), Error::PluginInvoke(_)));
+        assert!(matches!(native(None), Error::PluginInvoke(m) if m == "boom"));
     }
 }
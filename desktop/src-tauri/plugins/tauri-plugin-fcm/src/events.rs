@@ -0,0 +1,50 @@
+use serde::Deserialize;
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::{
+    actions,
+    history::{self, NotificationState},
+    lifecycle, messages, opened,
+    shutdown::ShutdownState,
+    silent, suppressed, token,
+};
+
+/// An event forwarded from the native plugin over the event-handler channel.
+#[derive(Debug, Deserialize)]
+pub(crate) struct NativeEvent {
+    pub event: String,
+    #[serde(default)]
+    pub payload: serde_json::Value,
+}
+
+/// Route a native event to the subsystem that owns it. Runs on the thread the
+/// native side sent from, so handlers must not block on further plugin calls.
+pub(crate) fn dispatch<R: Runtime>(app: &AppHandle<R>, event: NativeEvent) {
+    match event.event.as_str() {
+        "paused" => lifecycle::on_paused(app),
+        "resumed" => lifecycle::on_resumed(app),
+        "tokenRefresh" if app.state::<ShutdownState>().is_shut_down() => {
+            log::debug!("FCM plugin: ignoring token refresh after shutdown");
+        }
+        "tokenRefresh" => match event.payload.get("token").and_then(|t| t.as_str()) {
+            Some(value) => token::on_refreshed(app, value.to_string()),
+            None => log::warn!("FCM plugin: tokenRefresh event without a token"),
+        },
+        "messageReceived" => messages::record(app, &event.payload),
+        "notificationOpened" => {
+            if let Some(id) = event.payload.get("id").and_then(|id| id.as_str()) {
+                history::mark(app, id, NotificationState::Tapped);
+            }
+            let data = event.payload.get("data").and_then(|d| d.as_object());
+            opened::on_opened(app, data.cloned().unwrap_or_default());
+        }
+        "notificationDismissed" => match event.payload.get("id").and_then(|id| id.as_str()) {
+            Some(id) => history::mark(app, id, NotificationState::Dismissed),
+            None => log::warn!("FCM plugin: notificationDismissed event without an id"),
+        },
+        "notificationSuppressed" => suppressed::on_native(app, &event.payload),
+        "notificationAction" => actions::on_native(app, &event.payload),
+        "backgroundMessages" => silent::on_native(app, &event.payload),
+        other => log::debug!("FCM plugin: unhandled native event {other}"),
+    }
+}
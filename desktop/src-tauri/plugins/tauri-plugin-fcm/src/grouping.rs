@@ -0,0 +1,164 @@
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::{store, Error};
+
+const STORE_NAME: &str = "group_key";
+
+/// Payload key that overrides the group key for a single message.
+pub(crate) const GROUP_KEY: &str = "groupKey";
+/// The same under the name APNs uses for threads, for servers that send one
+/// payload to both platforms.
+pub(crate) const THREAD_KEY: &str = "threadId";
+/// Field grouped by unless configured otherwise, and the fallback after it.
+pub(crate) const SESSION_FIELD: &str = "sessionId";
+/// Group for messages that name none of the above.
+pub(crate) const FALLBACK_GROUP: &str = "default";
+const MAX_FIELD_LEN: usize = 64;
+
+/// Whether the OS was bundling this app's notifications into a group, as of
+/// the last resume. Outer `None` means it hasn't been queried yet; inner
+/// `None` means the platform can't tell.
+#[derive(Default)]
+pub(crate) struct NotificationGrouping(Mutex<Option<Option<bool>>>);
+
+/// Query the native side and cache the answer. Blocks on the native call.
+#[cfg(mobile)]
+pub(crate) fn refresh<R: Runtime>(app: &AppHandle<R>) -> Option<bool> {
+    use crate::FcmExt;
+    let grouped = app.fcm().notifications_are_grouped().unwrap_or_else(|e| {
+        log::warn!("FCM plugin: could not check notification grouping: {e}");
+        None
+    });
+    *app.state::<NotificationGrouping>().0.lock().unwrap() = Some(grouped);
+    grouped
+}
+
+/// The cached answer, queried on first use. Grouped notifications are
+/// cleared together, which explains "I tapped one but they all cleared".
+pub(crate) fn get<R: Runtime>(app: &AppHandle<R>) -> Option<bool> {
+    let cached = *app.state::<NotificationGrouping>().0.lock().unwrap();
+    match cached {
+        Some(grouped) => grouped,
+        #[cfg(mobile)]
+        None => refresh(app),
+        #[cfg(not(mobile))]
+        None => None,
+    }
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct GroupKeyConfig {
+    /// `None` groups by [`SESSION_FIELD`].
+    field: Option<String>,
+}
+
+/// The payload field notifications are grouped by.
+pub(crate) fn default_field<R: Runtime>(app: &AppHandle<R>) -> String {
+    store::load::<_, GroupKeyConfig>(app, STORE_NAME)
+        .field
+        .unwrap_or_else(|| SESSION_FIELD.to_string())
+}
+
+/// Persist which payload field groups notifications and hand it to the native
+/// side, which groups pushes that arrive while the app isn't running.
+pub(crate) fn set_default_field<R: Runtime>(
+    app: &AppHandle<R>,
+    field: String,
+) -> crate::Result<()> {
+    validate_field(&field)?;
+    #[cfg(mobile)]
+    {
+        use crate::FcmExt;
+        app.fcm().set_group_key_field(&field)?;
+    }
+    store::save(app, STORE_NAME, &GroupKeyConfig { field: Some(field) })
+}
+
+fn validate_field(field: &str) -> crate::Result<()> {
+    let valid = !field.is_empty()
+        && field.len() <= MAX_FIELD_LEN
+        && field
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'));
+    if valid {
+        Ok(())
+    } else {
+        Err(Error::InvalidGroupKeyField(field.to_string()))
+    }
+}
+
+/// The key a notification is grouped under: an explicit `group_key`, then the
+/// payload's [`GROUP_KEY`] or [`THREAD_KEY`], the configured `field`, [`SESSION_FIELD`], and
+/// finally [`FALLBACK_GROUP`]. Empty values are skipped. Mirrored by
+/// `LocalNotifications.groupKey` on Android.
+#[cfg_attr(not(mobile), allow(dead_code))]
+pub(crate) fn resolve_key<'a>(
+    group_key: Option<&'a str>,
+    field: &str,
+    lookup: impl Fn(&str) -> Option<&'a str>,
+) -> String {
+    group_key
+        .into_iter()
+        .chain(
+            [GROUP_KEY, THREAD_KEY, field, SESSION_FIELD]
+                .into_iter()
+                .filter_map(&lookup),
+        )
+        .find(|key| !key.is_empty())
+        .unwrap_or(FALLBACK_GROUP)
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn payload(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+
+    fn resolve(group_key: Option<&str>, field: &str, data: &HashMap<String, String>) -> String {
+        resolve_key(group_key, field, |key| data.get(key).map(String::as_str))
+    }
+
+    #[test]
+    fn group_key_precedence() {
+        let data = payload(&[
+            ("groupKey", "explicit-in-payload"),
+            ("project", "companion"),
+            ("sessionId", "abc"),
+        ]);
+        assert_eq!(resolve(Some("argument"), "project", &data), "argument");
+        assert_eq!(resolve(None, "project", &data), "explicit-in-payload");
+
+        let data = payload(&[("threadId", "thread"), ("sessionId", "abc")]);
+        assert_eq!(resolve(None, "project", &data), "thread");
+
+        let data = payload(&[("project", "companion"), ("sessionId", "abc")]);
+        assert_eq!(resolve(None, "project", &data), "companion");
+        assert_eq!(resolve(None, "missing", &data), "abc");
+        assert_eq!(resolve(None, "missing", &payload(&[])), "default");
+    }
+
+    #[test]
+    fn empty_values_fall_through() {
+        let data = payload(&[("groupKey", ""), ("project", ""), ("sessionId", "abc")]);
+        assert_eq!(resolve(Some(""), "project", &data), "abc");
+    }
+
+    #[test]
+    fn field_names_are_validated() {
+        assert!(validate_field("sessionId").is_ok());
+        assert!(validate_field("thread.id").is_ok());
+        for field in ["", "has space", "emoji\u{1f600}", "x".repeat(65).as_str()] {
+            assert!(validate_field(field).is_err(), "{field}");
+        }
+    }
+}
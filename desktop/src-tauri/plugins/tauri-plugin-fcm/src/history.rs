@@ -0,0 +1,611 @@
+use std::sync::Mutex;
+
+use rusqlite::{params, params_from_iter, types::Value as SqlValue, Connection};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::{
+    encryption::{self, FieldCipher},
+    now_ms, store, Error,
+};
+
+/// The JSON store the history lived in before it moved to SQLite; imported
+/// once and then removed.
+const LEGACY_STORE_NAME: &str = "notification_history";
+const DATABASE_FILE: &str = "notifications.db";
+
+/// How many notifications the history keeps; the oldest go first.
+const CAPACITY: u32 = 1000;
+const DEFAULT_PAGE_SIZE: u32 = 50;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum NotificationState {
+    Delivered,
+    Tapped,
+    /// Swiped away. Only Android reports this, and only for notifications the
+    /// app displayed itself.
+    Dismissed,
+}
+
+impl NotificationState {
+    fn as_str(self) -> &'static str {
+        match self {
+            NotificationState::Delivered => "delivered",
+            NotificationState::Tapped => "tapped",
+            NotificationState::Dismissed => "dismissed",
+        }
+    }
+
+    fn parse(state: &str) -> Self {
+        match state {
+            "tapped" => NotificationState::Tapped,
+            "dismissed" => NotificationState::Dismissed,
+            _ => NotificationState::Delivered,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum NotificationSource {
+    /// A push message.
+    #[default]
+    Push,
+    /// Posted by the app itself, e.g. `notify_on_background` or
+    /// `schedule_notification`.
+    Local,
+}
+
+impl NotificationSource {
+    fn as_str(self) -> &'static str {
+        match self {
+            NotificationSource::Push => "push",
+            NotificationSource::Local => "local",
+        }
+    }
+
+    fn parse(source: &str) -> Self {
+        match source {
+            "local" => NotificationSource::Local,
+            _ => NotificationSource::Push,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub id: String,
+    #[serde(default)]
+    pub source: NotificationSource,
+    pub title: String,
+    pub body: String,
+    pub data: Map<String, Value>,
+    /// When it was delivered, in ms since the Unix epoch.
+    pub posted_at: u64,
+    pub state: NotificationState,
+    /// When `state` last changed.
+    pub updated_at: u64,
+    /// When it was marked read, by `mark_read` or a tap.
+    #[serde(default)]
+    pub read_at: Option<u64>,
+}
+
+/// Narrows `list_notifications`; unset fields match everything.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct NotificationFilter {
+    pub unread_only: bool,
+    pub source: Option<NotificationSource>,
+    pub state: Option<NotificationState>,
+    /// Only notifications posted at or after this, in ms since the Unix epoch.
+    pub since: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PageRequest {
+    pub offset: u32,
+    pub limit: u32,
+}
+
+impl Default for PageRequest {
+    fn default() -> Self {
+        Self {
+            offset: 0,
+            limit: DEFAULT_PAGE_SIZE,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct NotificationPage {
+    /// Newest first.
+    pub notifications: Vec<HistoryEntry>,
+    /// How many match the filter across all pages.
+    pub total: u64,
+}
+
+/// Local log of delivered notifications and what became of them, for an
+/// in-app notification center. A SQLite table under `<app data dir>/fcm`,
+/// never redacted: it stays on the device. Titles, bodies and data are
+/// sealed with the cache key while cache encryption is on.
+pub(crate) struct NotificationHistory(Mutex<Connection>);
+
+impl NotificationHistory {
+    pub(crate) fn load<R: Runtime>(app: &AppHandle<R>) -> Self {
+        let connection = open(app).unwrap_or_else(|e| {
+            log::warn!("FCM plugin: notification history kept in memory only: {e}");
+            let connection = Connection::open_in_memory().expect("in-memory SQLite is available");
+            migrate(&connection).expect("schema applies to an empty database");
+            connection
+        });
+        Self(Mutex::new(connection))
+    }
+}
+
+fn storage(e: rusqlite::Error) -> Error {
+    Error::Storage(e.to_string())
+}
+
+fn open<R: Runtime>(app: &AppHandle<R>) -> crate::Result<Connection> {
+    let dir = store::dir(app)?;
+    std::fs::create_dir_all(&dir).map_err(|e| Error::Storage(e.to_string()))?;
+    let connection = Connection::open(dir.join(DATABASE_FILE)).map_err(storage)?;
+    migrate(&connection).map_err(storage)?;
+
+    let legacy: Vec<HistoryEntry> = store::load(app, LEGACY_STORE_NAME);
+    if !legacy.is_empty() {
+        // The legacy store was kept in the clear, so a key that isn't
+        // available yet doesn't hold up the import
+        let cipher = encryption::field_cipher(app).unwrap_or_default();
+        for entry in &legacy {
+            insert(&connection, entry, &cipher)?;
+        }
+        prune(&connection).map_err(storage)?;
+    }
+    store::remove(app, LEGACY_STORE_NAME, "json");
+    Ok(connection)
+}
+
+fn migrate(connection: &Connection) -> rusqlite::Result<()> {
+    connection.execute_batch(
+        "CREATE TABLE IF NOT EXISTS notifications (
+            id TEXT PRIMARY KEY,
+            source TEXT NOT NULL,
+            title TEXT NOT NULL,
+            body TEXT NOT NULL,
+            data TEXT NOT NULL,
+            posted_at INTEGER NOT NULL,
+            state TEXT NOT NULL,
+            updated_at INTEGER NOT NULL,
+            read_at INTEGER
+        );
+        CREATE INDEX IF NOT EXISTS notifications_posted_at ON notifications (posted_at);",
+    )
+}
+
+/// A redelivered id replaces its earlier row rather than duplicating it.
+fn insert(
+    connection: &Connection,
+    entry: &HistoryEntry,
+    cipher: &FieldCipher,
+) -> crate::Result<()> {
+    connection
+        .execute(
+            "INSERT OR REPLACE INTO notifications
+                (id, source, title, body, data, posted_at, state, updated_at, read_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![
+                entry.id,
+                entry.source.as_str(),
+                cipher.seal(entry.title.clone())?,
+                cipher.seal(entry.body.clone())?,
+                cipher.seal(Value::Object(entry.data.clone()).to_string())?,
+                entry.posted_at as i64,
+                entry.state.as_str(),
+                entry.updated_at as i64,
+                entry.read_at.map(|at| at as i64),
+            ],
+        )
+        .map_err(storage)?;
+    Ok(())
+}
+
+fn prune(connection: &Connection) -> rusqlite::Result<()> {
+    connection.execute(
+        "DELETE FROM notifications WHERE id NOT IN
+            (SELECT id FROM notifications ORDER BY posted_at DESC LIMIT ?1)",
+        params![CAPACITY],
+    )?;
+    Ok(())
+}
+
+fn entry(row: &rusqlite::Row, cipher: &FieldCipher) -> rusqlite::Result<HistoryEntry> {
+    let data = cipher.open(row.get("data")?);
+    let source: String = row.get("source")?;
+    let state: String = row.get("state")?;
+    Ok(HistoryEntry {
+        id: row.get("id")?,
+        source: NotificationSource::parse(&source),
+        title: cipher.open(row.get("title")?),
+        body: cipher.open(row.get("body")?),
+        data: serde_json::from_str(&data).unwrap_or_default(),
+        posted_at: row.get::<_, i64>("posted_at")? as u64,
+        state: NotificationState::parse(&state),
+        updated_at: row.get::<_, i64>("updated_at")? as u64,
+        read_at: row.get::<_, Option<i64>>("read_at")?.map(|at| at as u64),
+    })
+}
+
+/// Returns whether a row changed. A tap is final: the dismissal Android may
+/// report after it doesn't undo it. A tap also marks it read.
+fn set_state(
+    connection: &Connection,
+    id: &str,
+    state: NotificationState,
+    now: u64,
+) -> rusqlite::Result<bool> {
+    let read_at = (state == NotificationState::Tapped).then_some(now as i64);
+    let changed = connection.execute(
+        "UPDATE notifications
+         SET state = ?2, updated_at = ?3, read_at = COALESCE(read_at, ?4)
+         WHERE id = ?1 AND state != 'tapped' AND state != ?2",
+        params![id, state.as_str(), now as i64, read_at],
+    )?;
+    Ok(changed > 0)
+}
+
+fn query(
+    connection: &Connection,
+    filter: &NotificationFilter,
+    page: PageRequest,
+    cipher: &FieldCipher,
+) -> rusqlite::Result<NotificationPage> {
+    let mut conditions = Vec::new();
+    let mut values: Vec<SqlValue> = Vec::new();
+    if filter.unread_only {
+        conditions.push("read_at IS NULL".to_string());
+    }
+    if let Some(source) = filter.source {
+        values.push(SqlValue::Text(source.as_str().to_string()));
+        conditions.push(format!("source = ?{}", values.len()));
+    }
+    if let Some(state) = filter.state {
+        values.push(SqlValue::Text(state.as_str().to_string()));
+        conditions.push(format!("state = ?{}", values.len()));
+    }
+    if let Some(since) = filter.since {
+        values.push(SqlValue::Integer(since as i64));
+        conditions.push(format!("posted_at >= ?{}", values.len()));
+    }
+    let clause = if conditions.is_empty() {
+        String::new()
+    } else {
+        format!("WHERE {}", conditions.join(" AND "))
+    };
+
+    let total: i64 = connection.query_row(
+        &format!("SELECT COUNT(*) FROM notifications {clause}"),
+        params_from_iter(&values),
+        |row| row.get(0),
+    )?;
+
+    values.push(SqlValue::Integer(page.limit.into()));
+    values.push(SqlValue::Integer(page.offset.into()));
+    let mut statement = connection.prepare(&format!(
+        "SELECT * FROM notifications {clause} ORDER BY posted_at DESC LIMIT ?{} OFFSET ?{}",
+        values.len() - 1,
+        values.len(),
+    ))?;
+    let notifications: Vec<HistoryEntry> = statement
+        .query_map(params_from_iter(&values), |row| entry(row, cipher))?
+        .collect::<rusqlite::Result<_>>()?;
+    Ok(NotificationPage {
+        notifications,
+        total: total as u64,
+    })
+}
+
+/// Log a delivered notification.
+pub(crate) fn record<R: Runtime>(
+    app: &AppHandle<R>,
+    id: String,
+    source: NotificationSource,
+    title: String,
+    body: String,
+    data: Map<String, Value>,
+) {
+    let now = now_ms();
+    let entry = HistoryEntry {
+        id,
+        source,
+        title,
+        body,
+        data,
+        posted_at: now,
+        state: NotificationState::Delivered,
+        updated_at: now,
+        read_at: None,
+    };
+    let cipher = match encryption::field_cipher(app) {
+        Ok(cipher) => cipher,
+        Err(e) => {
+            log::warn!("FCM plugin: not recording notification: {e}");
+            return;
+        }
+    };
+    let connection = app.state::<NotificationHistory>().0.lock().unwrap();
+    let recorded =
+        insert(&connection, &entry, &cipher).and_then(|()| prune(&connection).map_err(storage));
+    if let Err(e) = recorded {
+        log::warn!("FCM plugin: failed to record notification: {e}");
+    }
+}
+
+/// The cipher to read entries with. Without the key, sealed fields read as
+/// empty rather than failing the whole query.
+fn read_cipher<R: Runtime>(app: &AppHandle<R>) -> FieldCipher {
+    encryption::field_cipher(app).unwrap_or_else(|e| {
+        log::warn!("FCM plugin: notification history can't be decrypted: {e}");
+        FieldCipher::default()
+    })
+}
+
+/// Update a logged notification after a tap or dismissal. Unknown ids (e.g.
+/// notifications Firebase displayed while the app wasn't running) are ignored.
+#[cfg_attr(not(mobile), allow(dead_code))]
+pub(crate) fn mark<R: Runtime>(app: &AppHandle<R>, id: &str, state: NotificationState) {
+    let connection = app.state::<NotificationHistory>().0.lock().unwrap();
+    if let Err(e) = set_state(&connection, id, state, now_ms()) {
+        log::warn!("FCM plugin: failed to update notification {id}: {e}");
+    }
+}
+
+/// Returns whether the notification was unread.
+pub(crate) fn mark_read<R: Runtime>(app: &AppHandle<R>, id: &str) -> crate::Result<bool> {
+    let connection = app.state::<NotificationHistory>().0.lock().unwrap();
+    let changed = connection
+        .execute(
+            "UPDATE notifications SET read_at = ?2 WHERE id = ?1 AND read_at IS NULL",
+            params![id, now_ms() as i64],
+        )
+        .map_err(storage)?;
+    Ok(changed > 0)
+}
+
+pub(crate) fn list<R: Runtime>(
+    app: &AppHandle<R>,
+    filter: &NotificationFilter,
+    page: PageRequest,
+) -> crate::Result<NotificationPage> {
+    let cipher = read_cipher(app);
+    let connection = app.state::<NotificationHistory>().0.lock().unwrap();
+    query(&connection, filter, page, &cipher).map_err(storage)
+}
+
+/// Newest first, at most `limit` entries.
+pub(crate) fn get<R: Runtime>(
+    app: &AppHandle<R>,
+    limit: Option<usize>,
+) -> crate::Result<Vec<HistoryEntry>> {
+    let page = PageRequest {
+        offset: 0,
+        limit: limit.map_or(CAPACITY, |limit| limit.min(CAPACITY as usize) as u32),
+    };
+    Ok(list(app, &NotificationFilter::default(), page)?.notifications)
+}
+
+/// Every entry, opened with the current key, for [`reseal`] once the
+/// encryption setting has changed.
+pub(crate) fn open_all<R: Runtime>(app: &AppHandle<R>) -> crate::Result<Vec<HistoryEntry>> {
+    let cipher = read_cipher(app);
+    let connection = app.state::<NotificationHistory>().0.lock().unwrap();
+    let mut statement = connection
+        .prepare("SELECT * FROM notifications")
+        .map_err(storage)?;
+    let entries = statement
+        .query_map([], |row| entry(row, &cipher))
+        .and_then(|rows| rows.collect())
+        .map_err(storage)?;
+    Ok(entries)
+}
+
+/// Write `entries` back, sealed or in the clear per the current setting.
+pub(crate) fn reseal<R: Runtime>(
+    app: &AppHandle<R>,
+    entries: Vec<HistoryEntry>,
+) -> crate::Result<()> {
+    let cipher = encryption::field_cipher(app)?;
+    let connection = app.state::<NotificationHistory>().0.lock().unwrap();
+    for entry in &entries {
+        insert(&connection, entry, &cipher)?;
+    }
+    Ok(())
+}
+
+pub(crate) fn clear<R: Runtime>(app: &AppHandle<R>) -> crate::Result<()> {
+    let connection = app.state::<NotificationHistory>().0.lock().unwrap();
+    connection
+        .execute("DELETE FROM notifications", [])
+        .map_err(storage)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rusqlite::OptionalExtension;
+
+    fn database() -> Connection {
+        let connection = Connection::open_in_memory().unwrap();
+        migrate(&connection).unwrap();
+        connection
+    }
+
+    fn entry(id: &str, posted_at: u64) -> HistoryEntry {
+        HistoryEntry {
+            id: id.to_string(),
+            source: NotificationSource::Push,
+            title: String::new(),
+            body: String::new(),
+            data: Map::new(),
+            posted_at,
+            state: NotificationState::Delivered,
+            updated_at: posted_at,
+            read_at: None,
+        }
+    }
+
+    fn exists(connection: &Connection, id: &str) -> bool {
+        connection
+            .query_row(
+                "SELECT 1 FROM notifications WHERE id = ?1",
+                params![id],
+                |_| Ok(()),
+            )
+            .optional()
+            .unwrap()
+            .is_some()
+    }
+
+    #[test]
+    fn caps_history_and_replaces_redelivered_ids() {
+        let connection = database();
+        let plain = FieldCipher::default();
+        for i in 0..=CAPACITY {
+            insert(&connection, &entry(&i.to_string(), i.into()), &plain).unwrap();
+            prune(&connection).unwrap();
+        }
+        let all = query(
+            &connection,
+            &NotificationFilter::default(),
+            PageRequest::default(),
+            &plain,
+        )
+        .unwrap();
+        assert_eq!(all.total, CAPACITY as u64);
+        assert!(!exists(&connection, "0"));
+
+        insert(&connection, &entry("1", 5000), &plain).unwrap();
+        let all = query(
+            &connection,
+            &NotificationFilter::default(),
+            PageRequest::default(),
+            &plain,
+        )
+        .unwrap();
+        assert_eq!(all.total, CAPACITY as u64);
+        assert_eq!(all.notifications[0].id, "1");
+    }
+
+    #[test]
+    fn tap_is_not_overwritten_by_dismissal() {
+        let connection = database();
+        insert(&connection, &entry("a", 0), &FieldCipher::default()).unwrap();
+        assert!(set_state(&connection, "a", NotificationState::Tapped, 5).unwrap());
+        assert!(!set_state(&connection, "a", NotificationState::Dismissed, 6).unwrap());
+        let page = query(
+            &connection,
+            &NotificationFilter::default(),
+            PageRequest::default(),
+            &FieldCipher::default(),
+        )
+        .unwrap();
+        assert_eq!(page.notifications[0].state, NotificationState::Tapped);
+        assert_eq!(page.notifications[0].updated_at, 5);
+        assert_eq!(page.notifications[0].read_at, Some(5));
+        assert!(!set_state(&connection, "b", NotificationState::Tapped, 7).unwrap());
+    }
+
+    #[test]
+    fn seals_title_body_and_data_when_encrypting() {
+        let connection = database();
+        let cipher = FieldCipher::generate();
+        let mut e = entry("a", 0);
+        e.title = "Build failed".to_string();
+        e.body = "3 tests".to_string();
+        e.data.insert("session".to_string(), "s1".into());
+        insert(&connection, &e, &cipher).unwrap();
+
+        let stored: String = connection
+            .query_row(
+                "SELECT title || body || data FROM notifications",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert!(!stored.contains("Build") && !stored.contains("s1"));
+
+        let page = query(
+            &connection,
+            &NotificationFilter::default(),
+            PageRequest::default(),
+            &cipher,
+        )
+        .unwrap();
+        assert_eq!(page.notifications[0].title, "Build failed");
+        assert_eq!(page.notifications[0].body, "3 tests");
+        assert_eq!(page.notifications[0].data["session"], "s1");
+    }
+
+    #[test]
+    fn filters_and_pages() {
+        let connection = database();
+        let plain = FieldCipher::default();
+        for i in 0..5u64 {
+            let mut e = entry(&format!("push-{i}"), i);
+            if i % 2 == 0 {
+                e.read_at = Some(i);
+            }
+            insert(&connection, &e, &plain).unwrap();
+        }
+        let mut local = entry("local", 10);
+        local.source = NotificationSource::Local;
+        insert(&connection, &local, &plain).unwrap();
+
+        let unread = NotificationFilter {
+            unread_only: true,
+            ..Default::default()
+        };
+        let page = query(
+            &connection,
+            &unread,
+            PageRequest {
+                offset: 0,
+                limit: 2,
+            },
+            &plain,
+        )
+        .unwrap();
+        assert_eq!(page.total, 3);
+        let ids: Vec<_> = page.notifications.iter().map(|n| n.id.as_str()).collect();
+        assert_eq!(ids, ["local", "push-3"]);
+
+        let next = query(
+            &connection,
+            &unread,
+            PageRequest {
+                offset: 2,
+                limit: 2,
+            },
+            &plain,
+        )
+        .unwrap();
+        assert_eq!(next.notifications.len(), 1);
+        assert_eq!(next.notifications[0].id, "push-1");
+
+        let pushes_since = NotificationFilter {
+            source: Some(NotificationSource::Push),
+            since: Some(3),
+            ..Default::default()
+        };
+        assert_eq!(
+            query(&connection, &pushes_since, PageRequest::default(), &plain)
+                .unwrap()
+                .total,
+            2
+        );
+    }
+}
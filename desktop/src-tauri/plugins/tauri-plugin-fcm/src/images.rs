@@ -0,0 +1,31 @@
+use std::time::Duration;
+
+use serde::Serialize;
+
+/// Limits on the image downloaded for a message's `image_url`. Delivery waits
+/// for the download, so an image that is too large or too slow is dropped
+/// and the notification shown without it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ImageLimits {
+    pub max_bytes: u64,
+    pub timeout_ms: u64,
+}
+
+impl Default for ImageLimits {
+    fn default() -> Self {
+        Self {
+            max_bytes: 2 * 1024 * 1024,
+            timeout_ms: 5_000,
+        }
+    }
+}
+
+impl ImageLimits {
+    pub(crate) fn new(max_bytes: u64, timeout: Duration) -> Self {
+        Self {
+            max_bytes,
+            timeout_ms: timeout.as_millis() as u64,
+        }
+    }
+}
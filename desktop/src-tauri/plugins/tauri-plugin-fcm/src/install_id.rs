@@ -0,0 +1,81 @@
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::store;
+
+const STORE_NAME: &str = "install_id";
+
+#[derive(Default, Serialize, Deserialize)]
+struct Persisted {
+    id: Option<String>,
+}
+
+/// Random identifier for this install, separate from the rotating FCM token
+/// so a backend can keep one record per device. Never derived from hardware
+/// identifiers; uninstalling or clearing app data yields a new one.
+pub(crate) struct InstallId(Mutex<String>);
+
+impl InstallId {
+    pub(crate) fn load<R: Runtime>(app: &AppHandle<R>) -> Self {
+        let id = match store::load::<_, Persisted>(app, STORE_NAME).id {
+            Some(id) => id,
+            None => {
+                let id = generate();
+                if let Err(e) = save(app, &id) {
+                    log::warn!("FCM plugin: failed to persist install id: {e}");
+                }
+                id
+            }
+        };
+        Self(Mutex::new(id))
+    }
+}
+
+fn generate() -> String {
+    uuid::Uuid::new_v4().to_string()
+}
+
+fn save<R: Runtime>(app: &AppHandle<R>, id: &str) -> crate::Result<()> {
+    store::save(
+        app,
+        STORE_NAME,
+        &Persisted {
+            id: Some(id.to_string()),
+        },
+    )
+}
+
+pub(crate) fn get<R: Runtime>(app: &AppHandle<R>) -> String {
+    app.state::<InstallId>().0.lock().unwrap().clone()
+}
+
+/// Replace the install id with a fresh one, e.g. on logout. On mobile the
+/// token is deleted and fetched again so the backend sees a new token under
+/// the new id rather than linking the two. Returns the new id.
+pub(crate) fn reset<R: Runtime>(app: &AppHandle<R>) -> crate::Result<String> {
+    let id = generate();
+    save(app, &id)?;
+    *app.state::<InstallId>().0.lock().unwrap() = id.clone();
+
+    #[cfg(mobile)]
+    {
+        let app = app.clone();
+        tauri::async_runtime::spawn_blocking(move || {
+            use crate::FcmExt;
+            let fcm = app.fcm();
+            if let Err(e) = fcm.delete_token() {
+                log::warn!("FCM plugin: could not delete token after install id reset: {e}");
+            }
+            match fcm.get_token() {
+                Ok(Some(token)) => crate::upload::enqueue(&app, token),
+                Ok(None) => {}
+                Err(e) => {
+                    log::warn!("FCM plugin: could not refresh token after install id reset: {e}")
+                }
+            }
+        });
+    }
+    Ok(id)
+}
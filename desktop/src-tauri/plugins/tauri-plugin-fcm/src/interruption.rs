@@ -0,0 +1,93 @@
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Runtime};
+
+use crate::store;
+
+const STORE_NAME: &str = "interruption_level";
+
+/// Payload key that overrides the default level for a single message.
+pub(crate) const INTERRUPTION_LEVEL_KEY: &str = "interruptionLevel";
+
+/// How far a notification may break through, named after iOS 15's
+/// interruption levels. Android maps them onto channel importance and
+/// priority; desktop ignores them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum InterruptionLevel {
+    /// Delivered quietly, without sound or heads-up.
+    Passive,
+    #[default]
+    Active,
+    /// Shown as a heads-up; on iOS, may break through Focus.
+    TimeSensitive,
+    /// Uses the urgent channel that may bypass Do Not Disturb.
+    Critical,
+}
+
+impl InterruptionLevel {
+    fn parse(value: &str) -> Option<Self> {
+        Self::deserialize(serde_json::Value::from(value)).ok()
+    }
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct InterruptionConfig {
+    level: Option<InterruptionLevel>,
+}
+
+/// The level for messages that don't set their own.
+pub(crate) fn default_level<R: Runtime>(app: &AppHandle<R>) -> InterruptionLevel {
+    store::load::<_, InterruptionConfig>(app, STORE_NAME)
+        .level
+        .unwrap_or_default()
+}
+
+/// Persist the default level and hand it to the native side, which applies it
+/// to pushes that arrive while the app isn't running.
+pub(crate) fn set_default_level<R: Runtime>(
+    app: &AppHandle<R>,
+    level: InterruptionLevel,
+) -> crate::Result<()> {
+    #[cfg(mobile)]
+    {
+        use crate::FcmExt;
+        app.fcm().set_interruption_level(level)?;
+    }
+    store::save(app, STORE_NAME, &InterruptionConfig { level: Some(level) })
+}
+
+/// A message's own `interruptionLevel` if it names a known level, else the
+/// default. Mirrored by `LocalNotifications.interruptionLevel` on Android.
+pub(crate) fn resolve(value: Option<&str>, default: InterruptionLevel) -> InterruptionLevel {
+    value.and_then(InterruptionLevel::parse).unwrap_or(default)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn message_level_overrides_default() {
+        let default = InterruptionLevel::Passive;
+        assert_eq!(
+            resolve(Some("timeSensitive"), default),
+            InterruptionLevel::TimeSensitive
+        );
+        assert_eq!(
+            resolve(Some("critical"), default),
+            InterruptionLevel::Critical
+        );
+        assert_eq!(resolve(None, default), default);
+    }
+
+    #[test]
+    fn unknown_levels_fall_back_to_default() {
+        for value in ["", "urgent", "time_sensitive", "Active"] {
+            assert_eq!(
+                resolve(Some(value), InterruptionLevel::Passive),
+                InterruptionLevel::Passive,
+                "{value}"
+            );
+        }
+    }
+}
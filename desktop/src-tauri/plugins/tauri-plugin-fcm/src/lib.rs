@@ -1,10 +1,96 @@
+use std::time::Duration;
+
 use tauri::{
-    plugin::{Builder, TauriPlugin},
-    Runtime,
+    plugin::{Builder as PluginBuilder, TauriPlugin},
+    webview::PageLoadEvent,
+    Manager, Runtime,
 };
 
+mod actions;
+mod appearance;
+mod auto_init;
+mod background;
+mod badge;
+mod batch;
+mod channels;
+mod clock;
+mod commands;
+mod dedup;
+#[cfg(desktop)]
+mod desktop_push;
+mod e2e;
+mod encryption;
 mod error;
+mod grouping;
+#[cfg(mobile)]
+mod events;
+mod history;
+mod images;
+mod install_id;
+mod interruption;
+mod lifecycle;
+mod messages;
+mod metrics;
+mod missed;
+#[cfg(all(desktop, feature = "mock"))]
+mod mock;
+// Notification taps only arrive from the native side
+#[cfg_attr(not(mobile), allow(dead_code))]
+mod opened;
+mod presentation;
+mod progress;
+mod quiet_hours;
+#[cfg(mobile)]
+mod review;
+mod scheduled;
+mod senders;
+mod shutdown;
+mod silent;
+mod sounds;
+mod sticky;
+mod store;
+mod suppressed;
+mod throttle;
+// Token refreshes come from the native side on mobile and from `desktop_push`
+// on desktop, so none of it is dead on either
+mod token;
+mod topics;
+mod upload;
+
+pub use actions::{NotificationAction, NOTIFICATION_ACTION_EVENT};
+pub use appearance::NotificationAppearance;
+pub use badge::{BadgeCount, BADGE_EVENT};
+pub use batch::{MESSAGES_BATCH_EVENT, MESSAGE_EVENT};
+pub use channels::ChannelImportance;
+pub use clock::{ClockSkew, CLOCK_SKEW_EVENT};
+pub use encryption::CacheEncryption;
 pub use error::Error;
+pub use history::{
+    HistoryEntry, NotificationFilter, NotificationPage, NotificationSource, NotificationState,
+    PageRequest,
+};
+pub use interruption::InterruptionLevel;
+pub use lifecycle::{APP_PAUSED_EVENT, APP_RESUMED_EVENT};
+pub use messages::CachedMessage;
+pub use metrics::Metrics;
+#[cfg(all(desktop, feature = "mock"))]
+pub use mock::MockFcm;
+pub use opened::{DefaultAction, NotificationOpened, OpenedAction, NOTIFICATION_OPENED_EVENT};
+pub use presentation::ForegroundPresentation;
+pub use progress::{ProgressUpdate, PROGRESS_EVENT};
+pub use quiet_hours::{
+    DeferredNotification, QuietHours, QuietHoursDigest, QUIET_HOURS_DIGEST_EVENT,
+};
+pub use scheduled::{ScheduledLimits, ScheduledNotification};
+pub use senders::{SenderMode, SenderPolicy, SenderPolicyState};
+pub use shutdown::ShutdownSummary;
+pub use silent::BACKGROUND_MESSAGES_EVENT;
+pub use sticky::emit_sticky;
+pub use suppressed::{SuppressedNotification, SuppressionReason, NOTIFICATION_SUPPRESSED_EVENT};
+pub use throttle::POSSIBLE_THROTTLING_EVENT;
+pub use token::TOKEN_REFRESHED_EVENT;
+pub use topics::TopicResult;
+pub use upload::{TokenUploadState, TOKEN_UPLOAD_UNAUTHORIZED_EVENT};
 
 #[cfg(mobile)]
 mod mobile;
@@ -20,6 +106,65 @@ pub type Result<T> = std::result::Result<T, Error>;
 pub struct FcmToken {
     pub token: String,
     pub platform: String,
+    /// Stable id for this install; see `get_install_id`.
+    pub install_id: String,
+}
+
+/// The Firebase project this build's tokens belong to, from the native config.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FcmProject {
+    pub sender_id: Option<String>,
+    pub project_id: Option<String>,
+}
+
+/// Which native listeners `init_fcm_listeners` has in place. Token, message and
+/// notification-opened events all arrive over the one native event channel,
+/// so they are only split out to spell out what the frontend can expect.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct FcmListeners {
+    pub token_listener: bool,
+    pub message_listener: bool,
+    pub opened_listener: bool,
+    /// App pause/resume events; on desktop they follow main-window focus.
+    pub lifecycle: bool,
+    /// The requested sticky events that were re-emitted for listeners
+    /// attached late.
+    pub replayed: Vec<String>,
+}
+
+/// Outcome of a notification permission request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PermissionStatus {
+    Granted,
+    /// The system prompt was shown; its answer arrives asynchronously.
+    Requested,
+    /// Denied with "don't ask again". Only the system settings can grant it now.
+    Blocked,
+}
+
+/// Where pushes come from on Android. UnifiedPush hands out an endpoint URL
+/// in place of the FCM token and works without Play Services; the token and
+/// message events are the same either way.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PushDistributor {
+    #[default]
+    Fcm,
+    UnifiedPush,
+    /// Huawei Push Kit, for devices with HMS Core instead of Play Services.
+    /// Picked automatically on those devices while FCM is selected.
+    #[cfg(feature = "hms")]
+    Hms,
+}
+
+/// Milliseconds since the Unix epoch.
+pub(crate) fn now_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default()
 }
 
 #[cfg(mobile)]
@@ -43,84 +188,251 @@ mod ext {
 pub use ext::FcmExt;
 
 pub fn init<R: Runtime>() -> TauriPlugin<R> {
-    Builder::new("fcm")
-        .setup(|app, api| {
-            #[cfg(mobile)]
-            {
-                use tauri::Manager;
-                let fcm = mobile::init(app, api)?;
-                app.manage(fcm);
-            }
-            #[cfg(not(mobile))]
-            {
-                let _ = (app, api);
-                log::debug!("FCM plugin: no-op on desktop");
-            }
-            Ok(())
-        })
-        .invoke_handler(tauri::generate_handler![
-            commands::get_fcm_token,
-            commands::request_notification_permission,
-            commands::is_notification_permission_granted,
-        ])
-        .build()
+    Builder::default().build()
 }
 
-mod commands {
-    use tauri::{command, AppHandle, Runtime};
-
-    #[command]
-    pub async fn get_fcm_token<R: Runtime>(app: AppHandle<R>) -> Result<Option<String>, String> {
-        #[cfg(mobile)]
-        {
-            use super::FcmExt;
-            match app.fcm().get_token() {
-                Ok(token) => Ok(token),
-                Err(e) => Err(e.to_string()),
-            }
-        }
-        #[cfg(not(mobile))]
-        {
-            let _ = app;
-            Ok(None)
-        }
+/// Configures the plugin before it is registered; [`init`] takes the defaults.
+#[derive(Debug, Default)]
+pub struct Builder {
+    image_limits: images::ImageLimits,
+    appearance: appearance::AppearanceDefaults,
+    auto_init: Option<bool>,
+}
+
+impl Builder {
+    pub fn new() -> Self {
+        Self::default()
     }
 
-    #[command]
-    pub async fn request_notification_permission<R: Runtime>(
-        app: AppHandle<R>,
-    ) -> Result<bool, String> {
-        #[cfg(mobile)]
-        {
-            use super::FcmExt;
-            match app.fcm().request_permission() {
-                Ok(granted) => Ok(granted),
-                Err(e) => Err(e.to_string()),
-            }
-        }
-        #[cfg(not(mobile))]
-        {
-            let _ = app;
-            Ok(true)
-        }
+    /// Cap the image attached from a message's `image_url` at `max_bytes`,
+    /// fetched within `timeout`; past either it is left out. Defaults to
+    /// 2 MiB and 5 seconds. Android only.
+    pub fn image_limits(mut self, max_bytes: u64, timeout: Duration) -> Self {
+        self.image_limits = images::ImageLimits::new(max_bytes, timeout);
+        self
     }
 
-    #[command]
-    pub async fn is_notification_permission_granted<R: Runtime>(
-        app: AppHandle<R>,
-    ) -> Result<bool, String> {
-        #[cfg(mobile)]
-        {
-            use super::FcmExt;
-            match app.fcm().is_permission_granted() {
-                Ok(granted) => Ok(granted),
-                Err(e) => Err(e.to_string()),
-            }
-        }
-        #[cfg(not(mobile))]
-        {
-            let _ = app;
-            Ok(true)
-        }
+    /// Channel for notifications that don't name one in `channelId`, in place
+    /// of the plugin's own "default" channel and its per-vibration-pattern
+    /// variants. Created with high importance if the app hasn't created it.
+    /// Android only.
+    pub fn default_channel(mut self, id: impl Into<String>) -> Self {
+        self.appearance.default_channel = Some(id.into());
+        self
+    }
+
+    /// Status-bar icon, a drawable resource name, in place of the app icon.
+    /// Android only.
+    pub fn small_icon(mut self, name: impl Into<String>) -> Self {
+        self.appearance.small_icon = Some(name.into());
+        self
+    }
+
+    /// Accent color, `#RRGGBB` or `#AARRGGBB`. Android only.
+    pub fn notification_color(mut self, color: impl Into<String>) -> Self {
+        self.appearance.accent_color = Some(color.into());
+        self
+    }
+
+    /// Whether FCM generates a token on its own at startup. A choice made
+    /// later with `set_auto_init_enabled` takes precedence. Android only.
+    pub fn auto_init(mut self, enabled: bool) -> Self {
+        self.auto_init = Some(enabled);
+        self
+    }
+
+    /// The channel, icon and color only apply to notifications the plugin
+    /// posts itself; ones Firebase displays use the manifest's meta-data.
+    /// Setup fails if any of them is malformed.
+    pub fn build<R: Runtime>(self) -> TauriPlugin<R> {
+        let image_limits = self.image_limits;
+        let defaults = self.appearance;
+        let auto_init = self.auto_init;
+        PluginBuilder::new("fcm")
+            .setup(move |app, api| {
+                defaults.validate()?;
+                app.manage(topics::Topics::load(app));
+                app.manage(upload::TokenUpload::load(app));
+                app.manage(shutdown::ShutdownState::default());
+                app.manage(token::TokenState::default());
+                app.manage(opened::NotificationActionState::load(app));
+                app.manage(sticky::StickyEvents::default());
+                app.manage(encryption::CacheEncryptionState::load(app));
+                app.manage(messages::MessageCache::load(app));
+                app.manage(throttle::ThrottleMonitor::load(app));
+                app.manage(senders::SenderFilter::load(app));
+                app.manage(batch::MessageBatching::default());
+                app.manage(grouping::NotificationGrouping::default());
+                app.manage(background::BackgroundNotifications::load(app));
+                app.manage(missed::MissedMessages::load(app));
+                app.manage(metrics::MetricsState::load(app));
+                app.manage(install_id::InstallId::load(app));
+                app.manage(progress::ProgressNotifications::default());
+                app.manage(appearance::AppearanceState::load(app, defaults));
+                app.manage(history::NotificationHistory::load(app));
+                app.manage(suppressed::SuppressionLog::load(app));
+                app.manage(clock::ClockOffset::load(app));
+                app.manage(quiet_hours::QuietHoursState::load(app));
+                app.manage(dedup::Dedup::load(app));
+                app.manage(e2e::PushKey::default());
+                tauri::async_runtime::spawn(upload::run(app.clone()));
+                tauri::async_runtime::spawn(upload::probe_clock(app.clone()));
+
+                #[cfg(mobile)]
+                {
+                    let fcm = mobile::init(app, api)?;
+                    app.manage(fcm);
+                    auto_init::reapply(app, auto_init);
+                    appearance::apply_defaults(app);
+                    encryption::reload(app);
+                    if let Err(e) = app.fcm().set_image_limits(image_limits) {
+                        log::warn!("FCM plugin: failed to set image limits: {e}");
+                    }
+                    #[cfg(feature = "hms")]
+                    match app.fcm().fall_back_to_hms() {
+                        Ok(true) => log::info!("FCM plugin: no Play Services, using HMS Push Kit"),
+                        Ok(false) => {}
+                        Err(e) => log::warn!("FCM plugin: failed to check for HMS: {e}"),
+                    }
+
+                    // Re-assert tracked topic subscriptions in the background
+                    let handle = app.clone();
+                    tauri::async_runtime::spawn(async move {
+                        topics::reapply(&handle).await;
+                    });
+                    tauri::async_runtime::spawn(throttle::run(app.clone()));
+                }
+                #[cfg(not(mobile))]
+                {
+                    let _ = (api, image_limits, auto_init);
+                    app.manage(desktop_push::DesktopPush::load(app));
+                    app.manage(scheduled::ScheduledNotifications::load(app));
+                    #[cfg(feature = "mock")]
+                    app.manage(mock::MockState::default());
+                    desktop_push::connect(app);
+                    scheduled::resume(app);
+                }
+                // After the native side is up, since a digest may be due right away
+                tauri::async_runtime::spawn(quiet_hours::run(app.clone()));
+                Ok(())
+            })
+            .on_page_load(|webview, payload| {
+                // A reload drops the page's listeners, so sticky events are kept again
+                if webview.label() == "main" && payload.event() == PageLoadEvent::Started {
+                    sticky::forget_listeners(webview.app_handle());
+                }
+            })
+            .on_event(|app, event| {
+                // Desktop has no app-level pause/resume, so approximate with main-window focus
+                #[cfg(desktop)]
+                {
+                    use tauri::{RunEvent, WindowEvent};
+
+                    if let RunEvent::WindowEvent {
+                        label,
+                        event: WindowEvent::Focused(focused),
+                        ..
+                    } = event
+                    {
+                        if label == "main" {
+                            if *focused {
+                                lifecycle::on_resumed(app);
+                            } else {
+                                lifecycle::on_paused(app);
+                            }
+                        }
+                    }
+                }
+                #[cfg(mobile)]
+                let _ = (app, event);
+            })
+            .invoke_handler(tauri::generate_handler![
+                commands::get_fcm_token,
+                commands::request_notification_permission,
+                commands::can_request_notification_permission,
+                commands::is_notification_permission_granted,
+                commands::subscribe_to_topics,
+                commands::unsubscribe_from_topics,
+                commands::get_subscribed_topics,
+                commands::clear_all_topic_subscriptions,
+                commands::request_in_app_review,
+                commands::configure_token_upload,
+                commands::set_token_upload_headers,
+                commands::fcm_shutdown,
+                commands::get_default_notification_action,
+                commands::set_default_notification_action,
+                commands::configure_notification_appearance,
+                commands::replay_sticky_events,
+                commands::fcm_ping,
+                commands::pause_token_upload,
+                commands::resume_token_upload,
+                commands::get_token_upload_state,
+                commands::export_message_cache,
+                commands::clear_message_cache,
+                commands::get_last_message_age,
+                commands::set_throttling_threshold,
+                commands::get_sender_policy,
+                commands::set_sender_policy,
+                commands::set_message_batching,
+                commands::notifications_are_grouped,
+                commands::notify_on_background,
+                commands::cancel_background_notification,
+                commands::get_fcm_auto_init_enabled,
+                commands::set_fcm_auto_init_enabled,
+                commands::get_missed_count,
+                commands::clear_missed_count,
+                commands::reinitialize_fcm,
+                commands::get_metrics,
+                commands::reset_metrics,
+                commands::set_metrics_enabled,
+                commands::record_metric,
+                commands::clear_notifications_for_session,
+                commands::set_vibration_pattern,
+                commands::get_install_id,
+                commands::reset_install_id,
+                commands::show_progress_notification,
+                commands::update_progress_notification,
+                commands::complete_progress_notification,
+                commands::get_notification_appearance,
+                commands::get_notification_history,
+                commands::clear_notification_history,
+                commands::get_fcm_sender_id,
+                commands::get_default_group_key_field,
+                commands::set_default_group_key_field,
+                commands::get_suppressed_notifications,
+                commands::get_default_interruption_level,
+                commands::set_default_interruption_level,
+                commands::get_clock_offset,
+                commands::init_fcm_listeners,
+                commands::get_cache_encryption,
+                commands::set_cache_encryption,
+                commands::get_foreground_presentation_options,
+                commands::set_foreground_presentation_options,
+                commands::subscribe_to_topic,
+                commands::unsubscribe_from_topic,
+                commands::delete_fcm_token,
+                commands::create_notification_channel,
+                commands::delete_notification_channel,
+                commands::set_badge_count,
+                commands::clear_badge,
+                commands::get_desktop_push_endpoint,
+                commands::set_desktop_push_endpoint,
+                commands::get_push_distributor,
+                commands::set_push_distributor,
+                commands::open_notification_settings,
+                commands::get_quiet_hours,
+                commands::set_quiet_hours,
+                commands::clear_quiet_hours,
+                commands::set_dedup_ttl,
+                commands::get_push_public_key,
+                commands::schedule_notification,
+                commands::cancel_scheduled,
+                commands::get_scheduled_limits,
+                commands::list_notifications,
+                commands::mark_read,
+                commands::clear_all,
+                commands::get_available_sounds,
+            ])
+            .build()
     }
 }
@@ -0,0 +1,36 @@
+use tauri::{AppHandle, Emitter, Runtime};
+
+use crate::{background, missed};
+
+/// Emitted when the app leaves the foreground (mobile pause, desktop main-window blur).
+pub const APP_PAUSED_EVENT: &str = "app-paused";
+/// Emitted when the app returns to the foreground (mobile resume, desktop main-window focus).
+pub const APP_RESUMED_EVENT: &str = "app-resumed";
+
+pub(crate) fn on_paused<R: Runtime>(app: &AppHandle<R>) {
+    let _ = app.emit(APP_PAUSED_EVENT, ());
+    background::on_paused(app);
+}
+
+pub(crate) fn on_resumed<R: Runtime>(app: &AppHandle<R>) {
+    let _ = app.emit(APP_RESUMED_EVENT, ());
+    missed::on_resumed(app);
+
+    // Native calls block on the main thread, so hop off the caller's thread first
+    #[cfg(mobile)]
+    {
+        let app = app.clone();
+        tauri::async_runtime::spawn_blocking(move || {
+            use crate::FcmExt;
+            let fcm = app.fcm();
+            if let Err(e) = fcm.register_event_handler(&app) {
+                log::warn!("FCM plugin: failed to re-register native event handler: {e}");
+            }
+            // Before clearing, so it reflects what the user saw while away
+            crate::grouping::refresh(&app);
+            if let Err(e) = fcm.clear_delivered_notifications() {
+                log::warn!("FCM plugin: failed to clear delivered notifications: {e}");
+            }
+        });
+    }
+}
@@ -0,0 +1,238 @@
+use std::{collections::VecDeque, fs, path::PathBuf, sync::Mutex};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::{
+    batch, dedup, e2e, encryption,
+    history::{self, NotificationSource},
+    metrics, missed, now_ms, senders, store,
+    suppressed::{self, SuppressionReason},
+    throttle, Error,
+};
+
+const STORE_NAME: &str = "messages";
+
+/// How many received messages are kept for debugging.
+const CAPACITY: usize = 100;
+
+/// Substrings of data keys whose values are replaced when exporting redacted.
+const SENSITIVE_KEYS: [&str; 7] = [
+    "token", "secret", "password", "auth", "email", "phone", "preview",
+];
+const REDACTED: &str = "[redacted]";
+
+/// A message as delivered by the native side, with its arrival time.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedMessage {
+    pub received_at: u64,
+    /// When the server sent it, in ms since the Unix epoch, if known.
+    #[serde(default)]
+    pub sent_at: Option<u64>,
+    pub title: String,
+    pub body: String,
+    pub data: Map<String, Value>,
+}
+
+impl CachedMessage {
+    fn redacted(&self) -> Self {
+        let data = self
+            .data
+            .iter()
+            .map(|(key, value)| {
+                let lower = key.to_ascii_lowercase();
+                if SENSITIVE_KEYS.iter().any(|s| lower.contains(s)) {
+                    (key.clone(), Value::from(REDACTED))
+                } else {
+                    (key.clone(), value.clone())
+                }
+            })
+            .collect();
+        Self {
+            received_at: self.received_at,
+            sent_at: self.sent_at,
+            title: self.title.clone(),
+            body: REDACTED.to_string(),
+            data,
+        }
+    }
+}
+
+/// Ring buffer of the most recent messages, oldest first, persisted so it
+/// survives the restarts that usually follow a missed notification.
+pub(crate) struct MessageCache(Mutex<VecDeque<CachedMessage>>);
+
+impl MessageCache {
+    pub(crate) fn load<R: Runtime>(app: &AppHandle<R>) -> Self {
+        Self(Mutex::new(encryption::load(app, STORE_NAME)))
+    }
+
+    fn persist<R: Runtime>(
+        &self,
+        app: &AppHandle<R>,
+        messages: &VecDeque<CachedMessage>,
+    ) -> crate::Result<()> {
+        encryption::save(app, STORE_NAME, messages)
+    }
+}
+
+fn push(messages: &mut VecDeque<CachedMessage>, message: CachedMessage) {
+    messages.push_back(message);
+    while messages.len() > CAPACITY {
+        messages.pop_front();
+    }
+}
+
+/// Record a received message and emit it to the frontend.
+pub(crate) fn record<R: Runtime>(app: &AppHandle<R>, payload: &Value) {
+    if let Some(message) = ingest(app, payload) {
+        batch::emit(app, message);
+    }
+}
+
+/// Decrypt a received message if it is sealed, append it, evicting the oldest beyond [`CAPACITY`], and
+/// add it to the history. Duplicates of a recent message and messages from
+/// senders the sender policy rejects are dropped here and logged as
+/// suppressed, returning `None`.
+pub(crate) fn ingest<R: Runtime>(app: &AppHandle<R>, payload: &Value) -> Option<CachedMessage> {
+    let payload = &e2e::decrypt(app, payload);
+    let data = payload
+        .get("data")
+        .and_then(Value::as_object)
+        .cloned()
+        .unwrap_or_default();
+    let received_at = now_ms();
+    let id = payload
+        .get("messageId")
+        .and_then(Value::as_str)
+        .map_or_else(|| format!("message-{received_at}"), str::to_string);
+    // Still counts as delivery for throttling purposes
+    throttle::on_message(app);
+    if dedup::is_duplicate(app, payload) {
+        suppressed::record(app, id, SuppressionReason::Duplicate);
+        return None;
+    }
+    if !senders::admit(app, &data) {
+        suppressed::record(app, id, SuppressionReason::SenderPolicy);
+        return None;
+    }
+
+    let text = |key: &str| {
+        payload
+            .get(key)
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string()
+    };
+    let sent_at = payload.get("sentTime").and_then(Value::as_u64);
+    missed::on_message(app, sent_at);
+    metrics::count(app, metrics::NOTIFICATION_RECEIVED);
+
+    let message = CachedMessage {
+        received_at,
+        sent_at,
+        title: text("title"),
+        body: text("body"),
+        data,
+    };
+
+    let cache = app.state::<MessageCache>();
+    let mut messages = cache.0.lock().unwrap();
+    push(&mut messages, message.clone());
+    if let Err(e) = cache.persist(app, &messages) {
+        log::warn!("FCM plugin: failed to persist message cache: {e}");
+    }
+    drop(messages);
+
+    history::record(
+        app,
+        id,
+        NotificationSource::Push,
+        message.title.clone(),
+        message.body.clone(),
+        message.data.clone(),
+    );
+    Some(message)
+}
+
+/// Write the cache to `<app data dir>/fcm/exports/messages-<ms>.json`,
+/// optionally redacting bodies and sensitive data values. Returns the path.
+pub(crate) fn export<R: Runtime>(app: &AppHandle<R>, redact: bool) -> crate::Result<PathBuf> {
+    let messages: Vec<CachedMessage> = {
+        let messages = app.state::<MessageCache>().0.lock().unwrap();
+        if redact {
+            messages.iter().map(CachedMessage::redacted).collect()
+        } else {
+            messages.iter().cloned().collect()
+        }
+    };
+
+    let dir = store::dir(app)?.join("exports");
+    fs::create_dir_all(&dir).map_err(|e| Error::Storage(e.to_string()))?;
+    let path = dir.join(format!("messages-{}.json", now_ms()));
+    let bytes = serde_json::to_vec_pretty(&messages).map_err(|e| Error::Storage(e.to_string()))?;
+    fs::write(&path, bytes).map_err(|e| Error::Storage(e.to_string()))?;
+    Ok(path)
+}
+
+/// Drop every cached message.
+pub(crate) fn clear<R: Runtime>(app: &AppHandle<R>) -> crate::Result<()> {
+    let cache = app.state::<MessageCache>();
+    let mut messages = cache.0.lock().unwrap();
+    messages.clear();
+    cache.persist(app, &messages)
+}
+
+/// Persist the cache again, e.g. after encryption was turned on or off.
+pub(crate) fn rewrite<R: Runtime>(app: &AppHandle<R>) -> crate::Result<()> {
+    let cache = app.state::<MessageCache>();
+    let messages = cache.0.lock().unwrap();
+    cache.persist(app, &messages)
+}
+
+/// Load the persisted cache again, keeping messages received since startup
+/// after the older ones.
+#[cfg(mobile)]
+pub(crate) fn reload<R: Runtime>(app: &AppHandle<R>) {
+    let loaded: VecDeque<CachedMessage> = encryption::load(app, STORE_NAME);
+    let cache = app.state::<MessageCache>();
+    let mut messages = cache.0.lock().unwrap();
+    let received = std::mem::replace(&mut *messages, loaded);
+    for message in received {
+        push(&mut messages, message);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn redaction_masks_body_and_sensitive_keys_only() {
+        let message = CachedMessage {
+            received_at: 1,
+            sent_at: None,
+            title: "Waiting for input".into(),
+            body: "Deploy to prod?".into(),
+            data: json!({
+                "type": "waiting_for_input",
+                "sessionId": "abc",
+                "preview": "Deploy to prod?",
+                "authToken": "secret-value",
+            })
+            .as_object()
+            .cloned()
+            .unwrap(),
+        };
+
+        let redacted = message.redacted();
+        assert_eq!(redacted.title, "Waiting for input");
+        assert_eq!(redacted.body, REDACTED);
+        assert_eq!(redacted.data["type"], "waiting_for_input");
+        assert_eq!(redacted.data["sessionId"], "abc");
+        assert_eq!(redacted.data["preview"], REDACTED);
+        assert_eq!(redacted.data["authToken"], REDACTED);
+    }
+}
@@ -0,0 +1,110 @@
+use std::{collections::BTreeMap, sync::Mutex};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::{store, Error};
+
+const STORE_NAME: &str = "metrics";
+
+pub(crate) const NOTIFICATION_RECEIVED: &str = "notification_received";
+pub(crate) const NOTIFICATION_TAPPED: &str = "notification_tapped";
+pub(crate) const NOTIFICATION_SHOWN: &str = "notification_shown";
+
+const MAX_NAME_LEN: usize = 64;
+
+/// Opt-in local usage counters. Nothing here is ever sent anywhere; the
+/// frontend reads them with `get_metrics` and decides what to do with them.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Metrics {
+    pub enabled: bool,
+    /// Event name to how often it happened since the last reset.
+    pub counters: BTreeMap<String, u64>,
+}
+
+pub(crate) struct MetricsState(Mutex<Metrics>);
+
+impl MetricsState {
+    pub(crate) fn load<R: Runtime>(app: &AppHandle<R>) -> Self {
+        Self(Mutex::new(store::load(app, STORE_NAME)))
+    }
+
+    fn update<R: Runtime>(
+        &self,
+        app: &AppHandle<R>,
+        change: impl FnOnce(&mut Metrics),
+    ) -> crate::Result<()> {
+        let mut metrics = self.0.lock().unwrap();
+        change(&mut metrics);
+        store::save(app, STORE_NAME, &*metrics)
+    }
+}
+
+/// Event names are short snake_case identifiers, so nothing identifying
+/// (a session id, a message preview) can end up in a counter name.
+fn validate(name: &str) -> crate::Result<()> {
+    let valid = !name.is_empty()
+        && name.len() <= MAX_NAME_LEN
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(Error::InvalidMetric(name.to_string()))
+    }
+}
+
+/// Count an event if metrics are enabled; a no-op otherwise.
+pub(crate) fn count<R: Runtime>(app: &AppHandle<R>, name: &str) {
+    let state = app.state::<MetricsState>();
+    if !state.0.lock().unwrap().enabled {
+        return;
+    }
+    let result = state.update(app, |metrics| {
+        *metrics.counters.entry(name.to_string()).or_default() += 1;
+    });
+    if let Err(e) = result {
+        log::warn!("FCM plugin: failed to persist metrics: {e}");
+    }
+}
+
+/// Count an event reported by the frontend, e.g. `session_opened`.
+pub(crate) fn record<R: Runtime>(app: &AppHandle<R>, name: &str) -> crate::Result<()> {
+    validate(name)?;
+    count(app, name);
+    Ok(())
+}
+
+pub(crate) fn get<R: Runtime>(app: &AppHandle<R>) -> Metrics {
+    app.state::<MetricsState>().0.lock().unwrap().clone()
+}
+
+/// Turning metrics off also discards what was counted.
+pub(crate) fn set_enabled<R: Runtime>(app: &AppHandle<R>, enabled: bool) -> crate::Result<()> {
+    app.state::<MetricsState>().update(app, |metrics| {
+        metrics.enabled = enabled;
+        if !enabled {
+            metrics.counters.clear();
+        }
+    })
+}
+
+pub(crate) fn reset<R: Runtime>(app: &AppHandle<R>) -> crate::Result<()> {
+    app.state::<MetricsState>()
+        .update(app, |metrics| metrics.counters.clear())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::validate;
+
+    #[test]
+    fn only_snake_case_names_are_accepted() {
+        assert!(validate("session_opened").is_ok());
+        assert!(validate("").is_err());
+        assert!(validate("session:abc123-def").is_err());
+        assert!(validate(&"a".repeat(65)).is_err());
+    }
+}
@@ -0,0 +1,80 @@
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::{clock, store};
+
+const STORE_NAME: &str = "missed";
+
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+struct MissedState {
+    /// Messages sent before the app last came to the foreground but delivered
+    /// after it, i.e. from the backlog rather than live.
+    count: u64,
+    last_resumed_ms: Option<u64>,
+}
+
+/// Counts messages that were waiting while the user was away, for a
+/// "N messages while you were away" hint separate from the unread count.
+pub(crate) struct MissedMessages(Mutex<MissedState>);
+
+impl MissedMessages {
+    pub(crate) fn load<R: Runtime>(app: &AppHandle<R>) -> Self {
+        Self(Mutex::new(store::load(app, STORE_NAME)))
+    }
+
+    fn update<R: Runtime>(&self, app: &AppHandle<R>, change: impl FnOnce(&mut MissedState)) {
+        let mut state = self.0.lock().unwrap();
+        change(&mut state);
+        if let Err(e) = store::save(app, STORE_NAME, &*state) {
+            log::warn!("FCM plugin: failed to persist missed count: {e}");
+        }
+    }
+}
+
+fn is_missed(sent_at: Option<u64>, last_resumed_ms: Option<u64>) -> bool {
+    matches!((sent_at, last_resumed_ms), (Some(sent), Some(resumed)) if sent < resumed)
+}
+
+/// Count a message if the server sent it before the last resume. Messages
+/// without a server timestamp are treated as live.
+#[cfg_attr(not(mobile), allow(dead_code))]
+pub(crate) fn on_message<R: Runtime>(app: &AppHandle<R>, sent_at: Option<u64>) {
+    let missed = app.state::<MissedMessages>();
+    let last_resumed_ms = missed.0.lock().unwrap().last_resumed_ms;
+    if is_missed(sent_at, last_resumed_ms) {
+        missed.update(app, |state| state.count += 1);
+    }
+}
+
+/// Stamped on the server's clock, since it is compared with server send times.
+pub(crate) fn on_resumed<R: Runtime>(app: &AppHandle<R>) {
+    let now = clock::server_now_ms(app);
+    app.state::<MissedMessages>()
+        .update(app, |state| state.last_resumed_ms = Some(now));
+}
+
+pub(crate) fn count<R: Runtime>(app: &AppHandle<R>) -> u64 {
+    app.state::<MissedMessages>().0.lock().unwrap().count
+}
+
+/// Reset the count, e.g. once the user has seen the messages.
+pub(crate) fn clear<R: Runtime>(app: &AppHandle<R>) {
+    app.state::<MissedMessages>()
+        .update(app, |state| state.count = 0);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::is_missed;
+
+    #[test]
+    fn only_messages_sent_before_the_last_resume_are_missed() {
+        assert!(is_missed(Some(100), Some(200)));
+        assert!(!is_missed(Some(300), Some(200)));
+        assert!(!is_missed(None, Some(200)));
+        assert!(!is_missed(Some(100), None));
+    }
+}
@@ -1,36 +1,319 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        RwLock,
+    },
+    time::{Duration, Instant},
+};
+
+use serde::{de::DeserializeOwned, Serialize};
 use tauri::{
+    ipc::{Channel, InvokeResponseBody},
     plugin::{PluginApi, PluginHandle},
     AppHandle, Runtime,
 };
 
-use crate::Error;
+use crate::{
+    appearance, events::NativeEvent, ChannelImportance, DeferredNotification, Error, FcmProject,
+    ForegroundPresentation, InterruptionLevel, PermissionStatus, ProgressUpdate, PushDistributor,
+    QuietHours, ScheduledNotification,
+};
+
+/// Consecutive bridge failures after which the native plugin is registered again.
+const MAX_CONSECUTIVE_FAILURES: u32 = 3;
 
 /// Manages the mobile FCM plugin handle.
-pub struct Fcm<R: Runtime>(Option<PluginHandle<R>>);
+pub struct Fcm<R: Runtime> {
+    handle: RwLock<Option<PluginHandle<R>>>,
+    /// Kept to register the native plugin again if its handle wedges.
+    #[cfg_attr(not(target_os = "android"), allow(dead_code))]
+    api: PluginApi<R, ()>,
+    app: AppHandle<R>,
+    failures: AtomicU32,
+}
 
 /// Initializes the mobile FCM plugin.
-pub fn init<R: Runtime>(
-    app: &AppHandle<R>,
-    api: PluginApi<R, ()>,
-) -> crate::Result<Fcm<R>> {
+pub fn init<R: Runtime>(app: &AppHandle<R>, api: PluginApi<R, ()>) -> crate::Result<Fcm<R>> {
+    let fcm = Fcm {
+        handle: RwLock::new(None),
+        api,
+        app: app.clone(),
+        failures: AtomicU32::new(0),
+    };
+    // No iOS native plugin yet, so the handle stays empty there (no-op)
     #[cfg(target_os = "android")]
     {
-        let handle = api.register_android_plugin("com.hexidecibel.companion.fcm", "FcmPlugin")?;
-        let _ = app;
-        Ok(Fcm(Some(handle)))
-    }
-    #[cfg(not(target_os = "android"))]
-    {
-        // No iOS native plugin yet — return a no-op handle
-        let _ = (app, api);
-        Ok(Fcm(None))
+        *fcm.handle.write().unwrap() = Some(fcm.register()?);
+        if let Err(e) = fcm.register_event_handler(app) {
+            log::warn!("FCM plugin: failed to register native event handler: {e}");
+        }
     }
+    Ok(fcm)
 }
 
 impl<R: Runtime> Fcm<R> {
+    #[cfg(target_os = "android")]
+    fn register(&self) -> crate::Result<PluginHandle<R>> {
+        Ok(self
+            .api
+            .register_android_plugin("com.hexidecibel.companion.fcm", "FcmPlugin")?)
+    }
+
+    fn handle(&self) -> Option<PluginHandle<R>> {
+        self.handle.read().unwrap().clone()
+    }
+
+    /// Call the native plugin, counting consecutive failures. Native rejections
+    /// count too: a wedged handle can surface as one, and a spurious
+    /// re-registration after a run of genuine rejections is harmless.
+    fn run<T: DeserializeOwned>(
+        &self,
+        handle: &PluginHandle<R>,
+        command: &str,
+        payload: impl Serialize,
+    ) -> crate::Result<T> {
+        match handle.run_mobile_plugin(command, payload) {
+            Ok(value) => {
+                self.failures.store(0, Ordering::SeqCst);
+                Ok(value)
+            }
+            Err(e) => {
+                if self.failures.fetch_add(1, Ordering::SeqCst) + 1 == MAX_CONSECUTIVE_FAILURES {
+                    log::warn!("FCM plugin: native bridge keeps failing, reinitializing");
+                    if let Err(e) = self.reinitialize() {
+                        log::error!("FCM plugin: reinitialization failed: {e}");
+                    }
+                }
+                Err(e.into())
+            }
+        }
+    }
+
+    /// Register the native plugin again and replace the handle, recovering
+    /// from a handle invalidated by process recreation. Resets the failure count.
+    pub fn reinitialize(&self) -> crate::Result<()> {
+        log::info!("FCM plugin: reinitializing native plugin");
+        self.failures.store(0, Ordering::SeqCst);
+        #[cfg(target_os = "android")]
+        {
+            *self.handle.write().unwrap() = Some(self.register()?);
+            self.register_event_handler(&self.app)?;
+        }
+        Ok(())
+    }
+
+    /// (Re-)register the channel the native plugin uses to forward events to Rust.
+    /// Native code buffers events until a handler is set, so nothing is lost at startup.
+    /// Returns whether there was a native plugin to register with.
+    pub(crate) fn register_event_handler(&self, app: &AppHandle<R>) -> crate::Result<bool> {
+        let Some(handle) = self.handle() else {
+            return Ok(false);
+        };
+
+        let app = app.clone();
+        let handler = Channel::new(move |body| {
+            if let InvokeResponseBody::Json(json) = body {
+                match serde_json::from_str::<NativeEvent>(&json) {
+                    Ok(event) => crate::events::dispatch(&app, event),
+                    Err(e) => log::warn!("FCM plugin: malformed native event: {e}"),
+                }
+            }
+            Ok(())
+        });
+
+        self.run::<()>(&handle, "setEventHandler", EventHandlerArgs { handler })?;
+        Ok(true)
+    }
+
+    /// Round-trip a no-op call through the native bridge. Returns how long it
+    /// took, or `None` when there is no native plugin.
+    pub fn ping(&self) -> crate::Result<Option<Duration>> {
+        let Some(handle) = self.handle() else {
+            return Ok(None);
+        };
+
+        let start = Instant::now();
+        self.run::<()>(&handle, "ping", ())?;
+        Ok(Some(start.elapsed()))
+    }
+
+    /// Whether the OS is currently bundling this app's notifications into a
+    /// group. `None` where that can't be queried.
+    pub fn notifications_are_grouped(&self) -> crate::Result<Option<bool>> {
+        let Some(handle) = self.handle() else {
+            return Ok(None);
+        };
+
+        #[derive(serde::Deserialize)]
+        struct GroupedResponse {
+            #[serde(default)]
+            grouped: Option<bool>,
+        }
+
+        let result: GroupedResponse = self.run(&handle, "notificationsGrouped", ())?;
+        Ok(result.grouped)
+    }
+
+    /// Post a local notification in group `group_key`. Tapping it opens the
+    /// app like a push notification tap, with `data` as its payload.
+    pub fn show_notification(
+        &self,
+        id: &str,
+        title: &str,
+        body: &str,
+        data: &HashMap<String, String>,
+        group_key: &str,
+        interruption_level: InterruptionLevel,
+    ) -> crate::Result<()> {
+        let Some(handle) = self.handle() else {
+            return Ok(());
+        };
+
+        #[derive(serde::Serialize)]
+        #[serde(rename_all = "camelCase")]
+        struct ShowNotificationArgs<'a> {
+            id: &'a str,
+            title: &'a str,
+            body: &'a str,
+            data: &'a HashMap<String, String>,
+            group_key: &'a str,
+            interruption_level: InterruptionLevel,
+        }
+
+        self.run::<()>(
+            &handle,
+            "showNotification",
+            ShowNotificationArgs {
+                id,
+                title,
+                body,
+                data,
+                group_key,
+                interruption_level,
+            },
+        )
+    }
+
+    /// Have AlarmManager post the notification at its time, surviving app
+    /// restarts and reboots.
+    pub fn schedule_notification(&self, notification: &ScheduledNotification) -> crate::Result<()> {
+        let Some(handle) = self.handle() else {
+            return Err(Error::NotAvailable);
+        };
+        self.run::<()>(&handle, "scheduleNotification", notification)
+    }
+
+    /// Returns whether the notification was still scheduled.
+    pub fn cancel_scheduled_notification(&self, id: &str) -> crate::Result<bool> {
+        let Some(handle) = self.handle() else {
+            return Ok(false);
+        };
+
+        #[derive(serde::Serialize)]
+        struct ScheduledIdArgs<'a> {
+            id: &'a str,
+        }
+
+        #[derive(serde::Deserialize)]
+        struct CancelResponse {
+            cancelled: bool,
+        }
+
+        let result: CancelResponse = self.run(
+            &handle,
+            "cancelScheduledNotification",
+            ScheduledIdArgs { id },
+        )?;
+        Ok(result.cancelled)
+    }
+
+    /// How many notifications are scheduled and not yet posted.
+    pub fn scheduled_count(&self) -> crate::Result<u32> {
+        let Some(handle) = self.handle() else {
+            return Ok(0);
+        };
+
+        #[derive(serde::Deserialize)]
+        struct CountResponse {
+            count: u32,
+        }
+
+        let result: CountResponse = self.run(&handle, "getScheduledCount", ())?;
+        Ok(result.count)
+    }
+
+    /// Post or update an ongoing notification with a progress bar.
+    pub fn show_progress_notification(&self, update: &ProgressUpdate) -> crate::Result<()> {
+        let Some(handle) = self.handle() else {
+            return Ok(());
+        };
+
+        self.run::<()>(&handle, "showProgressNotification", update)
+    }
+
+    /// Replace a progress notification with a final `message`, or remove it.
+    pub fn complete_progress_notification(
+        &self,
+        id: &str,
+        title: &str,
+        message: Option<&str>,
+    ) -> crate::Result<()> {
+        let Some(handle) = self.handle() else {
+            return Ok(());
+        };
+
+        #[derive(serde::Serialize)]
+        struct CompleteProgressArgs<'a> {
+            id: &'a str,
+            title: &'a str,
+            message: Option<&'a str>,
+        }
+
+        self.run::<()>(
+            &handle,
+            "completeProgressNotification",
+            CompleteProgressArgs { id, title, message },
+        )
+    }
+
+    /// Remove the shown notifications belonging to a session. Returns how many.
+    pub fn clear_notifications_for_session(&self, session_id: &str) -> crate::Result<u32> {
+        let Some(handle) = self.handle() else {
+            return Ok(0);
+        };
+
+        #[derive(serde::Serialize)]
+        #[serde(rename_all = "camelCase")]
+        struct SessionArgs<'a> {
+            session_id: &'a str,
+        }
+
+        #[derive(serde::Deserialize)]
+        struct ClearedResponse {
+            cleared: u32,
+        }
+
+        let result: ClearedResponse = self.run(
+            &handle,
+            "clearNotificationsForSession",
+            SessionArgs { session_id },
+        )?;
+        Ok(result.cleared)
+    }
+
+    /// Remove all of this app's notifications from the notification shade.
+    pub fn clear_delivered_notifications(&self) -> crate::Result<()> {
+        let Some(handle) = self.handle() else {
+            return Ok(());
+        };
+
+        self.run::<()>(&handle, "clearDeliveredNotifications", ())
+    }
+
     /// Get the current FCM token, if available.
     pub fn get_token(&self) -> crate::Result<Option<String>> {
-        let Some(handle) = &self.0 else {
+        let Some(handle) = self.handle() else {
             return Ok(None);
         };
 
@@ -39,32 +322,359 @@ impl<R: Runtime> Fcm<R> {
             token: Option<String>,
         }
 
-        let result: TokenResponse = handle
-            .run_mobile_plugin("getToken", ())
-            .map_err(|e| Error::PluginInvoke(e.to_string()))?;
+        let result: TokenResponse = self.run(&handle, "getToken", ())?;
         Ok(result.token)
     }
 
-    /// Request notification permission (Android 13+, iOS always).
-    pub fn request_permission(&self) -> crate::Result<bool> {
-        let Some(handle) = &self.0 else {
-            return Ok(true);
+    /// The sender and project ids of the default Firebase app, as configured
+    /// by `google-services.json`. `None` where there is no native plugin.
+    pub fn get_sender_id(&self) -> crate::Result<Option<FcmProject>> {
+        let Some(handle) = self.handle() else {
+            return Ok(None);
+        };
+
+        self.run(&handle, "getSenderId", ()).map(Some)
+    }
+
+    /// Allow or prevent FCM from generating a token before one is requested.
+    pub fn set_auto_init_enabled(&self, enabled: bool) -> crate::Result<()> {
+        let Some(handle) = self.handle() else {
+            return Ok(());
+        };
+
+        #[derive(serde::Serialize)]
+        struct AutoInitArgs {
+            enabled: bool,
+        }
+
+        self.run::<()>(&handle, "setAutoInitEnabled", AutoInitArgs { enabled })
+    }
+
+    /// Set the payload field the native side groups notifications by.
+    pub fn set_group_key_field(&self, field: &str) -> crate::Result<()> {
+        let Some(handle) = self.handle() else {
+            return Ok(());
+        };
+
+        #[derive(serde::Serialize)]
+        struct GroupKeyFieldArgs<'a> {
+            field: &'a str,
+        }
+
+        self.run::<()>(&handle, "setGroupKeyField", GroupKeyFieldArgs { field })
+    }
+
+    /// Set the interruption level for messages that don't carry their own.
+    pub fn set_interruption_level(&self, level: InterruptionLevel) -> crate::Result<()> {
+        let Some(handle) = self.handle() else {
+            return Ok(());
+        };
+
+        #[derive(serde::Serialize)]
+        struct InterruptionLevelArgs {
+            level: InterruptionLevel,
+        }
+
+        self.run::<()>(
+            &handle,
+            "setInterruptionLevel",
+            InterruptionLevelArgs { level },
+        )
+    }
+
+    /// The hex-encoded cache key kept under `source`, generated first if
+    /// there is none and `create` is set.
+    pub fn get_cache_key(&self, source: &str, create: bool) -> crate::Result<Option<String>> {
+        let Some(handle) = self.handle() else {
+            return Ok(None);
+        };
+
+        #[derive(serde::Serialize)]
+        struct CacheKeyArgs<'a> {
+            source: &'a str,
+            create: bool,
+        }
+
+        #[derive(serde::Deserialize)]
+        struct CacheKeyResponse {
+            key: Option<String>,
+        }
+
+        let result: CacheKeyResponse =
+            self.run(&handle, "getCacheKey", CacheKeyArgs { source, create })?;
+        Ok(result.key)
+    }
+
+    /// Set whether pushes received while the app is running also post a
+    /// system notification.
+    pub fn set_foreground_presentation(
+        &self,
+        options: ForegroundPresentation,
+    ) -> crate::Result<()> {
+        let Some(handle) = self.handle() else {
+            return Ok(());
+        };
+
+        self.run::<()>(&handle, "setForegroundPresentation", options)
+    }
+
+    pub fn create_notification_channel(
+        &self,
+        id: &str,
+        name: &str,
+        importance: ChannelImportance,
+        sound: Option<&str>,
+    ) -> crate::Result<()> {
+        let Some(handle) = self.handle() else {
+            return Ok(());
+        };
+
+        #[derive(serde::Serialize)]
+        struct CreateChannelArgs<'a> {
+            id: &'a str,
+            name: &'a str,
+            importance: ChannelImportance,
+            sound: Option<&'a str>,
+        }
+
+        self.run::<()>(
+            &handle,
+            "createNotificationChannel",
+            CreateChannelArgs {
+                id,
+                name,
+                importance,
+                sound,
+            },
+        )
+    }
+
+    /// Delete a notification channel. Returns whether it existed.
+    pub fn delete_notification_channel(&self, id: &str) -> crate::Result<bool> {
+        let Some(handle) = self.handle() else {
+            return Ok(false);
+        };
+
+        #[derive(serde::Serialize)]
+        struct ChannelIdArgs<'a> {
+            id: &'a str,
+        }
+
+        #[derive(serde::Deserialize)]
+        struct DeletedResponse {
+            deleted: bool,
+        }
+
+        let result: DeletedResponse =
+            self.run(&handle, "deleteNotificationChannel", ChannelIdArgs { id })?;
+        Ok(result.deleted)
+    }
+
+    /// Set the count attached to posted notifications; zero clears it.
+    pub fn set_badge_count(&self, count: u32) -> crate::Result<()> {
+        let Some(handle) = self.handle() else {
+            return Ok(());
+        };
+
+        #[derive(serde::Serialize)]
+        struct BadgeArgs {
+            count: u32,
+        }
+
+        self.run::<()>(&handle, "setBadgeCount", BadgeArgs { count })
+    }
+
+    /// Hand the image download limits to the native side, which applies
+    /// them when a message carries an `image_url`.
+    pub(crate) fn set_image_limits(&self, limits: crate::images::ImageLimits) -> crate::Result<()> {
+        let Some(handle) = self.handle() else {
+            return Ok(());
+        };
+        self.run::<()>(&handle, "setImageLimits", limits)
+    }
+
+    /// Hand the channel, icon and color set on the plugin builder to the
+    /// native side, which uses them until the app configures its own.
+    pub(crate) fn set_appearance_defaults(
+        &self,
+        defaults: &appearance::AppearanceDefaults,
+    ) -> crate::Result<()> {
+        let Some(handle) = self.handle() else {
+            return Ok(());
+        };
+        self.run::<()>(&handle, "setAppearanceDefaults", defaults)
+    }
+
+    pub fn get_push_distributor(&self) -> crate::Result<PushDistributor> {
+        let Some(handle) = self.handle() else {
+            return Ok(PushDistributor::Fcm);
+        };
+
+        #[derive(serde::Deserialize)]
+        struct DistributorResponse {
+            distributor: PushDistributor,
+        }
+
+        let result: DistributorResponse = self.run(&handle, "getPushDistributor", ())?;
+        Ok(result.distributor)
+    }
+
+    /// Switch distributors. The new token follows as a token refresh once
+    /// the distributor hands one out; switching to UnifiedPush fails if no
+    /// distributor app is installed.
+    pub fn set_push_distributor(&self, distributor: PushDistributor) -> crate::Result<()> {
+        let Some(handle) = self.handle() else {
+            return Err(Error::NotAvailable);
+        };
+
+        #[derive(serde::Serialize)]
+        struct DistributorArgs {
+            distributor: PushDistributor,
+        }
+
+        self.run::<()>(
+            &handle,
+            "setPushDistributor",
+            DistributorArgs { distributor },
+        )
+    }
+
+    /// Switch to HMS Push Kit if the device has HMS Core but no Play
+    /// Services and FCM is still the distributor. Returns whether it did.
+    #[cfg(feature = "hms")]
+    pub(crate) fn fall_back_to_hms(&self) -> crate::Result<bool> {
+        let Some(handle) = self.handle() else {
+            return Ok(false);
+        };
+
+        #[derive(serde::Deserialize)]
+        struct FallBackResponse {
+            switched: bool,
+        }
+
+        let result: FallBackResponse = self.run(&handle, "fallBackToHms", ())?;
+        Ok(result.switched)
+    }
+
+    /// Hand the quiet hours to the native side, or with `None` turn them off.
+    pub fn set_quiet_hours(&self, quiet_hours: Option<&QuietHours>) -> crate::Result<()> {
+        let Some(handle) = self.handle() else {
+            return Ok(());
+        };
+
+        #[derive(serde::Serialize)]
+        #[serde(rename_all = "camelCase")]
+        struct QuietHoursArgs<'a> {
+            quiet_hours: Option<&'a QuietHours>,
+        }
+
+        self.run::<()>(&handle, "setQuietHours", QuietHoursArgs { quiet_hours })
+    }
+
+    /// Notifications the native side held back during quiet hours, removed
+    /// from its queue.
+    pub fn take_deferred_notifications(&self) -> crate::Result<Vec<DeferredNotification>> {
+        let Some(handle) = self.handle() else {
+            return Ok(Vec::new());
+        };
+
+        #[derive(serde::Deserialize)]
+        struct DeferredResponse {
+            notifications: Vec<DeferredNotification>,
+        }
+
+        let result: DeferredResponse = self.run(&handle, "takeDeferredNotifications", ())?;
+        Ok(result.notifications)
+    }
+
+    pub(crate) fn set_dedup_ttl(&self, seconds: u64) -> crate::Result<()> {
+        let Some(handle) = self.handle() else {
+            return Ok(());
+        };
+
+        #[derive(serde::Serialize)]
+        #[serde(rename_all = "camelCase")]
+        struct DedupTtlArgs {
+            ttl_ms: u64,
+        }
+
+        self.run::<()>(
+            &handle,
+            "setDedupTtl",
+            DedupTtlArgs {
+                ttl_ms: seconds.saturating_mul(1000),
+            },
+        )
+    }
+
+    /// Raw resources bundled with the app that notifications can play.
+    pub fn available_sounds(&self) -> crate::Result<Vec<String>> {
+        let Some(handle) = self.handle() else {
+            return Ok(Vec::new());
+        };
+
+        #[derive(serde::Deserialize)]
+        struct SoundsResponse {
+            sounds: Vec<String>,
+        }
+
+        let result: SoundsResponse = self.run(&handle, "getAvailableSounds", ())?;
+        Ok(result.sounds)
+    }
+
+    /// Delete the current token so it can no longer receive pushes.
+    /// Returns whether there was a token to delete.
+    pub fn delete_token(&self) -> crate::Result<bool> {
+        let Some(handle) = self.handle() else {
+            return Ok(false);
+        };
+
+        self.run::<()>(&handle, "deleteToken", ())?;
+        Ok(true)
+    }
+
+    /// Request notification permission (Android 13+, iOS always). A blocked
+    /// permission is reported instead of re-requested, since the system
+    /// would dismiss the request without showing anything.
+    pub fn request_permission(&self) -> crate::Result<PermissionStatus> {
+        let Some(handle) = self.handle() else {
+            return Ok(PermissionStatus::Granted);
         };
 
         #[derive(serde::Deserialize)]
         struct PermResponse {
             granted: bool,
+            status: Option<PermissionStatus>,
         }
 
-        let result: PermResponse = handle
-            .run_mobile_plugin("requestPermission", ())
-            .map_err(|e| Error::PluginInvoke(e.to_string()))?;
-        Ok(result.granted)
+        let result: PermResponse = self.run(&handle, "requestPermission", ())?;
+        Ok(result.status.unwrap_or(if result.granted {
+            PermissionStatus::Granted
+        } else {
+            PermissionStatus::Requested
+        }))
+    }
+
+    /// Whether requesting permission would show the system prompt: it is
+    /// neither granted nor blocked.
+    pub fn can_request_permission(&self) -> crate::Result<bool> {
+        let Some(handle) = self.handle() else {
+            return Ok(false);
+        };
+
+        #[derive(serde::Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct CanRequestResponse {
+            can_request: bool,
+        }
+
+        let result: CanRequestResponse = self.run(&handle, "canRequestPermission", ())?;
+        Ok(result.can_request)
     }
 
     /// Check if notification permission is already granted.
     pub fn is_permission_granted(&self) -> crate::Result<bool> {
-        let Some(handle) = &self.0 else {
+        let Some(handle) = self.handle() else {
             return Ok(true);
         };
 
@@ -73,9 +683,148 @@ impl<R: Runtime> Fcm<R> {
             granted: bool,
         }
 
-        let result: PermResponse = handle
-            .run_mobile_plugin("isPermissionGranted", ())
-            .map_err(|e| Error::PluginInvoke(e.to_string()))?;
+        let result: PermResponse = self.run(&handle, "isPermissionGranted", ())?;
         Ok(result.granted)
     }
+
+    /// Open the system settings page for this app's notifications.
+    pub fn open_notification_settings(&self) -> crate::Result<()> {
+        let Some(handle) = self.handle() else {
+            return Err(Error::NotAvailable);
+        };
+
+        self.run::<()>(&handle, "openNotificationSettings", ())
+    }
+
+    /// Whether the device currently has a validated internet connection.
+    pub fn is_online(&self) -> crate::Result<bool> {
+        let Some(handle) = self.handle() else {
+            return Ok(true);
+        };
+
+        #[derive(serde::Deserialize)]
+        struct ConnectivityResponse {
+            online: bool,
+        }
+
+        let result: ConnectivityResponse = self.run(&handle, "getConnectivity", ())?;
+        Ok(result.online)
+    }
+
+    /// Launch the native in-app review flow. Returns whether it was dispatched.
+    pub fn request_in_app_review(&self) -> crate::Result<bool> {
+        let Some(handle) = self.handle() else {
+            return Err(Error::NotAvailable);
+        };
+
+        #[derive(serde::Deserialize)]
+        struct ReviewResponse {
+            dispatched: bool,
+        }
+
+        let result: ReviewResponse = self.run(&handle, "requestInAppReview", ())?;
+        Ok(result.dispatched)
+    }
+
+    /// Set the status-bar icon (a drawable resource name) and accent color used
+    /// for notifications the app displays itself. Persisted natively, so it also
+    /// applies to messages handled while the app isn't running. Without an icon,
+    /// the previously configured one is kept.
+    pub fn configure_notification_appearance(
+        &self,
+        small_icon: Option<String>,
+        accent_color: String,
+    ) -> crate::Result<()> {
+        let Some(handle) = self.handle() else {
+            return Ok(());
+        };
+
+        self.run::<()>(
+            &handle,
+            "configureNotificationAppearance",
+            AppearanceArgs {
+                small_icon: small_icon.clone(),
+                accent_color: accent_color.clone(),
+            },
+        )?;
+        appearance::record_style(&self.app, small_icon, accent_color);
+        Ok(())
+    }
+
+    /// The default notification channel and its sound URI (`None` for the
+    /// system default), neither of which exist before Android 8.
+    pub fn notification_channel_info(&self) -> crate::Result<(Option<String>, Option<String>)> {
+        let Some(handle) = self.handle() else {
+            return Ok((None, None));
+        };
+
+        #[derive(serde::Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct ChannelResponse {
+            default_channel: Option<String>,
+            sound: Option<String>,
+        }
+
+        let result: ChannelResponse = self.run(&handle, "getNotificationChannelInfo", ())?;
+        Ok((result.default_channel, result.sound))
+    }
+
+    /// Set the vibration pattern for notifications the app displays itself.
+    /// An empty pattern disables vibration.
+    pub fn set_vibration_pattern(&self, pattern: Vec<u64>) -> crate::Result<()> {
+        let Some(handle) = self.handle() else {
+            return Ok(());
+        };
+
+        #[derive(serde::Serialize)]
+        struct VibrationArgs {
+            pattern: Vec<u64>,
+        }
+
+        self.run::<()>(
+            &handle,
+            "setVibrationPattern",
+            VibrationArgs {
+                pattern: pattern.clone(),
+            },
+        )?;
+        appearance::record_vibration_pattern(&self.app, pattern);
+        Ok(())
+    }
+
+    /// Subscribe this device to an FCM topic.
+    pub fn subscribe(&self, topic: &str) -> crate::Result<()> {
+        let Some(handle) = self.handle() else {
+            return Ok(());
+        };
+
+        self.run::<()>(&handle, "subscribeToTopic", TopicArgs { topic })
+    }
+
+    /// Unsubscribe this device from an FCM topic.
+    pub fn unsubscribe(&self, topic: &str) -> crate::Result<()> {
+        let Some(handle) = self.handle() else {
+            return Ok(());
+        };
+
+        self.run::<()>(&handle, "unsubscribeFromTopic", TopicArgs { topic })
+    }
+}
+
+#[derive(serde::Serialize)]
+struct TopicArgs<'a> {
+    topic: &'a str,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AppearanceArgs {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    small_icon: Option<String>,
+    accent_color: String,
+}
+
+#[derive(serde::Serialize)]
+struct EventHandlerArgs {
+    handler: Channel,
 }
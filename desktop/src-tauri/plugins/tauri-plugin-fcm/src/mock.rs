@@ -0,0 +1,124 @@
+use std::{collections::HashMap, sync::Mutex};
+
+use serde_json::json;
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::{messages, now_ms, PermissionStatus};
+
+pub(crate) struct MockState {
+    token: Mutex<Option<String>>,
+    permission: Mutex<PermissionStatus>,
+}
+
+impl Default for MockState {
+    fn default() -> Self {
+        Self {
+            token: Mutex::new(None),
+            permission: Mutex::new(PermissionStatus::Granted),
+        }
+    }
+}
+
+/// Plays the native side on desktop so tests can drive the notification flow
+/// without a device: the commands answer with what is set here, and injected
+/// messages go through the same cache, history and events as real ones.
+/// Only built with the `mock` feature.
+pub struct MockFcm<R: Runtime> {
+    app: AppHandle<R>,
+}
+
+impl<R: Runtime> MockFcm<R> {
+    pub fn new(app: &AppHandle<R>) -> Self {
+        Self { app: app.clone() }
+    }
+
+    /// The token `get_fcm_token` returns. A new one is also reported like a
+    /// native refresh, so `fcm://token-refreshed` follows after the debounce.
+    pub fn set_token(&self, value: Option<String>) {
+        *self.app.state::<MockState>().token.lock().unwrap() = value.clone();
+        if let Some(value) = value {
+            crate::token::on_refreshed(&self.app, value);
+        }
+    }
+
+    /// The answer to `request_notification_permission`. `Requested` stands
+    /// for a denial that can be asked again, `Blocked` for "don't ask again";
+    /// the other permission commands follow from it.
+    pub fn set_permission(&self, status: PermissionStatus) {
+        *self.app.state::<MockState>().permission.lock().unwrap() = status;
+    }
+
+    /// Deliver a message as if it had been pushed, emitted as `fcm://message`.
+    pub fn send_message(&self, title: &str, body: &str, data: HashMap<String, String>) {
+        let payload = json!({
+            "messageId": format!("mock-{}", uuid::Uuid::new_v4()),
+            "title": title,
+            "body": body,
+            "data": data,
+            "sentTime": now_ms(),
+        });
+        messages::record(&self.app, &payload);
+    }
+}
+
+pub(crate) fn token<R: Runtime>(app: &AppHandle<R>) -> Option<String> {
+    app.state::<MockState>().token.lock().unwrap().clone()
+}
+
+pub(crate) fn permission<R: Runtime>(app: &AppHandle<R>) -> PermissionStatus {
+    *app.state::<MockState>().permission.lock().unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::mpsc, time::Duration};
+
+    use serde_json::Value;
+    use tauri::{
+        async_runtime::block_on,
+        test::{mock_builder, mock_context, noop_assets},
+        Listener,
+    };
+
+    use super::*;
+    use crate::{commands, MESSAGE_EVENT};
+
+    #[test]
+    fn commands_and_events_follow_the_mock() {
+        let app = mock_builder()
+            .plugin(tauri_plugin_notification::init())
+            .plugin(crate::init())
+            .build(mock_context(noop_assets()))
+            .unwrap();
+        let app = app.handle().clone();
+        let mock = MockFcm::new(&app);
+
+        mock.set_token(Some("mock-token".to_string()));
+        let token = block_on(commands::get_fcm_token(app.clone())).unwrap();
+        assert_eq!(token.as_deref(), Some("mock-token"));
+
+        mock.set_permission(PermissionStatus::Requested);
+        assert_eq!(
+            block_on(commands::request_notification_permission(app.clone())).unwrap(),
+            PermissionStatus::Requested
+        );
+        assert!(block_on(commands::can_request_notification_permission(app.clone())).unwrap());
+        assert!(!block_on(commands::is_notification_permission_granted(app.clone())).unwrap());
+
+        mock.set_permission(PermissionStatus::Granted);
+        assert!(!block_on(commands::can_request_notification_permission(app.clone())).unwrap());
+        assert!(block_on(commands::is_notification_permission_granted(app.clone())).unwrap());
+
+        let (sent, received) = mpsc::channel();
+        app.listen(MESSAGE_EVENT, move |event| {
+            let _ = sent.send(event.payload().to_string());
+        });
+        let data = HashMap::from([("sessionId".to_string(), "abc".to_string())]);
+        mock.send_message("Build finished", "All tests passed", data);
+        let payload = received.recv_timeout(Duration::from_secs(5)).unwrap();
+        let message: Value = serde_json::from_str(&payload).unwrap();
+        assert_eq!(message["title"], "Build finished");
+        assert_eq!(message["body"], "All tests passed");
+        assert_eq!(message["data"]["sessionId"], "abc");
+    }
+}
@@ -0,0 +1,146 @@
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::{metrics, sticky, store, Error};
+
+const STORE_NAME: &str = "notification_action";
+
+/// Emitted with a [`NotificationOpened`] when the user taps a notification.
+pub const NOTIFICATION_OPENED_EVENT: &str = "fcm://notification-opened";
+
+/// Payload keys that name a specific destination for a tap.
+const TARGET_KEYS: [&str; 2] = ["route", "sessionId"];
+
+/// What a tap on a notification without its own target does.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "route", rename_all = "camelCase")]
+pub enum DefaultAction {
+    /// Just bring the app to the front.
+    #[default]
+    ShowWindow,
+    /// Navigate to a frontend route.
+    Navigate(String),
+    /// Leave it to the frontend's `fcm://notification-opened` listener.
+    Custom,
+}
+
+/// How a particular tap was resolved, sent alongside its data.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum OpenedAction {
+    /// The payload carries its own target (`route` or `sessionId`).
+    Target,
+    ShowWindow,
+    Navigate {
+        route: String,
+    },
+    Custom,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct NotificationOpened {
+    pub action: OpenedAction,
+    pub data: Map<String, Value>,
+}
+
+pub(crate) struct NotificationActionState(Mutex<DefaultAction>);
+
+impl NotificationActionState {
+    pub(crate) fn load<R: Runtime>(app: &AppHandle<R>) -> Self {
+        Self(Mutex::new(store::load(app, STORE_NAME)))
+    }
+
+    pub(crate) fn get(&self) -> DefaultAction {
+        self.0.lock().unwrap().clone()
+    }
+}
+
+/// Persist the action for taps on notifications that lack a target.
+pub(crate) fn set_default<R: Runtime>(
+    app: &AppHandle<R>,
+    action: DefaultAction,
+) -> crate::Result<()> {
+    if let DefaultAction::Navigate(route) = &action {
+        if !route.starts_with('/') {
+            return Err(Error::InvalidRoute(route.clone()));
+        }
+    }
+    store::save(app, STORE_NAME, &action)?;
+    *app.state::<NotificationActionState>().0.lock().unwrap() = action;
+    Ok(())
+}
+
+/// A payload's own target wins; otherwise fall back to the configured default.
+fn resolve(data: &Map<String, Value>, default: DefaultAction) -> OpenedAction {
+    let has_target = TARGET_KEYS.iter().any(|key| {
+        data.get(*key)
+            .and_then(Value::as_str)
+            .is_some_and(|v| !v.is_empty())
+    });
+    if has_target {
+        return OpenedAction::Target;
+    }
+    match default {
+        DefaultAction::ShowWindow => OpenedAction::ShowWindow,
+        DefaultAction::Navigate(route) => OpenedAction::Navigate { route },
+        DefaultAction::Custom => OpenedAction::Custom,
+    }
+}
+
+/// Handle a notification tap reported by the native side. The app is already
+/// brought to the front by the OS; this tells the frontend where to go.
+pub(crate) fn on_opened<R: Runtime>(app: &AppHandle<R>, data: Map<String, Value>) {
+    metrics::count(app, metrics::NOTIFICATION_TAPPED);
+    let default = app.state::<NotificationActionState>().get();
+    let action = resolve(&data, default);
+    sticky::emit_sticky(
+        app,
+        NOTIFICATION_OPENED_EVENT,
+        NotificationOpened { action, data },
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn payload(value: Value) -> Map<String, Value> {
+        value.as_object().cloned().unwrap()
+    }
+
+    #[test]
+    fn payload_target_overrides_default() {
+        let data = payload(json!({ "type": "waiting_for_input", "sessionId": "abc" }));
+        let default = DefaultAction::Navigate("/inbox".into());
+        assert_eq!(resolve(&data, default), OpenedAction::Target);
+    }
+
+    #[test]
+    fn untargeted_payload_uses_default() {
+        let data = payload(json!({ "type": "waiting_for_input", "sessionId": "" }));
+        assert_eq!(
+            resolve(&data, DefaultAction::ShowWindow),
+            OpenedAction::ShowWindow
+        );
+        assert_eq!(
+            resolve(&data, DefaultAction::Navigate("/inbox".into())),
+            OpenedAction::Navigate {
+                route: "/inbox".into()
+            }
+        );
+        assert_eq!(resolve(&data, DefaultAction::Custom), OpenedAction::Custom);
+    }
+
+    #[test]
+    fn default_action_wire_format() {
+        let navigate: DefaultAction =
+            serde_json::from_value(json!({ "kind": "navigate", "route": "/inbox" })).unwrap();
+        assert_eq!(navigate, DefaultAction::Navigate("/inbox".into()));
+        let show: DefaultAction = serde_json::from_value(json!({ "kind": "showWindow" })).unwrap();
+        assert_eq!(show, DefaultAction::ShowWindow);
+    }
+}
@@ -0,0 +1,36 @@
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Runtime};
+
+use crate::store;
+
+const STORE_NAME: &str = "foreground_presentation";
+
+/// What the OS shows for a push that arrives while the app is running. The
+/// `fcm://message` event is emitted either way, so by default the frontend
+/// alone decides how to present it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ForegroundPresentation {
+    /// Also post the system notification.
+    pub alert: bool,
+    /// Let it make sound; without, it is posted on the quiet channel.
+    pub sound: bool,
+}
+
+pub(crate) fn get<R: Runtime>(app: &AppHandle<R>) -> ForegroundPresentation {
+    store::load(app, STORE_NAME)
+}
+
+/// Persist the options and hand them to the native side, which receives the
+/// pushes before the webview does.
+pub(crate) fn set<R: Runtime>(
+    app: &AppHandle<R>,
+    options: ForegroundPresentation,
+) -> crate::Result<()> {
+    #[cfg(mobile)]
+    {
+        use crate::FcmExt;
+        app.fcm().set_foreground_presentation(options)?;
+    }
+    store::save(app, STORE_NAME, &options)
+}
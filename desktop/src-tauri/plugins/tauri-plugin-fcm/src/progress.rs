@@ -0,0 +1,141 @@
+use std::{collections::HashMap, sync::Mutex};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+
+use crate::Error;
+
+/// Emitted with a [`ProgressUpdate`] whenever a progress notification changes,
+/// on every platform. The desktop app mirrors it into the tray tooltip.
+pub const PROGRESS_EVENT: &str = "fcm://progress";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProgressUpdate {
+    pub id: String,
+    pub title: String,
+    /// Percent done, 0 to 100; meaningless while `indeterminate`.
+    pub progress: u8,
+    pub indeterminate: bool,
+    /// The operation finished and the notification is gone or final.
+    pub completed: bool,
+}
+
+/// Titles of the progress notifications currently shown, by id, so updates
+/// only need to carry the progress.
+#[derive(Default)]
+pub(crate) struct ProgressNotifications(Mutex<HashMap<String, String>>);
+
+fn validate(progress: u8) -> crate::Result<()> {
+    if progress > 100 {
+        return Err(Error::InvalidProgress(progress));
+    }
+    Ok(())
+}
+
+/// Show (or replace) an ongoing progress notification.
+pub(crate) fn show<R: Runtime>(
+    app: &AppHandle<R>,
+    id: String,
+    title: String,
+    progress: u8,
+    indeterminate: bool,
+) -> crate::Result<()> {
+    validate(progress)?;
+    app.state::<ProgressNotifications>()
+        .0
+        .lock()
+        .unwrap()
+        .insert(id.clone(), title.clone());
+    dispatch(
+        app,
+        ProgressUpdate {
+            id,
+            title,
+            progress,
+            indeterminate,
+            completed: false,
+        },
+        None,
+    )
+}
+
+pub(crate) fn update<R: Runtime>(
+    app: &AppHandle<R>,
+    id: String,
+    progress: u8,
+    indeterminate: bool,
+) -> crate::Result<()> {
+    validate(progress)?;
+    let title = app
+        .state::<ProgressNotifications>()
+        .0
+        .lock()
+        .unwrap()
+        .get(&id)
+        .cloned()
+        .ok_or_else(|| Error::UnknownProgressNotification(id.clone()))?;
+    dispatch(
+        app,
+        ProgressUpdate {
+            id,
+            title,
+            progress,
+            indeterminate,
+            completed: false,
+        },
+        None,
+    )
+}
+
+/// Finish a progress notification: replace it with a dismissable one showing
+/// `message`, or remove it when there is none.
+pub(crate) fn complete<R: Runtime>(
+    app: &AppHandle<R>,
+    id: String,
+    message: Option<String>,
+) -> crate::Result<()> {
+    let title = app
+        .state::<ProgressNotifications>()
+        .0
+        .lock()
+        .unwrap()
+        .remove(&id)
+        .ok_or_else(|| Error::UnknownProgressNotification(id.clone()))?;
+    dispatch(
+        app,
+        ProgressUpdate {
+            id,
+            title,
+            progress: 100,
+            indeterminate: false,
+            completed: true,
+        },
+        message,
+    )
+}
+
+/// Post to the native notification where there is one (Android; iOS has no
+/// arbitrary progress notifications) and tell listeners.
+fn dispatch<R: Runtime>(
+    app: &AppHandle<R>,
+    update: ProgressUpdate,
+    message: Option<String>,
+) -> crate::Result<()> {
+    #[cfg(mobile)]
+    {
+        use crate::FcmExt;
+        if update.completed {
+            app.fcm().complete_progress_notification(
+                &update.id,
+                &update.title,
+                message.as_deref(),
+            )?;
+        } else {
+            app.fcm().show_progress_notification(&update)?;
+        }
+    }
+    #[cfg(not(mobile))]
+    let _ = message;
+    let _ = app.emit(PROGRESS_EVENT, &update);
+    Ok(())
+}
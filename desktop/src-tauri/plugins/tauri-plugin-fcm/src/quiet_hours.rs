@@ -0,0 +1,328 @@
+use std::{collections::HashMap, sync::Mutex, time::Duration};
+
+use chrono::{Datelike, Local, Timelike};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::{
+    interruption::{self, InterruptionLevel},
+    now_ms, sticky, store, Error,
+};
+
+/// Emitted with a [`QuietHoursDigest`] once quiet hours end, if anything was
+/// held back. Sticky, so a webview started afterwards still gets it.
+pub const QUIET_HOURS_DIGEST_EVENT: &str = "fcm://quiet-hours-digest";
+
+const CONFIG_STORE_NAME: &str = "quiet_hours";
+const DEFERRED_STORE_NAME: &str = "quiet_hours_deferred";
+
+/// Messages flagged critical by the server skip quiet hours, like the
+/// `critical` interruption level.
+const CRITICAL_KEY: &str = "critical";
+#[cfg_attr(not(mobile), allow(dead_code))]
+const DIGEST_ID: &str = "quiet-hours-digest";
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// A daily window, in local time, during which non-critical notifications are
+/// held back. A window whose end is before its start runs past midnight and
+/// belongs to the day it starts on.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuietHours {
+    /// `HH:MM`, 24-hour.
+    pub start: String,
+    /// `HH:MM`, 24-hour. Equal to `start` for the whole day.
+    pub end: String,
+    /// Days the window starts on, 0 for Sunday through 6 for Saturday.
+    pub days: Vec<u8>,
+}
+
+impl QuietHours {
+    fn validate(&self) -> crate::Result<()> {
+        let invalid = |reason: &str| Err(Error::InvalidQuietHours(reason.to_string()));
+        if minutes(&self.start).is_none() || minutes(&self.end).is_none() {
+            return invalid("times must be HH:MM");
+        }
+        if self.days.is_empty() || self.days.iter().any(|&day| day > 6) {
+            return invalid("days must be 0 (Sunday) to 6 (Saturday)");
+        }
+        Ok(())
+    }
+
+    /// Whether `minute` past midnight on `weekday` (0 for Sunday) is inside
+    /// the window.
+    fn contains(&self, weekday: u8, minute: u16) -> bool {
+        let (Some(start), Some(end)) = (minutes(&self.start), minutes(&self.end)) else {
+            return false;
+        };
+        let on = |day: u8| self.days.contains(&day);
+        let yesterday = (weekday + 6) % 7;
+        match start.cmp(&end) {
+            std::cmp::Ordering::Equal => on(weekday),
+            std::cmp::Ordering::Less => on(weekday) && (start..end).contains(&minute),
+            std::cmp::Ordering::Greater => {
+                (on(weekday) && minute >= start) || (on(yesterday) && minute < end)
+            }
+        }
+    }
+}
+
+fn minutes(time: &str) -> Option<u16> {
+    let (hours, mins) = time.split_once(':')?;
+    if hours.len() != 2 || mins.len() != 2 {
+        return None;
+    }
+    let (hours, mins): (u16, u16) = (hours.parse().ok()?, mins.parse().ok()?);
+    (hours < 24 && mins < 60).then_some(hours * 60 + mins)
+}
+
+/// A notification held back during quiet hours.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeferredNotification {
+    pub id: String,
+    pub title: String,
+    pub body: String,
+    #[serde(default)]
+    pub data: HashMap<String, String>,
+    /// When it would have been shown, in ms since the Unix epoch.
+    pub deferred_at: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuietHoursDigest {
+    /// Oldest first.
+    pub notifications: Vec<DeferredNotification>,
+}
+
+pub(crate) struct QuietHoursState {
+    config: Mutex<Option<QuietHours>>,
+    deferred: Mutex<Vec<DeferredNotification>>,
+}
+
+impl QuietHoursState {
+    pub(crate) fn load<R: Runtime>(app: &AppHandle<R>) -> Self {
+        Self {
+            config: Mutex::new(store::load(app, CONFIG_STORE_NAME)),
+            deferred: Mutex::new(store::load(app, DEFERRED_STORE_NAME)),
+        }
+    }
+}
+
+pub(crate) fn get<R: Runtime>(app: &AppHandle<R>) -> Option<QuietHours> {
+    app.state::<QuietHoursState>()
+        .config
+        .lock()
+        .unwrap()
+        .clone()
+}
+
+/// Persist the window, or with `None` turn quiet hours off, and hand it to
+/// the native side, which holds back pushes it would display itself.
+/// Turning them off delivers the digest right away.
+pub(crate) fn set<R: Runtime>(
+    app: &AppHandle<R>,
+    quiet_hours: Option<QuietHours>,
+) -> crate::Result<()> {
+    if let Some(quiet_hours) = &quiet_hours {
+        quiet_hours.validate()?;
+    }
+    #[cfg(mobile)]
+    {
+        use crate::FcmExt;
+        app.fcm().set_quiet_hours(quiet_hours.as_ref())?;
+    }
+    store::save(app, CONFIG_STORE_NAME, &quiet_hours)?;
+    *app.state::<QuietHoursState>().config.lock().unwrap() = quiet_hours;
+    if !is_quiet(app) {
+        let app = app.clone();
+        tauri::async_runtime::spawn_blocking(move || deliver_digest(&app));
+    }
+    Ok(())
+}
+
+fn is_quiet<R: Runtime>(app: &AppHandle<R>) -> bool {
+    let now = Local::now();
+    let minute = (now.hour() * 60 + now.minute()) as u16;
+    let weekday = now.weekday().num_days_from_sunday() as u8;
+    get(app).is_some_and(|quiet_hours| quiet_hours.contains(weekday, minute))
+}
+
+/// Hold a notification back if it's quiet hours and it isn't critical.
+/// Returns whether it was deferred, in which case the caller shouldn't show it.
+pub(crate) fn defer<R: Runtime>(
+    app: &AppHandle<R>,
+    id: &str,
+    title: &str,
+    body: &str,
+    data: &HashMap<String, String>,
+) -> bool {
+    let level = interruption::resolve(
+        data.get(interruption::INTERRUPTION_LEVEL_KEY)
+            .map(String::as_str),
+        interruption::default_level(app),
+    );
+    let critical =
+        level == InterruptionLevel::Critical || data.get(CRITICAL_KEY).is_some_and(|c| c == "true");
+    if critical || !is_quiet(app) {
+        return false;
+    }
+
+    let state = app.state::<QuietHoursState>();
+    let mut deferred = state.deferred.lock().unwrap();
+    deferred.push(DeferredNotification {
+        id: id.to_string(),
+        title: title.to_string(),
+        body: body.to_string(),
+        data: data.clone(),
+        deferred_at: now_ms(),
+    });
+    if let Err(e) = store::save(app, DEFERRED_STORE_NAME, &*deferred) {
+        log::warn!("FCM plugin: failed to persist deferred notifications: {e}");
+    }
+    true
+}
+
+/// Check once a minute, and once at startup, whether quiet hours have ended
+/// and deliver what they held back.
+pub(crate) async fn run<R: Runtime>(app: AppHandle<R>) {
+    let mut was_quiet = true;
+    loop {
+        let quiet = is_quiet(&app);
+        if was_quiet && !quiet {
+            let handle = app.clone();
+            let _ = tauri::async_runtime::spawn_blocking(move || deliver_digest(&handle)).await;
+        }
+        was_quiet = quiet;
+        tokio::time::sleep(CHECK_INTERVAL).await;
+    }
+}
+
+/// Emit [`QUIET_HOURS_DIGEST_EVENT`] and post one notification summing up
+/// everything held back, here and by the native side. Blocks on native calls.
+fn deliver_digest<R: Runtime>(app: &AppHandle<R>) {
+    let mut notifications = {
+        let state = app.state::<QuietHoursState>();
+        let mut deferred = state.deferred.lock().unwrap();
+        let taken = std::mem::take(&mut *deferred);
+        if !taken.is_empty() {
+            if let Err(e) = store::save(app, DEFERRED_STORE_NAME, &*deferred) {
+                log::warn!("FCM plugin: failed to persist deferred notifications: {e}");
+            }
+        }
+        taken
+    };
+    #[cfg(mobile)]
+    {
+        use crate::FcmExt;
+        match app.fcm().take_deferred_notifications() {
+            Ok(native) => notifications.extend(native),
+            Err(e) => log::warn!("FCM plugin: could not read deferred notifications: {e}"),
+        }
+    }
+    if notifications.is_empty() {
+        return;
+    }
+    notifications.sort_by_key(|n| n.deferred_at);
+
+    let title = match notifications.len() {
+        1 => notifications[0].title.clone(),
+        count => format!("{count} notifications during quiet hours"),
+    };
+    let body = notifications
+        .iter()
+        .map(|n| n.title.as_str())
+        .collect::<Vec<_>>()
+        .join("\n");
+    if let Err(e) = show(app, &title, &body) {
+        log::warn!("FCM plugin: failed to show quiet hours digest: {e}");
+    }
+    sticky::emit_sticky(
+        app,
+        QUIET_HOURS_DIGEST_EVENT,
+        QuietHoursDigest { notifications },
+    );
+}
+
+#[cfg(mobile)]
+fn show<R: Runtime>(app: &AppHandle<R>, title: &str, body: &str) -> crate::Result<()> {
+    use crate::FcmExt;
+    app.fcm().show_notification(
+        DIGEST_ID,
+        title,
+        body,
+        &HashMap::new(),
+        DIGEST_ID,
+        InterruptionLevel::Active,
+    )
+}
+
+#[cfg(desktop)]
+fn show<R: Runtime>(app: &AppHandle<R>, title: &str, body: &str) -> crate::Result<()> {
+    use tauri_plugin_notification::NotificationExt;
+    app.notification()
+        .builder()
+        .title(title)
+        .body(body)
+        .show()
+        .map_err(|e| Error::PluginInvoke(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn window(start: &str, end: &str, days: &[u8]) -> QuietHours {
+        QuietHours {
+            start: start.to_string(),
+            end: end.to_string(),
+            days: days.to_vec(),
+        }
+    }
+
+    #[test]
+    fn parses_times() {
+        assert_eq!(minutes("00:00"), Some(0));
+        assert_eq!(minutes("22:30"), Some(22 * 60 + 30));
+        for time in ["24:00", "7:00", "07:60", "0700", ""] {
+            assert_eq!(minutes(time), None, "{time}");
+        }
+    }
+
+    #[test]
+    fn same_day_window() {
+        let quiet = window("13:00", "14:00", &[1]);
+        assert!(quiet.contains(1, 13 * 60));
+        assert!(!quiet.contains(1, 14 * 60));
+        assert!(!quiet.contains(2, 13 * 60 + 30));
+    }
+
+    #[test]
+    fn overnight_window_belongs_to_its_start_day() {
+        // Friday 22:00 to Saturday 07:00
+        let quiet = window("22:00", "07:00", &[5]);
+        assert!(quiet.contains(5, 23 * 60));
+        assert!(quiet.contains(6, 6 * 60));
+        assert!(!quiet.contains(6, 23 * 60));
+        assert!(!quiet.contains(5, 6 * 60));
+
+        // Saturday night runs into Sunday
+        let quiet = window("22:00", "07:00", &[6]);
+        assert!(quiet.contains(0, 60));
+    }
+
+    #[test]
+    fn equal_times_cover_the_whole_day() {
+        let quiet = window("00:00", "00:00", &[0, 6]);
+        assert!(quiet.contains(0, 12 * 60));
+        assert!(!quiet.contains(3, 12 * 60));
+    }
+
+    #[test]
+    fn rejects_bad_days() {
+        assert!(window("22:00", "07:00", &[]).validate().is_err());
+        assert!(window("22:00", "07:00", &[7]).validate().is_err());
+        assert!(window("22:00", "07:00", &[0, 6]).validate().is_ok());
+    }
+}
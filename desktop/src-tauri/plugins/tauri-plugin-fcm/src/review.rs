@@ -0,0 +1,38 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Runtime};
+
+use crate::{now_ms, store, FcmExt};
+
+const STORE_NAME: &str = "review";
+
+/// The OS throttles review prompts itself, but quotas are opaque; never ask more
+/// often than this so a chatty caller can't burn through them.
+const MIN_INTERVAL: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
+#[derive(Default, Serialize, Deserialize)]
+struct ReviewState {
+    last_requested_ms: Option<u64>,
+}
+
+/// Ask the OS to show its in-app review sheet, at most once per [`MIN_INTERVAL`].
+/// Returns whether the request was dispatched; whether the user actually left a
+/// review is never exposed by the OS.
+pub(crate) fn request<R: Runtime>(app: &AppHandle<R>) -> crate::Result<bool> {
+    let now = now_ms();
+    let mut state: ReviewState = store::load(app, STORE_NAME);
+    if let Some(last) = state.last_requested_ms {
+        if now.saturating_sub(last) < MIN_INTERVAL.as_millis() as u64 {
+            log::debug!("FCM plugin: skipping in-app review, last requested at {last}");
+            return Ok(false);
+        }
+    }
+
+    let dispatched = app.fcm().request_in_app_review()?;
+    if dispatched {
+        state.last_requested_ms = Some(now);
+        store::save(app, STORE_NAME, &state)?;
+    }
+    Ok(dispatched)
+}
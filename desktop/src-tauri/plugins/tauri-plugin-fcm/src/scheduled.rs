@@ -0,0 +1,253 @@
+use std::collections::HashMap;
+#[cfg(desktop)]
+use std::{sync::Mutex, time::Duration};
+
+use serde::{Deserialize, Serialize};
+#[cfg(desktop)]
+use tauri::{async_runtime::JoinHandle, Manager};
+use tauri::{AppHandle, Runtime};
+
+#[cfg(desktop)]
+use crate::{
+    history::{self, NotificationSource},
+    metrics, now_ms, quiet_hours, sounds, store,
+};
+
+#[cfg(desktop)]
+const STORE_NAME: &str = "scheduled_notifications";
+
+/// Most notifications the OS lets an app have scheduled at once. Android
+/// refuses more than 500 alarms per app and iOS keeps only 64 pending
+/// requests; desktop timers are unbounded.
+const MAX_PENDING: Option<u32> = if cfg!(target_os = "android") {
+    Some(500)
+} else if cfg!(target_os = "ios") {
+    Some(64)
+} else {
+    None
+};
+
+/// A notification to post at a set time.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduledNotification {
+    pub id: String,
+    /// When to post it, in ms since the Unix epoch. A time already past
+    /// posts it right away.
+    pub at: u64,
+    pub title: String,
+    pub body: String,
+    #[serde(default)]
+    pub data: HashMap<String, String>,
+}
+
+/// How many notifications may be scheduled and how many are.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduledLimits {
+    /// `None` where the platform sets no cap.
+    pub max: Option<u32>,
+    pub used: u32,
+}
+
+impl ScheduledLimits {
+    fn check(self) -> crate::Result<()> {
+        match self.max {
+            Some(max) if self.used >= max => Err(crate::Error::ScheduleLimitReached(max)),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Notifications scheduled on desktop and the timers waiting on them.
+/// Persisted so ones scheduled before a restart still fire; one that fell
+/// due while the app wasn't running fires at the next startup.
+#[cfg(desktop)]
+pub(crate) struct ScheduledNotifications {
+    pending: Mutex<Vec<ScheduledNotification>>,
+    timers: Mutex<HashMap<String, JoinHandle<()>>>,
+}
+
+#[cfg(desktop)]
+impl ScheduledNotifications {
+    pub(crate) fn load<R: Runtime>(app: &AppHandle<R>) -> Self {
+        Self {
+            pending: Mutex::new(store::load(app, STORE_NAME)),
+            timers: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn persist<R: Runtime>(
+        &self,
+        app: &AppHandle<R>,
+        pending: &[ScheduledNotification],
+    ) -> crate::Result<()> {
+        store::save(app, STORE_NAME, &pending)
+    }
+}
+
+/// The platform's cap on pending notifications and how many are scheduled.
+pub(crate) fn limits<R: Runtime>(app: &AppHandle<R>) -> crate::Result<ScheduledLimits> {
+    #[cfg(mobile)]
+    let used = {
+        use crate::FcmExt;
+        app.fcm().scheduled_count()?
+    };
+    #[cfg(desktop)]
+    let used = app
+        .state::<ScheduledNotifications>()
+        .pending
+        .lock()
+        .unwrap()
+        .len() as u32;
+    Ok(ScheduledLimits {
+        max: MAX_PENDING,
+        used,
+    })
+}
+
+/// Schedule a notification for `at`. Android hands it to AlarmManager, so it
+/// fires even if the app has been killed; desktop keeps a timer while the
+/// app runs. Returns its id for [`cancel`], or
+/// [`ScheduleLimitReached`](crate::Error::ScheduleLimitReached) once the
+/// platform's cap is hit rather than letting the OS drop it.
+pub(crate) fn schedule<R: Runtime>(
+    app: &AppHandle<R>,
+    at: u64,
+    title: String,
+    body: String,
+    data: HashMap<String, String>,
+) -> crate::Result<String> {
+    limits(app)?.check()?;
+    let notification = ScheduledNotification {
+        id: format!("scheduled-{}", uuid::Uuid::new_v4()),
+        at,
+        title,
+        body,
+        data,
+    };
+    let id = notification.id.clone();
+    #[cfg(mobile)]
+    {
+        use crate::FcmExt;
+        app.fcm().schedule_notification(&notification)?;
+    }
+    #[cfg(desktop)]
+    {
+        let state = app.state::<ScheduledNotifications>();
+        let mut pending = state.pending.lock().unwrap();
+        pending.push(notification.clone());
+        state.persist(app, &pending)?;
+        drop(pending);
+        arm(app, notification);
+    }
+    Ok(id)
+}
+
+/// Returns whether the notification was still scheduled.
+pub(crate) fn cancel<R: Runtime>(app: &AppHandle<R>, id: &str) -> crate::Result<bool> {
+    #[cfg(mobile)]
+    {
+        use crate::FcmExt;
+        app.fcm().cancel_scheduled_notification(id)
+    }
+    #[cfg(desktop)]
+    {
+        let state = app.state::<ScheduledNotifications>();
+        if let Some(timer) = state.timers.lock().unwrap().remove(id) {
+            timer.abort();
+        }
+        let mut pending = state.pending.lock().unwrap();
+        let before = pending.len();
+        pending.retain(|n| n.id != id);
+        if pending.len() == before {
+            return Ok(false);
+        }
+        state.persist(app, &pending)?;
+        Ok(true)
+    }
+}
+
+/// Start the timers for notifications scheduled before the last restart.
+#[cfg(desktop)]
+pub(crate) fn resume<R: Runtime>(app: &AppHandle<R>) {
+    let pending = app
+        .state::<ScheduledNotifications>()
+        .pending
+        .lock()
+        .unwrap()
+        .clone();
+    for notification in pending {
+        arm(app, notification);
+    }
+}
+
+#[cfg(desktop)]
+fn arm<R: Runtime>(app: &AppHandle<R>, notification: ScheduledNotification) {
+    let state = app.state::<ScheduledNotifications>();
+    // Held until the timer is stored, so one already due can't fire first
+    let mut timers = state.timers.lock().unwrap();
+    let id = notification.id.clone();
+    let handle = app.clone();
+    let timer = tauri::async_runtime::spawn(async move {
+        let delay = notification.at.saturating_sub(now_ms());
+        tokio::time::sleep(Duration::from_millis(delay)).await;
+        fire(&handle, notification);
+    });
+    timers.insert(id, timer);
+}
+
+#[cfg(desktop)]
+fn fire<R: Runtime>(app: &AppHandle<R>, notification: ScheduledNotification) {
+    use tauri_plugin_notification::NotificationExt;
+
+    let state = app.state::<ScheduledNotifications>();
+    state.timers.lock().unwrap().remove(&notification.id);
+    {
+        let mut pending = state.pending.lock().unwrap();
+        pending.retain(|n| n.id != notification.id);
+        if let Err(e) = state.persist(app, &pending) {
+            log::warn!("FCM plugin: failed to persist scheduled notifications: {e}");
+        }
+    }
+
+    let ScheduledNotification {
+        id,
+        title,
+        body,
+        data,
+        ..
+    } = notification;
+    if quiet_hours::defer(app, &id, &title, &body, &data) {
+        return;
+    }
+    let builder = app.notification().builder().title(&title).body(&body);
+    let shown = sounds::apply(builder, &data).show();
+    match shown {
+        Ok(()) => {
+            metrics::count(app, metrics::NOTIFICATION_SHOWN);
+            let data = data
+                .into_iter()
+                .map(|(key, value)| (key, value.into()))
+                .collect();
+            history::record(app, id, NotificationSource::Local, title, body, data);
+        }
+        Err(e) => log::warn!("FCM plugin: failed to show scheduled notification: {e}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_schedules_at_the_cap() {
+        let limits = |max, used| ScheduledLimits { max, used };
+        assert!(limits(Some(64), 63).check().is_ok());
+        assert!(matches!(
+            limits(Some(64), 64).check(),
+            Err(crate::Error::ScheduleLimitReached(64))
+        ));
+        assert!(limits(None, 10_000).check().is_ok());
+    }
+}
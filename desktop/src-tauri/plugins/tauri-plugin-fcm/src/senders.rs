@@ -0,0 +1,139 @@
+use std::{
+    collections::BTreeSet,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::store;
+
+const STORE_NAME: &str = "senders";
+
+/// Payload key identifying who sent a message.
+const SENDER_KEY: &str = "sender_id";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SenderMode {
+    #[default]
+    AllowAll,
+    /// Only listed senders get through; messages without a sender are dropped.
+    Allowlist,
+    /// Listed senders are dropped; messages without a sender get through.
+    Blocklist,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SenderPolicy {
+    pub mode: SenderMode,
+    pub ids: BTreeSet<String>,
+}
+
+impl SenderPolicy {
+    fn allows(&self, data: &Map<String, Value>) -> bool {
+        let sender = data.get(SENDER_KEY).and_then(Value::as_str);
+        match self.mode {
+            SenderMode::AllowAll => true,
+            SenderMode::Allowlist => sender.is_some_and(|s| self.ids.contains(s)),
+            SenderMode::Blocklist => !sender.is_some_and(|s| self.ids.contains(s)),
+        }
+    }
+}
+
+/// The policy plus how many messages it has dropped since launch.
+#[derive(Debug, Clone, Serialize)]
+pub struct SenderPolicyState {
+    #[serde(flatten)]
+    pub policy: SenderPolicy,
+    pub blocked_count: u64,
+}
+
+pub(crate) struct SenderFilter {
+    policy: Mutex<SenderPolicy>,
+    blocked: AtomicU64,
+}
+
+impl SenderFilter {
+    pub(crate) fn load<R: Runtime>(app: &AppHandle<R>) -> Self {
+        Self {
+            policy: Mutex::new(store::load(app, STORE_NAME)),
+            blocked: AtomicU64::new(0),
+        }
+    }
+
+    pub(crate) fn state(&self) -> SenderPolicyState {
+        SenderPolicyState {
+            policy: self.policy.lock().unwrap().clone(),
+            blocked_count: self.blocked.load(Ordering::Relaxed),
+        }
+    }
+}
+
+pub(crate) fn set_policy<R: Runtime>(
+    app: &AppHandle<R>,
+    mode: SenderMode,
+    ids: Vec<String>,
+) -> crate::Result<()> {
+    let policy = SenderPolicy {
+        mode,
+        ids: ids.into_iter().collect(),
+    };
+    store::save(app, STORE_NAME, &policy)?;
+    *app.state::<SenderFilter>().policy.lock().unwrap() = policy;
+    Ok(())
+}
+
+/// Whether a message from this payload may be cached and emitted. Drops are counted.
+#[cfg_attr(not(mobile), allow(dead_code))]
+pub(crate) fn admit<R: Runtime>(app: &AppHandle<R>, data: &Map<String, Value>) -> bool {
+    let filter = app.state::<SenderFilter>();
+    let allowed = filter.policy.lock().unwrap().allows(data);
+    if !allowed {
+        filter.blocked.fetch_add(1, Ordering::Relaxed);
+        log::debug!("FCM plugin: dropped message from blocked sender");
+    }
+    allowed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn from(sender: Option<&str>) -> Map<String, Value> {
+        let mut data = Map::new();
+        if let Some(sender) = sender {
+            data.insert(SENDER_KEY.into(), json!(sender));
+        }
+        data
+    }
+
+    fn policy(mode: SenderMode) -> SenderPolicy {
+        SenderPolicy {
+            mode,
+            ids: ["daemon-a".to_string()].into(),
+        }
+    }
+
+    #[test]
+    fn allowlist_admits_only_listed_senders() {
+        let policy = policy(SenderMode::Allowlist);
+        assert!(policy.allows(&from(Some("daemon-a"))));
+        assert!(!policy.allows(&from(Some("daemon-b"))));
+        assert!(!policy.allows(&from(None)));
+    }
+
+    #[test]
+    fn blocklist_drops_only_listed_senders() {
+        let policy = policy(SenderMode::Blocklist);
+        assert!(!policy.allows(&from(Some("daemon-a"))));
+        assert!(policy.allows(&from(Some("daemon-b"))));
+        assert!(policy.allows(&from(None)));
+    }
+}
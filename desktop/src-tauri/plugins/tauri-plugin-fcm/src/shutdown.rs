@@ -0,0 +1,89 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use serde::Serialize;
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::{
+    topics::{self, TopicOp},
+    upload,
+};
+
+/// Whether push activity has been shut down (e.g. on logout). Native events
+/// keep flowing for lifecycle purposes, but token traffic is ignored until
+/// the app starts a new session by fetching a token or configuring uploads.
+#[derive(Default)]
+pub(crate) struct ShutdownState(AtomicBool);
+
+impl ShutdownState {
+    pub(crate) fn is_shut_down(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+
+    /// Leave the shut-down state; called when the app starts using FCM again.
+    pub(crate) fn reset(&self) {
+        self.0.store(false, Ordering::SeqCst);
+    }
+}
+
+/// What [`shutdown`] did.
+#[derive(Debug, Default, Serialize)]
+pub struct ShutdownSummary {
+    /// Nothing was done because FCM was already shut down.
+    pub already_shut_down: bool,
+    pub token_deleted: bool,
+    pub topics_unsubscribed: usize,
+    /// A token was waiting to be uploaded and was dropped.
+    pub upload_cancelled: bool,
+    /// Token refresh events are no longer acted on.
+    pub listeners_detached: bool,
+    pub errors: Vec<String>,
+}
+
+/// Stop all FCM activity: cancel uploads, detach listeners, unsubscribe from
+/// tracked topics and delete the token. Safe to call repeatedly.
+pub(crate) async fn shutdown<R: Runtime>(app: &AppHandle<R>) -> ShutdownSummary {
+    if app.state::<ShutdownState>().0.swap(true, Ordering::SeqCst) {
+        return ShutdownSummary {
+            already_shut_down: true,
+            ..Default::default()
+        };
+    }
+
+    let mut summary = ShutdownSummary {
+        // Stop uploads first so a refresh triggered by the deletion isn't shipped
+        upload_cancelled: upload::cancel(app),
+        listeners_detached: true,
+        ..Default::default()
+    };
+
+    // Unsubscribe while the token still exists; topics are bound to it
+    let tracked = app.state::<topics::Topics>().snapshot();
+    for result in topics::apply(app, tracked, TopicOp::Unsubscribe).await {
+        match result.error {
+            None => summary.topics_unsubscribed += 1,
+            Some(e) => summary
+                .errors
+                .push(format!("unsubscribe {}: {e}", result.topic)),
+        }
+    }
+
+    match delete_token(app) {
+        Ok(deleted) => summary.token_deleted = deleted,
+        Err(e) => summary.errors.push(format!("delete token: {e}")),
+    }
+
+    summary
+}
+
+pub(crate) fn delete_token<R: Runtime>(app: &AppHandle<R>) -> crate::Result<bool> {
+    #[cfg(mobile)]
+    {
+        use crate::FcmExt;
+        app.fcm().delete_token()
+    }
+    #[cfg(not(mobile))]
+    {
+        let _ = app;
+        Ok(false)
+    }
+}
@@ -0,0 +1,33 @@
+use serde_json::Value;
+use tauri::{AppHandle, Runtime};
+
+use crate::{messages, sticky};
+
+/// Emitted once per launch with the data-only messages that arrived while the
+/// app wasn't running, oldest first, as [`crate::CachedMessage`]s. Sticky, so
+/// a webview that attaches its listener late still gets them.
+pub const BACKGROUND_MESSAGES_EVENT: &str = "fcm://background-messages";
+
+/// Take in the messages the native side queued while the app wasn't running.
+/// They go through the cache and history like any other message, but reach
+/// the frontend as one batch instead of individual `fcm://message` events.
+#[cfg(mobile)]
+pub(crate) fn on_native<R: Runtime>(app: &AppHandle<R>, payload: &Value) {
+    deliver(app, payload.get("messages").and_then(Value::as_array));
+}
+
+#[cfg_attr(not(mobile), allow(dead_code))]
+fn deliver<R: Runtime>(app: &AppHandle<R>, queued: Option<&Vec<Value>>) {
+    let delivered: Vec<_> = queued
+        .into_iter()
+        .flatten()
+        .filter_map(|payload| messages::ingest(app, payload))
+        .collect();
+    log::debug!(
+        "FCM plugin: {} messages arrived while not running",
+        delivered.len()
+    );
+    if !delivered.is_empty() {
+        sticky::emit_sticky(app, BACKGROUND_MESSAGES_EVENT, delivered);
+    }
+}
@@ -0,0 +1,105 @@
+#[cfg(desktop)]
+use std::collections::HashMap;
+#[cfg(any(target_os = "macos", target_os = "linux"))]
+use std::path::PathBuf;
+
+use tauri::{AppHandle, Runtime};
+
+/// The payload field naming the sound for one notification.
+#[cfg(desktop)]
+const SOUND_KEY: &str = "sound";
+
+/// The toast sounds Windows plays by name.
+#[cfg(windows)]
+const WINDOWS_SOUNDS: [&str; 5] = ["Default", "IM", "Mail", "Reminder", "SMS"];
+
+/// File names without their extension, for the files in `dirs` that have
+/// one of `extensions`. Missing directories are skipped.
+#[cfg(any(target_os = "macos", target_os = "linux"))]
+fn file_stems(dirs: &[PathBuf], extensions: &[&str]) -> Vec<String> {
+    let mut sounds = Vec::new();
+    for entry in dirs
+        .iter()
+        .filter_map(|dir| std::fs::read_dir(dir).ok())
+        .flatten()
+        .flatten()
+    {
+        let path = entry.path();
+        let known = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| extensions.contains(&ext.to_ascii_lowercase().as_str()));
+        if !known {
+            continue;
+        }
+        if let Some(stem) = path.file_stem().and_then(|stem| stem.to_str()) {
+            sounds.push(stem.to_string());
+        }
+    }
+    sounds
+}
+
+/// NSSound finds names in the app bundle first, then the user, local and
+/// system sound folders.
+#[cfg(target_os = "macos")]
+fn desktop_sounds<R: Runtime>(app: &AppHandle<R>) -> Vec<String> {
+    use tauri::Manager;
+
+    let mut dirs = Vec::new();
+    if let Ok(resources) = app.path().resource_dir() {
+        dirs.push(resources);
+    }
+    if let Ok(home) = app.path().home_dir() {
+        dirs.push(home.join("Library/Sounds"));
+    }
+    dirs.push("/Library/Sounds".into());
+    dirs.push("/System/Library/Sounds".into());
+    file_stems(&dirs, &["aiff", "aif", "caf", "wav"])
+}
+
+/// Names from the freedesktop sound theme, which notification daemons play.
+#[cfg(target_os = "linux")]
+fn desktop_sounds<R: Runtime>(app: &AppHandle<R>) -> Vec<String> {
+    let _ = app;
+    file_stems(
+        &["/usr/share/sounds/freedesktop/stereo".into()],
+        &["oga", "ogg", "wav"],
+    )
+}
+
+#[cfg(windows)]
+fn desktop_sounds<R: Runtime>(app: &AppHandle<R>) -> Vec<String> {
+    let _ = app;
+    WINDOWS_SOUNDS.iter().map(|s| s.to_string()).collect()
+}
+
+/// Sounds a notification can name in its `sound` field: raw resources
+/// bundled with the Android app, or what the desktop OS plays by name.
+/// Empty on iOS.
+pub(crate) fn available<R: Runtime>(app: &AppHandle<R>) -> crate::Result<Vec<String>> {
+    #[cfg(mobile)]
+    {
+        use crate::FcmExt;
+        app.fcm().available_sounds()
+    }
+    #[cfg(desktop)]
+    {
+        let mut sounds = desktop_sounds(app);
+        sounds.sort();
+        sounds.dedup();
+        Ok(sounds)
+    }
+}
+
+/// Play the sound named in the payload's `sound` field, if any. An unknown
+/// name falls back to the OS default.
+#[cfg(desktop)]
+pub(crate) fn apply<R: Runtime>(
+    builder: tauri_plugin_notification::NotificationBuilder<R>,
+    data: &HashMap<String, String>,
+) -> tauri_plugin_notification::NotificationBuilder<R> {
+    match data.get(SOUND_KEY).filter(|sound| !sound.is_empty()) {
+        Some(sound) => builder.sound(sound),
+        None => builder,
+    }
+}
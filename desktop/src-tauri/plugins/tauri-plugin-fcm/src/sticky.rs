@@ -0,0 +1,115 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::Mutex,
+};
+
+use serde::Serialize;
+use serde_json::Value;
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+
+/// Last payload of each sticky event that fired with no listener attached.
+#[derive(Default)]
+pub(crate) struct StickyEvents(Mutex<Sticky>);
+
+#[derive(Default)]
+struct Sticky {
+    pending: BTreeMap<String, Value>,
+    /// Events the page has asked to replay, so it is listening for them and
+    /// later ones reach it directly. Forgotten when the page reloads.
+    listening: BTreeSet<String>,
+}
+
+impl Sticky {
+    /// Keep `payload` unless the page is already listening for `event`.
+    fn record(&mut self, event: &str, payload: &Value) {
+        if !self.listening.contains(event) {
+            self.pending.insert(event.to_string(), payload.clone());
+        }
+    }
+
+    /// Mark `events` as listened to and take whatever they left pending.
+    fn take(&mut self, events: &[String]) -> Vec<(String, Value)> {
+        events
+            .iter()
+            .filter_map(|event| {
+                self.listening.insert(event.clone());
+                self.pending
+                    .remove(event)
+                    .map(|payload| (event.clone(), payload))
+            })
+            .collect()
+    }
+}
+
+/// Emit an event and, while the page isn't listening for it yet, also keep
+/// its payload, so a webview that attaches its listener late (e.g. during a
+/// cold start) can still receive it by calling `replay_sticky_events` with
+/// the event's name. Only the latest payload per event is kept.
+pub fn emit_sticky<R: Runtime, S: Serialize>(app: &AppHandle<R>, event: &str, payload: S) {
+    let value = match serde_json::to_value(payload) {
+        Ok(value) => value,
+        Err(e) => {
+            log::warn!("FCM plugin: could not serialize sticky event {event}: {e}");
+            return;
+        }
+    };
+    app.state::<StickyEvents>()
+        .0
+        .lock()
+        .unwrap()
+        .record(event, &value);
+    let _ = app.emit(event, value);
+}
+
+/// Re-emit the pending payloads of `events`, whose listeners were just
+/// attached, and deliver later ones directly. Returns the event names that
+/// had one.
+pub(crate) fn replay<R: Runtime>(app: &AppHandle<R>, events: &[String]) -> Vec<String> {
+    let pending = app.state::<StickyEvents>().0.lock().unwrap().take(events);
+    pending
+        .into_iter()
+        .map(|(event, payload)| {
+            let _ = app.emit(&event, payload);
+            event
+        })
+        .collect()
+}
+
+/// The page reloaded and its listeners are gone; keep payloads again until
+/// it asks for them.
+pub(crate) fn forget_listeners<R: Runtime>(app: &AppHandle<R>) {
+    app.state::<StickyEvents>()
+        .0
+        .lock()
+        .unwrap()
+        .listening
+        .clear();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn replays_each_event_once_and_only_when_asked() {
+        let mut sticky = Sticky::default();
+        sticky.record("opened", &json!(1));
+        sticky.record("token", &json!("a"));
+
+        // Asking for one event leaves the other for its own listener
+        assert_eq!(
+            sticky.take(&["token".to_string()]),
+            [("token".to_string(), json!("a"))]
+        );
+        assert!(sticky.take(&["token".to_string()]).is_empty());
+
+        // Once listened to, later payloads are delivered live and not kept
+        sticky.record("token", &json!("b"));
+        assert!(sticky.take(&["token".to_string()]).is_empty());
+        assert_eq!(
+            sticky.take(&["opened".to_string()]),
+            [("opened".to_string(), json!(1))]
+        );
+    }
+}
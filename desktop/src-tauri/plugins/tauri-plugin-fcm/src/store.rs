@@ -0,0 +1,72 @@
+use std::{fs, path::PathBuf};
+
+use serde::{de::DeserializeOwned, Serialize};
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::Error;
+
+/// Resolves `<app data dir>/fcm`, where all plugin state lives.
+pub(crate) fn dir<R: Runtime>(app: &AppHandle<R>) -> crate::Result<PathBuf> {
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| Error::Storage(e.to_string()))?;
+    Ok(dir.join("fcm"))
+}
+
+/// Resolves `<app data dir>/fcm/<name>.<extension>`.
+fn path_for<R: Runtime>(app: &AppHandle<R>, name: &str, extension: &str) -> crate::Result<PathBuf> {
+    Ok(dir(app)?.join(format!("{name}.{extension}")))
+}
+
+/// Load a persisted value, falling back to the default if it is missing or unreadable.
+pub(crate) fn load<R: Runtime, T: DeserializeOwned + Default>(app: &AppHandle<R>, name: &str) -> T {
+    let Ok(path) = path_for(app, name, "json") else {
+        return T::default();
+    };
+    match fs::read(&path) {
+        Ok(bytes) => serde_json::from_slice(&bytes).unwrap_or_else(|e| {
+            log::warn!("FCM plugin: ignoring unreadable {}: {e}", path.display());
+            T::default()
+        }),
+        Err(_) => T::default(),
+    }
+}
+
+/// Persist a value, writing to a temp file first so a crash never leaves a torn file.
+pub(crate) fn save<R: Runtime, T: Serialize>(
+    app: &AppHandle<R>,
+    name: &str,
+    value: &T,
+) -> crate::Result<()> {
+    let bytes = serde_json::to_vec_pretty(value).map_err(|e| Error::Storage(e.to_string()))?;
+    write(app, name, "json", &bytes)
+}
+
+/// Raw contents of `<name>.<extension>`, or `None` if it doesn't exist.
+pub(crate) fn read<R: Runtime>(app: &AppHandle<R>, name: &str, extension: &str) -> Option<Vec<u8>> {
+    fs::read(path_for(app, name, extension).ok()?).ok()
+}
+
+/// Write raw bytes to `<name>.<extension>` through a temp file, like [`save`].
+pub(crate) fn write<R: Runtime>(
+    app: &AppHandle<R>,
+    name: &str,
+    extension: &str,
+    bytes: &[u8],
+) -> crate::Result<()> {
+    let path = path_for(app, name, extension)?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| Error::Storage(e.to_string()))?;
+    }
+    let tmp = path.with_extension(format!("{extension}.tmp"));
+    fs::write(&tmp, bytes).map_err(|e| Error::Storage(e.to_string()))?;
+    fs::rename(&tmp, &path).map_err(|e| Error::Storage(e.to_string()))
+}
+
+/// Delete `<name>.<extension>` if it exists.
+pub(crate) fn remove<R: Runtime>(app: &AppHandle<R>, name: &str, extension: &str) {
+    if let Ok(path) = path_for(app, name, extension) {
+        let _ = fs::remove_file(path);
+    }
+}
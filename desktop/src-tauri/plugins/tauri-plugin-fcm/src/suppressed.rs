@@ -0,0 +1,160 @@
+use std::{collections::VecDeque, sync::Mutex};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+
+use crate::{encryption, now_ms};
+
+/// Emitted with a [`SuppressedNotification`] whenever a message is dropped.
+pub const NOTIFICATION_SUPPRESSED_EVENT: &str = "notification-suppressed";
+
+const STORE_NAME: &str = "suppressed_notifications";
+
+/// How many suppressions are kept; the oldest go first.
+const CAPACITY: usize = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SuppressionReason {
+    /// The sender policy rejected the message's sender.
+    SenderPolicy,
+    /// The user turned off all of the app's notifications.
+    NotificationsDisabled,
+    /// The notification's channel is set to "no notifications".
+    ChannelMuted,
+    /// Posting failed for lack of the notification permission.
+    PermissionDenied,
+    /// The same message was delivered moments ago.
+    Duplicate,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SuppressedNotification {
+    pub message_id: String,
+    pub reason: SuppressionReason,
+    /// When it was dropped, in ms since the Unix epoch.
+    pub suppressed_at: u64,
+}
+
+/// Why recent messages never reached the user, to answer "I missed a
+/// notification" reports. Persisted, since those reports usually follow a
+/// restart.
+pub(crate) struct SuppressionLog(Mutex<VecDeque<SuppressedNotification>>);
+
+impl SuppressionLog {
+    pub(crate) fn load<R: Runtime>(app: &AppHandle<R>) -> Self {
+        Self(Mutex::new(encryption::load(app, STORE_NAME)))
+    }
+}
+
+fn push(entries: &mut VecDeque<SuppressedNotification>, entry: SuppressedNotification) {
+    entries.push_back(entry);
+    while entries.len() > CAPACITY {
+        entries.pop_front();
+    }
+}
+
+/// Log a dropped message and tell the frontend.
+pub(crate) fn record<R: Runtime>(
+    app: &AppHandle<R>,
+    message_id: String,
+    reason: SuppressionReason,
+) {
+    log::debug!("FCM plugin: notification {message_id} suppressed: {reason:?}");
+    let entry = SuppressedNotification {
+        message_id,
+        reason,
+        suppressed_at: now_ms(),
+    };
+    let state = app.state::<SuppressionLog>();
+    let mut entries = state.0.lock().unwrap();
+    push(&mut entries, entry.clone());
+    if let Err(e) = encryption::save(app, STORE_NAME, &*entries) {
+        log::warn!("FCM plugin: failed to persist suppressed notifications: {e}");
+    }
+    drop(entries);
+    let _ = app.emit(NOTIFICATION_SUPPRESSED_EVENT, &entry);
+}
+
+/// Persist the log again, e.g. after encryption was turned on or off.
+pub(crate) fn rewrite<R: Runtime>(app: &AppHandle<R>) -> crate::Result<()> {
+    let entries = app.state::<SuppressionLog>().0.lock().unwrap();
+    encryption::save(app, STORE_NAME, &*entries)
+}
+
+/// Load the persisted log again, keeping suppressions recorded since startup
+/// after the older ones.
+#[cfg(mobile)]
+pub(crate) fn reload<R: Runtime>(app: &AppHandle<R>) {
+    let loaded: VecDeque<SuppressedNotification> = encryption::load(app, STORE_NAME);
+    let mut entries = app.state::<SuppressionLog>().0.lock().unwrap();
+    let recorded = std::mem::replace(&mut *entries, loaded);
+    for entry in recorded {
+        push(&mut entries, entry);
+    }
+}
+
+/// Record a suppression the native side detected while posting.
+#[cfg(mobile)]
+pub(crate) fn on_native<R: Runtime>(app: &AppHandle<R>, payload: &serde_json::Value) {
+    let id = payload.get("id").and_then(|id| id.as_str());
+    let reason = payload
+        .get("reason")
+        .and_then(|r| SuppressionReason::deserialize(r).ok());
+    match (id, reason) {
+        (Some(id), Some(reason)) => record(app, id.to_string(), reason),
+        _ => log::warn!("FCM plugin: malformed notificationSuppressed event: {payload}"),
+    }
+}
+
+/// Newest first, at most `limit` entries.
+pub(crate) fn get<R: Runtime>(
+    app: &AppHandle<R>,
+    limit: Option<usize>,
+) -> Vec<SuppressedNotification> {
+    let entries = app.state::<SuppressionLog>().0.lock().unwrap();
+    entries
+        .iter()
+        .rev()
+        .take(limit.unwrap_or(CAPACITY))
+        .cloned()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn keeps_the_most_recent_suppressions() {
+        let mut entries = VecDeque::new();
+        for i in 0..=CAPACITY {
+            push(
+                &mut entries,
+                SuppressedNotification {
+                    message_id: i.to_string(),
+                    reason: SuppressionReason::SenderPolicy,
+                    suppressed_at: 0,
+                },
+            );
+        }
+        assert_eq!(entries.len(), CAPACITY);
+        assert_eq!(entries.front().unwrap().message_id, "1");
+    }
+
+    #[test]
+    fn reasons_match_the_native_names() {
+        for (name, reason) in [
+            ("senderPolicy", SuppressionReason::SenderPolicy),
+            (
+                "notificationsDisabled",
+                SuppressionReason::NotificationsDisabled,
+            ),
+            ("channelMuted", SuppressionReason::ChannelMuted),
+            ("permissionDenied", SuppressionReason::PermissionDenied),
+        ] {
+            assert_eq!(SuppressionReason::deserialize(json!(name)).unwrap(), reason);
+        }
+    }
+}
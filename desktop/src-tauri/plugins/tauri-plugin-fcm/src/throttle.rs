@@ -0,0 +1,121 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+
+use crate::{now_ms, store};
+
+const STORE_NAME: &str = "throttle";
+
+/// Emitted with the message age in seconds when nothing has arrived for longer
+/// than the threshold although delivery should be possible.
+pub const POSSIBLE_THROTTLING_EVENT: &str = "possible-throttling";
+
+const DEFAULT_THRESHOLD: Duration = Duration::from_secs(6 * 60 * 60);
+#[cfg(mobile)]
+const CHECK_INTERVAL: Duration = Duration::from_secs(15 * 60);
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+struct ThrottleConfig {
+    last_message_ms: Option<u64>,
+    threshold_secs: u64,
+}
+
+impl Default for ThrottleConfig {
+    fn default() -> Self {
+        Self {
+            last_message_ms: None,
+            threshold_secs: DEFAULT_THRESHOLD.as_secs(),
+        }
+    }
+}
+
+/// Tracks when the last message arrived, to spot the OS silently holding
+/// back delivery (Doze, App Standby buckets, iOS budgets).
+pub(crate) struct ThrottleMonitor {
+    config: Mutex<ThrottleConfig>,
+    /// Set once the current gap has been reported, so it is reported once.
+    warned: AtomicBool,
+}
+
+impl ThrottleMonitor {
+    pub(crate) fn load<R: Runtime>(app: &AppHandle<R>) -> Self {
+        Self {
+            config: Mutex::new(store::load(app, STORE_NAME)),
+            warned: AtomicBool::new(false),
+        }
+    }
+
+    fn update<R: Runtime>(&self, app: &AppHandle<R>, change: impl FnOnce(&mut ThrottleConfig)) {
+        let mut config = self.config.lock().unwrap();
+        change(&mut config);
+        if let Err(e) = store::save(app, STORE_NAME, &*config) {
+            log::warn!("FCM plugin: failed to persist throttle state: {e}");
+        }
+    }
+}
+
+/// Seconds since the last message arrived, if one ever has.
+pub(crate) fn last_message_age<R: Runtime>(app: &AppHandle<R>) -> Option<u64> {
+    let last = app
+        .state::<ThrottleMonitor>()
+        .config
+        .lock()
+        .unwrap()
+        .last_message_ms?;
+    Some(now_ms().saturating_sub(last) / 1000)
+}
+
+#[cfg_attr(not(mobile), allow(dead_code))]
+pub(crate) fn on_message<R: Runtime>(app: &AppHandle<R>) {
+    let monitor = app.state::<ThrottleMonitor>();
+    monitor.warned.store(false, Ordering::SeqCst);
+    monitor.update(app, |c| c.last_message_ms = Some(now_ms()));
+}
+
+pub(crate) fn set_threshold<R: Runtime>(app: &AppHandle<R>, seconds: u64) {
+    app.state::<ThrottleMonitor>()
+        .update(app, |c| c.threshold_secs = seconds);
+}
+
+/// Whether delivery should currently be possible: online and allowed to notify.
+#[cfg(mobile)]
+fn can_receive<R: Runtime>(app: &AppHandle<R>) -> bool {
+    use crate::FcmExt;
+    let fcm = app.fcm();
+    fcm.is_permission_granted().unwrap_or(false) && fcm.is_online().unwrap_or(false)
+}
+
+/// Periodically compare the last message's age against the threshold and emit
+/// [`POSSIBLE_THROTTLING_EVENT`] once per gap.
+#[cfg(mobile)]
+pub(crate) async fn run<R: Runtime>(app: AppHandle<R>) {
+    loop {
+        tokio::time::sleep(CHECK_INTERVAL).await;
+
+        let monitor = app.state::<ThrottleMonitor>();
+        let threshold = monitor.config.lock().unwrap().threshold_secs;
+        let Some(age) = last_message_age(&app) else {
+            continue;
+        };
+        if age < threshold || monitor.warned.load(Ordering::SeqCst) {
+            continue;
+        }
+
+        let handle = app.clone();
+        let receivable = tauri::async_runtime::spawn_blocking(move || can_receive(&handle))
+            .await
+            .unwrap_or(false);
+        if receivable && !monitor.warned.swap(true, Ordering::SeqCst) {
+            log::info!("FCM plugin: no message for {age}s, delivery may be throttled");
+            let _ = app.emit(POSSIBLE_THROTTLING_EVENT, age);
+        }
+    }
+}
@@ -0,0 +1,156 @@
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::{install_id, sticky, upload, FcmToken};
+
+/// Emitted with an [`FcmToken`] once a token refresh has settled.
+pub const TOKEN_REFRESHED_EVENT: &str = "fcm://token-refreshed";
+
+const BASE_WINDOW: Duration = Duration::from_secs(2);
+const MAX_WINDOW: Duration = Duration::from_secs(16);
+
+/// Trailing-edge debounce for native token refreshes. Some Android devices
+/// fire `onNewToken` several times in a row around install; each update
+/// restarts the timer, and updates arriving inside the current window double
+/// it (up to [`MAX_WINDOW`]) so a burst collapses into a single emission.
+/// Repeats of the latest token are dropped outright.
+#[derive(Debug)]
+struct Debounce {
+    /// Last token handed to the frontend and the upload queue.
+    emitted: Option<String>,
+    /// Newest token waiting for its window to elapse.
+    pending: Option<String>,
+    /// Bumped on every accepted update; a timer only fires if it still matches.
+    generation: u64,
+    window: Duration,
+    last_update: Option<Instant>,
+}
+
+impl Default for Debounce {
+    fn default() -> Self {
+        Self {
+            emitted: None,
+            pending: None,
+            generation: 0,
+            window: BASE_WINDOW,
+            last_update: None,
+        }
+    }
+}
+
+impl Debounce {
+    /// Record a token update. Returns the generation and delay to schedule a
+    /// timer for, or `None` if the token is a repeat of the latest one.
+    fn update(&mut self, token: String, now: Instant) -> Option<(u64, Duration)> {
+        let latest = self.pending.as_ref().or(self.emitted.as_ref());
+        if latest == Some(&token) {
+            return None;
+        }
+
+        self.window = match self.last_update {
+            Some(last) if now.duration_since(last) < self.window => {
+                (self.window * 2).min(MAX_WINDOW)
+            }
+            _ => BASE_WINDOW,
+        };
+        self.last_update = Some(now);
+        self.pending = Some(token);
+        self.generation += 1;
+        Some((self.generation, self.window))
+    }
+
+    /// A timer for `generation` elapsed. Returns the token to emit, if that
+    /// timer is still the latest and the token differs from the last emitted.
+    fn fire(&mut self, generation: u64) -> Option<String> {
+        if generation != self.generation {
+            return None;
+        }
+        let token = self.pending.take()?;
+        if self.emitted.as_ref() == Some(&token) {
+            return None;
+        }
+        self.emitted = Some(token.clone());
+        Some(token)
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct TokenState(Mutex<Debounce>);
+
+/// Feed a token reported by the native side through the debounce. The settled
+/// token is emitted as [`TOKEN_REFRESHED_EVENT`] and queued for upload.
+pub(crate) fn on_refreshed<R: Runtime>(app: &AppHandle<R>, token: String) {
+    let scheduled = app
+        .state::<TokenState>()
+        .0
+        .lock()
+        .unwrap()
+        .update(token, Instant::now());
+    let Some((generation, delay)) = scheduled else {
+        return;
+    };
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(delay).await;
+        let settled = app.state::<TokenState>().0.lock().unwrap().fire(generation);
+        if let Some(token) = settled {
+            let payload = FcmToken {
+                token: token.clone(),
+                platform: std::env::consts::OS.to_string(),
+                install_id: install_id::get(&app),
+            };
+            sticky::emit_sticky(&app, TOKEN_REFRESHED_EVENT, payload);
+            upload::enqueue(&app, token);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn duplicate_then_changed_token_emits_once_per_token() {
+        let start = Instant::now();
+        let mut debounce = Debounce::default();
+
+        let (first, delay) = debounce.update("a".into(), start).unwrap();
+        assert_eq!(delay, BASE_WINDOW);
+        // A repeat while pending is dropped without rescheduling
+        assert_eq!(debounce.update("a".into(), start), None);
+
+        // A genuinely new token inside the window supersedes and widens it
+        let (second, delay) = debounce
+            .update("b".into(), start + Duration::from_millis(500))
+            .unwrap();
+        assert_eq!(delay, BASE_WINDOW * 2);
+
+        assert_eq!(debounce.fire(first), None);
+        assert_eq!(debounce.fire(second).as_deref(), Some("b"));
+
+        // Once emitted, the same token is a repeat
+        assert_eq!(debounce.update("b".into(), start + MAX_WINDOW), None);
+    }
+
+    #[test]
+    fn window_grows_during_burst_and_resets_after_quiet() {
+        let start = Instant::now();
+        let mut debounce = Debounce::default();
+        let mut now = start;
+        let mut delays = Vec::new();
+        for token in ["a", "b", "c", "d", "e"] {
+            delays.push(debounce.update(token.into(), now).unwrap().1);
+            now += Duration::from_millis(100);
+        }
+        assert_eq!(delays, [2, 4, 8, 16, 16].map(Duration::from_secs).to_vec());
+
+        let (generation, _) = debounce.update("f".into(), now + MAX_WINDOW).unwrap();
+        assert_eq!(debounce.window, BASE_WINDOW);
+        assert_eq!(debounce.fire(generation).as_deref(), Some("f"));
+    }
+}
@@ -0,0 +1,166 @@
+use std::{collections::BTreeSet, sync::Mutex};
+
+use serde::Serialize;
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::{store, Error};
+
+const STORE_NAME: &str = "topics";
+
+/// Topics the client wants to be subscribed to, persisted so they can be re-asserted.
+#[derive(Default)]
+pub(crate) struct Topics(Mutex<BTreeSet<String>>);
+
+/// Outcome of a single topic (un)subscription within a batch.
+#[derive(Debug, Clone, Serialize)]
+pub struct TopicResult {
+    pub topic: String,
+    pub ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum TopicOp {
+    Subscribe,
+    Unsubscribe,
+}
+
+impl Topics {
+    pub(crate) fn load<R: Runtime>(app: &AppHandle<R>) -> Self {
+        Self(Mutex::new(store::load(app, STORE_NAME)))
+    }
+
+    pub(crate) fn snapshot(&self) -> Vec<String> {
+        self.0.lock().unwrap().iter().cloned().collect()
+    }
+
+    fn persist<R: Runtime>(&self, app: &AppHandle<R>) {
+        let set = self.0.lock().unwrap();
+        if let Err(e) = store::save(app, STORE_NAME, &*set) {
+            log::warn!("FCM plugin: failed to persist topics: {e}");
+        }
+    }
+}
+
+/// FCM topic names must match `[a-zA-Z0-9-_.~%]{1,900}`.
+pub(crate) fn validate(topic: &str) -> crate::Result<()> {
+    let valid_char = |c: char| c.is_ascii_alphanumeric() || "-_.~%".contains(c);
+    if topic.is_empty() || topic.len() > 900 || !topic.chars().all(valid_char) {
+        return Err(Error::InvalidTopic(topic.to_string()));
+    }
+    Ok(())
+}
+
+fn native<R: Runtime>(app: &AppHandle<R>, topic: &str, op: TopicOp) -> crate::Result<()> {
+    #[cfg(mobile)]
+    {
+        use crate::FcmExt;
+        match op {
+            TopicOp::Subscribe => app.fcm().subscribe(topic),
+            TopicOp::Unsubscribe => app.fcm().unsubscribe(topic),
+        }
+    }
+    #[cfg(not(mobile))]
+    {
+        let _ = (app, topic, op);
+        Ok(())
+    }
+}
+
+/// Validate and (un)subscribe every topic, returning each with its outcome in
+/// order. Successful operations update the tracked topic set.
+async fn run<R: Runtime>(
+    app: &AppHandle<R>,
+    topics: Vec<String>,
+    op: TopicOp,
+) -> Vec<(String, crate::Result<()>)> {
+    // Kick off every native call before awaiting any so they run concurrently.
+    let pending: Vec<_> = topics
+        .into_iter()
+        .map(|topic| {
+            let task = validate(&topic).map(|()| {
+                let app = app.clone();
+                let topic = topic.clone();
+                tauri::async_runtime::spawn_blocking(move || native(&app, &topic, op))
+            });
+            (topic, task)
+        })
+        .collect();
+
+    let mut outcomes = Vec::with_capacity(pending.len());
+    for (topic, task) in pending {
+        let outcome = match task {
+            Ok(handle) => handle
+                .await
+                .unwrap_or_else(|e| Err(Error::PluginInvoke(e.to_string()))),
+            Err(e) => Err(e),
+        };
+        outcomes.push((topic, outcome));
+    }
+
+    let state = app.state::<Topics>();
+    let changed = {
+        let mut set = state.0.lock().unwrap();
+        let mut changed = false;
+        for (topic, _) in outcomes.iter().filter(|(_, outcome)| outcome.is_ok()) {
+            changed |= match op {
+                TopicOp::Subscribe => set.insert(topic.clone()),
+                TopicOp::Unsubscribe => set.remove(topic),
+            };
+        }
+        changed
+    };
+    if changed {
+        state.persist(app);
+    }
+
+    outcomes
+}
+
+/// [`run`] with one result per input, for the batch commands.
+pub(crate) async fn apply<R: Runtime>(
+    app: &AppHandle<R>,
+    topics: Vec<String>,
+    op: TopicOp,
+) -> Vec<TopicResult> {
+    run(app, topics, op)
+        .await
+        .into_iter()
+        .map(|(topic, outcome)| TopicResult {
+            ok: outcome.is_ok(),
+            error: outcome.err().map(|e| e.to_string()),
+            topic,
+        })
+        .collect()
+}
+
+/// [`run`] for a single topic, failing with its error.
+pub(crate) async fn apply_one<R: Runtime>(
+    app: &AppHandle<R>,
+    topic: String,
+    op: TopicOp,
+) -> crate::Result<()> {
+    match run(app, vec![topic], op).await.pop() {
+        Some((_, outcome)) => outcome,
+        None => Ok(()),
+    }
+}
+
+/// Re-assert every tracked subscription with the native SDK. FCM keeps topic
+/// subscriptions across token changes, but re-subscribing is idempotent and
+/// covers installs where the SDK state was reset.
+pub(crate) async fn reapply<R: Runtime>(app: &AppHandle<R>) {
+    let topics = app.state::<Topics>().snapshot();
+    if topics.is_empty() {
+        return;
+    }
+    for result in apply(app, topics, TopicOp::Subscribe).await {
+        if let Some(error) = result.error {
+            log::warn!(
+                "FCM plugin: failed to re-subscribe to {}: {error}",
+                result.topic
+            );
+        }
+    }
+}
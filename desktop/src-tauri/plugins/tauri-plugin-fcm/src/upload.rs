@@ -0,0 +1,314 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+    time::Duration,
+};
+
+use reqwest::{
+    header::{HeaderName, HeaderValue, DATE},
+    Response, StatusCode,
+};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager, Runtime, Url};
+use tokio::sync::Notify;
+
+use crate::{clock, install_id, now_ms, store, Error, FcmToken};
+
+const STORE_NAME: &str = "upload";
+
+/// Emitted when the upload endpoint rejects our credentials. The frontend should
+/// refresh them and call `set_token_upload_headers`; the queue waits until then.
+pub const TOKEN_UPLOAD_UNAUTHORIZED_EVENT: &str = "token-upload-unauthorized";
+
+const INITIAL_BACKOFF: Duration = Duration::from_secs(2);
+const MAX_BACKOFF: Duration = Duration::from_secs(5 * 60);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Only the endpoint is persisted; headers usually carry bearer tokens and stay in memory.
+#[derive(Default, Serialize, Deserialize)]
+struct PersistedConfig {
+    endpoint: Option<String>,
+}
+
+/// Snapshot of the upload queue for `get_token_upload_state`.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct TokenUploadState {
+    pub paused: bool,
+    /// A token is waiting to be uploaded.
+    pub pending: bool,
+    /// When the last upload attempt finished, in ms since the Unix epoch.
+    pub last_attempt: Option<u64>,
+    /// Error from the last attempt; cleared by a successful upload.
+    pub last_error: Option<String>,
+}
+
+/// Uploads the latest FCM token to a configured endpoint, retrying with backoff.
+/// Only the most recent token is kept: an older one is never worth uploading.
+pub(crate) struct TokenUpload {
+    endpoint: Mutex<Option<String>>,
+    headers: Mutex<HashMap<String, String>>,
+    pending: Mutex<Option<FcmToken>>,
+    paused: AtomicBool,
+    /// Set on resume so the worker restarts its backoff from the initial delay.
+    reset_backoff: AtomicBool,
+    last_attempt: Mutex<Option<(u64, Option<String>)>>,
+    wake: Notify,
+    client: reqwest::Client,
+}
+
+enum UploadError {
+    Unauthorized,
+    Failed(String),
+}
+
+impl TokenUpload {
+    pub(crate) fn load<R: Runtime>(app: &AppHandle<R>) -> Self {
+        let config: PersistedConfig = store::load(app, STORE_NAME);
+        Self {
+            endpoint: Mutex::new(config.endpoint),
+            headers: Mutex::default(),
+            pending: Mutex::default(),
+            paused: AtomicBool::new(false),
+            reset_backoff: AtomicBool::new(false),
+            last_attempt: Mutex::default(),
+            wake: Notify::new(),
+            client: reqwest::Client::builder()
+                .timeout(REQUEST_TIMEOUT)
+                .build()
+                .unwrap_or_default(),
+        }
+    }
+
+    fn next_job(&self) -> Option<(String, FcmToken)> {
+        if self.paused.load(Ordering::SeqCst) {
+            return None;
+        }
+        let endpoint = self.endpoint.lock().unwrap().clone()?;
+        let token = self.pending.lock().unwrap().clone()?;
+        Some((endpoint, token))
+    }
+
+    /// Clear the pending token unless a newer one arrived during the upload.
+    fn finish(&self, uploaded: &FcmToken) {
+        let mut pending = self.pending.lock().unwrap();
+        if pending.as_ref().map(|t| &t.token) == Some(&uploaded.token) {
+            *pending = None;
+        }
+    }
+
+    fn record_attempt(&self, error: Option<String>) {
+        *self.last_attempt.lock().unwrap() = Some((now_ms(), error));
+    }
+
+    async fn upload<R: Runtime>(
+        &self,
+        app: &AppHandle<R>,
+        endpoint: &str,
+        token: &FcmToken,
+    ) -> Result<(), UploadError> {
+        // Read headers per attempt so rotated credentials apply to retries
+        let headers = self.headers.lock().unwrap().clone();
+        let mut request = self.client.post(endpoint).json(token);
+        for (name, value) in headers {
+            request = request.header(name, value);
+        }
+
+        let sent_ms = now_ms();
+        let response = request
+            .send()
+            .await
+            .map_err(|e| UploadError::Failed(e.to_string()))?;
+        record_server_date(app, &response, sent_ms);
+        match response.status() {
+            StatusCode::UNAUTHORIZED => Err(UploadError::Unauthorized),
+            status if status.is_success() => Ok(()),
+            status => Err(UploadError::Failed(format!("HTTP {status}"))),
+        }
+    }
+}
+
+/// Measure the device clock against the response's `Date` header, whatever
+/// its status.
+fn record_server_date<R: Runtime>(app: &AppHandle<R>, response: &Response, sent_ms: u64) {
+    if let Some(date) = response.headers().get(DATE).and_then(|v| v.to_str().ok()) {
+        clock::on_server_date(app, date, sent_ms, now_ms());
+    }
+}
+
+/// Check the device clock at startup with a `HEAD` to the upload endpoint,
+/// the one server we know, rather than waiting for the next token upload.
+pub(crate) async fn probe_clock<R: Runtime>(app: AppHandle<R>) {
+    let state = app.state::<TokenUpload>();
+    let Some(endpoint) = state.endpoint.lock().unwrap().clone() else {
+        return;
+    };
+    let sent_ms = now_ms();
+    match state.client.head(&endpoint).send().await {
+        Ok(response) => record_server_date(&app, &response, sent_ms),
+        Err(e) => log::debug!("FCM plugin: clock probe failed: {e}"),
+    }
+}
+
+fn validate_headers(headers: &HashMap<String, String>) -> crate::Result<()> {
+    for (name, value) in headers {
+        HeaderName::from_bytes(name.as_bytes()).map_err(|_| Error::InvalidHeader(name.clone()))?;
+        HeaderValue::from_str(value).map_err(|_| Error::InvalidHeader(name.clone()))?;
+    }
+    Ok(())
+}
+
+/// Set the upload endpoint (persisted) and optionally its headers (memory only),
+/// then queue the current token so the new endpoint learns about it.
+pub(crate) fn configure<R: Runtime>(
+    app: &AppHandle<R>,
+    endpoint: String,
+    headers: Option<HashMap<String, String>>,
+) -> crate::Result<()> {
+    let url: Url = endpoint
+        .parse()
+        .map_err(|_| Error::InvalidEndpoint(endpoint.clone()))?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(Error::InvalidEndpoint(endpoint));
+    }
+    if let Some(headers) = &headers {
+        validate_headers(headers)?;
+    }
+
+    let state = app.state::<TokenUpload>();
+    *state.endpoint.lock().unwrap() = Some(endpoint.clone());
+    if let Some(headers) = headers {
+        *state.headers.lock().unwrap() = headers;
+    }
+    store::save(
+        app,
+        STORE_NAME,
+        &PersistedConfig {
+            endpoint: Some(endpoint),
+        },
+    )?;
+
+    #[cfg(mobile)]
+    {
+        let app = app.clone();
+        tauri::async_runtime::spawn_blocking(move || {
+            use crate::FcmExt;
+            match app.fcm().get_token() {
+                Ok(Some(token)) => enqueue(&app, token),
+                Ok(None) => {}
+                Err(e) => log::warn!("FCM plugin: could not read token for upload: {e}"),
+            }
+        });
+    }
+    state.wake.notify_one();
+    Ok(())
+}
+
+/// Replace the upload headers without touching the endpoint. Wakes a queue
+/// that was waiting on fresh credentials.
+pub(crate) fn set_headers<R: Runtime>(
+    app: &AppHandle<R>,
+    headers: HashMap<String, String>,
+) -> crate::Result<()> {
+    validate_headers(&headers)?;
+    let state = app.state::<TokenUpload>();
+    *state.headers.lock().unwrap() = headers;
+    state.wake.notify_one();
+    Ok(())
+}
+
+/// Queue a token for upload, replacing any token still waiting.
+pub(crate) fn enqueue<R: Runtime>(app: &AppHandle<R>, token: String) {
+    let state = app.state::<TokenUpload>();
+    *state.pending.lock().unwrap() = Some(FcmToken {
+        token,
+        platform: std::env::consts::OS.to_string(),
+        install_id: install_id::get(app),
+    });
+    state.wake.notify_one();
+}
+
+/// Drop any token waiting for upload along with the in-memory credentials.
+/// Returns whether a token was pending.
+pub(crate) fn cancel<R: Runtime>(app: &AppHandle<R>) -> bool {
+    let state = app.state::<TokenUpload>();
+    state.headers.lock().unwrap().clear();
+    state.pending.lock().unwrap().take().is_some()
+}
+
+/// Drop a token waiting for upload, keeping the configuration.
+pub(crate) fn discard_pending<R: Runtime>(app: &AppHandle<R>) {
+    app.state::<TokenUpload>().pending.lock().unwrap().take();
+}
+
+/// Stop uploading until [`resume`]; a queued token is kept, not dropped.
+pub(crate) fn pause<R: Runtime>(app: &AppHandle<R>) {
+    let state = app.state::<TokenUpload>();
+    state.paused.store(true, Ordering::SeqCst);
+    // Interrupt a backoff sleep so the worker parks right away
+    state.wake.notify_one();
+}
+
+/// Resume uploading, starting the backoff over from the initial delay.
+pub(crate) fn resume<R: Runtime>(app: &AppHandle<R>) {
+    let state = app.state::<TokenUpload>();
+    state.reset_backoff.store(true, Ordering::SeqCst);
+    state.paused.store(false, Ordering::SeqCst);
+    state.wake.notify_one();
+}
+
+pub(crate) fn state<R: Runtime>(app: &AppHandle<R>) -> TokenUploadState {
+    let upload = app.state::<TokenUpload>();
+    let (last_attempt, last_error) = match upload.last_attempt.lock().unwrap().clone() {
+        Some((at, error)) => (Some(at), error),
+        None => (None, None),
+    };
+    TokenUploadState {
+        paused: upload.paused.load(Ordering::SeqCst),
+        pending: upload.pending.lock().unwrap().is_some(),
+        last_attempt,
+        last_error,
+    }
+}
+
+/// Background worker draining the upload queue. Failed uploads back off
+/// exponentially; a 401 parks the queue until new headers or a new token arrive.
+pub(crate) async fn run<R: Runtime>(app: AppHandle<R>) {
+    let state = app.state::<TokenUpload>();
+    let mut backoff = INITIAL_BACKOFF;
+    loop {
+        if state.reset_backoff.swap(false, Ordering::SeqCst) {
+            backoff = INITIAL_BACKOFF;
+        }
+        let Some((endpoint, token)) = state.next_job() else {
+            state.wake.notified().await;
+            continue;
+        };
+
+        match state.upload(&app, &endpoint, &token).await {
+            Ok(()) => {
+                state.record_attempt(None);
+                state.finish(&token);
+                backoff = INITIAL_BACKOFF;
+            }
+            Err(UploadError::Unauthorized) => {
+                log::warn!("FCM plugin: token upload unauthorized");
+                state.record_attempt(Some("Unauthorized".to_string()));
+                let _ = app.emit(TOKEN_UPLOAD_UNAUTHORIZED_EVENT, ());
+                state.wake.notified().await;
+                backoff = INITIAL_BACKOFF;
+            }
+            Err(UploadError::Failed(e)) => {
+                log::warn!("FCM plugin: token upload failed, retrying in {backoff:?}: {e}");
+                state.record_attempt(Some(e));
+                tokio::select! {
+                    _ = tokio::time::sleep(backoff) => {}
+                    _ = state.wake.notified() => {}
+                }
+                backoff = (backoff * 2).min(MAX_BACKOFF);
+            }
+        }
+    }
+}
@@ -0,0 +1,35 @@
+use tauri::{window::Color, Emitter};
+
+/// Emitted with the new `#RRGGBB` color so the frontend's CSS variables follow.
+const ACCENT_COLOR_CHANGED_EVENT: &str = "accent-color-changed";
+
+/// Parse `#RRGGBB`.
+pub fn parse(hex: &str) -> Option<Color> {
+    let digits = hex.strip_prefix('#').filter(|d| d.len() == 6)?;
+    let channel = |i: usize| u8::from_str_radix(digits.get(i..i + 2)?, 16).ok();
+    Some(Color(channel(0)?, channel(2)?, channel(4)?, 255))
+}
+
+/// Set the app's accent color. On desktop it is persisted and tints window
+/// chrome where the platform allows (Windows 11 caption, macOS background);
+/// on Android it becomes the notification accent, persisted natively.
+#[tauri::command]
+pub fn set_accent_color(app: tauri::AppHandle, hex: String) -> Result<(), String> {
+    let Some(color) = parse(&hex) else {
+        return Err(format!("Invalid color: {hex} (expected #RRGGBB)"));
+    };
+
+    #[cfg(desktop)]
+    crate::desktop::set_accent_color(&app, &hex, color)?;
+    #[cfg(mobile)]
+    {
+        use tauri_plugin_fcm::FcmExt;
+        let _ = color;
+        app.fcm()
+            .configure_notification_appearance(None, hex.clone())
+            .map_err(|e| format!("{e}"))?;
+    }
+
+    let _ = app.emit(ACCENT_COLOR_CHANGED_EVENT, &hex);
+    Ok(())
+}
@@ -0,0 +1,60 @@
+use serde::Serialize;
+
+/// What this binary is, for the About box and channel-aware update checks.
+/// The values are baked in by `build.rs`.
+#[derive(Debug, Clone, Serialize)]
+pub struct BuildInfo {
+    pub version: String,
+    /// Short git hash, or `unknown` when built outside a checkout.
+    pub commit: &'static str,
+    /// `YYYY-MM-DD`, UTC.
+    pub build_date: &'static str,
+    /// `stable`, `beta` or `nightly`.
+    pub channel: &'static str,
+    /// Rust target triple.
+    pub target: &'static str,
+}
+
+#[tauri::command]
+pub fn get_build_info(app: tauri::AppHandle) -> BuildInfo {
+    BuildInfo {
+        version: app.package_info().version.to_string(),
+        commit: env!("COMPANION_COMMIT"),
+        build_date: env!("COMPANION_BUILD_DATE"),
+        channel: env!("COMPANION_CHANNEL"),
+        target: env!("COMPANION_TARGET"),
+    }
+}
+
+/// Optional subsystems compiled into this binary. Most are gated on the
+/// platform rather than on Cargo features, so the UI can't infer them from
+/// the OS alone; commands for a missing subsystem aren't registered at all.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct CompiledFeatures {
+    pub global_shortcut: bool,
+    /// The `companion://` URL scheme handler.
+    pub deep_link: bool,
+    pub single_instance: bool,
+    pub updater: bool,
+    pub clipboard: bool,
+    /// Native message and confirm dialogs.
+    pub dialog: bool,
+    /// Message cache encryption at rest; needs a native key store.
+    pub encryption: bool,
+}
+
+pub const COMPILED_FEATURES: CompiledFeatures = CompiledFeatures {
+    global_shortcut: cfg!(desktop),
+    deep_link: true,
+    single_instance: cfg!(desktop),
+    // No updater or clipboard plugin yet
+    updater: false,
+    clipboard: false,
+    dialog: cfg!(desktop),
+    encryption: !cfg!(target_os = "ios"),
+};
+
+#[tauri::command]
+pub fn get_compiled_features() -> CompiledFeatures {
+    COMPILED_FEATURES
+}
@@ -0,0 +1,125 @@
+use clap::Parser;
+use tauri::{AppHandle, Manager};
+
+use crate::deep_link::{self, DeepLink};
+
+const VERSION: &str = concat!(
+    env!("CARGO_PKG_VERSION"),
+    " (",
+    env!("COMPANION_COMMIT"),
+    ")"
+);
+
+/// Passed by the launch-at-login entry, so `start_minimized` can tell an
+/// autostarted launch from the user opening the app.
+pub const AUTOSTART_ARG: &str = "--autostart";
+
+/// Longest profile name accepted.
+const MAX_PROFILE_LEN: usize = 64;
+
+/// The desktop command line. A second launch hands its arguments to the
+/// running instance, which applies them with [`forward`].
+#[derive(Debug, Clone, Default, PartialEq, Parser)]
+#[command(name = "companion", version = VERSION, about = "Companion desktop app")]
+pub struct Cli {
+    /// Send PROMPT to the session open in the app; it is dropped if none is
+    /// open within a few seconds
+    #[arg(long, value_name = "PROMPT")]
+    pub send: Option<String>,
+    /// Start in the tray without showing the main window
+    #[arg(long)]
+    pub hidden: bool,
+    /// Launched at login; see [`AUTOSTART_ARG`]
+    #[arg(long, hide = true)]
+    pub autostart: bool,
+    /// Keep settings apart under NAME; only takes effect at startup
+    #[arg(long, value_name = "NAME", value_parser = profile_name)]
+    pub profile: Option<String>,
+    /// A companion:// link, as passed by the OS when one is opened
+    #[arg(hide = true)]
+    pub link: Option<String>,
+}
+
+/// Profile names become a directory name, so they are plain tokens.
+fn profile_name(name: &str) -> Result<String, String> {
+    let valid = !name.is_empty()
+        && name.len() <= MAX_PROFILE_LEN
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_'));
+    if valid {
+        Ok(name.to_string())
+    } else {
+        Err("use letters, digits, '-' and '_' only".to_string())
+    }
+}
+
+/// Apply what the command line asks for at startup. Links are picked up by
+/// the deep-link plugin itself, and `--hidden` by `setup_desktop`, which
+/// decides whether the main window is shown at all.
+pub fn apply_startup(app: &AppHandle) {
+    if let Some(prompt) = app.state::<Cli>().send.clone() {
+        deep_link::dispatch(app, DeepLink::SendPrompt { prompt });
+    }
+}
+
+/// Apply the arguments of a second launch to this instance, which shows the
+/// main window unless asked to stay hidden. A link among them reaches the
+/// deep-link plugin through the single-instance plugin.
+pub fn forward(app: &AppHandle, argv: Vec<String>) {
+    let cli = match Cli::try_parse_from(argv) {
+        Ok(cli) => cli,
+        Err(e) => {
+            log::warn!("Ignoring the arguments of a second launch: {e}");
+            return;
+        }
+    };
+    let running = app.state::<Cli>().profile.clone();
+    if cli.profile != running {
+        log::warn!(
+            "Ignoring --profile from a second launch; already running with {}",
+            running.as_deref().unwrap_or("the default profile")
+        );
+    }
+    if !cli.hidden {
+        let _ = crate::desktop::open_main_window(app.clone());
+    }
+    if let Some(prompt) = cli.send {
+        deep_link::dispatch(app, DeepLink::SendPrompt { prompt });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_actions_and_links() {
+        let cli = Cli::try_parse_from([
+            "companion",
+            "--send",
+            "fix the build",
+            "--hidden",
+            "--profile",
+            "work",
+        ])
+        .unwrap();
+        assert_eq!(cli.send.as_deref(), Some("fix the build"));
+        assert!(cli.hidden);
+        assert_eq!(cli.profile.as_deref(), Some("work"));
+
+        let cli = Cli::try_parse_from(["companion", AUTOSTART_ARG]).unwrap();
+        assert!(cli.autostart && !cli.hidden);
+
+        let cli = Cli::try_parse_from(["companion", "companion://session/abc"]).unwrap();
+        assert_eq!(cli.link.as_deref(), Some("companion://session/abc"));
+        assert_eq!(Cli::try_parse_from(["companion"]).unwrap(), Cli::default());
+    }
+
+    #[test]
+    fn profile_names_are_plain_tokens() {
+        assert!(Cli::try_parse_from(["companion", "--profile", "../x"]).is_err());
+        assert!(Cli::try_parse_from(["companion", "--profile", ""]).is_err());
+        assert!(Cli::try_parse_from(["companion", "--bogus"]).is_err());
+    }
+}
@@ -0,0 +1,183 @@
+use std::sync::Mutex;
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager, Url};
+use tauri_plugin_deep_link::DeepLinkExt;
+
+/// The URL scheme the app handles, e.g. `companion://session/abc`.
+pub const SCHEME: &str = "companion";
+
+/// Emitted with a [`DeepLink`] for each link opened once the frontend has
+/// collected the ones buffered before it was listening.
+const DEEP_LINK_EVENT: &str = "deep-link";
+
+/// Longest id or section name accepted in a link.
+const MAX_SEGMENT_LEN: usize = 128;
+
+/// Where a `companion://` link points.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(
+    tag = "kind",
+    rename_all = "kebab-case",
+    rename_all_fields = "camelCase"
+)]
+pub enum DeepLink {
+    /// `companion://session/<id>`
+    Session { session_id: String },
+    /// `companion://settings`, or `companion://settings/<section>` for one
+    /// section such as `notifications`.
+    Settings { section: Option<String> },
+    /// A prompt for the open session from `companion --send`. Links never
+    /// produce this, so a web page can't type into a session.
+    SendPrompt { prompt: String },
+}
+
+/// Links that arrived before the frontend was listening, e.g. the one the
+/// app was launched with. `None` once `take_pending_deep_links` was called.
+pub struct DeepLinkState(Mutex<Option<Vec<DeepLink>>>);
+
+impl Default for DeepLinkState {
+    fn default() -> Self {
+        Self(Mutex::new(Some(Vec::new())))
+    }
+}
+
+/// Ids and section names are plain tokens, so a link can't smuggle path
+/// separators or markup into the frontend.
+fn segment(s: &str) -> Option<String> {
+    let valid = !s.is_empty()
+        && s.len() <= MAX_SEGMENT_LEN
+        && s.chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    valid.then(|| s.to_string())
+}
+
+/// The route a link points to, or `None` for other schemes and unknown or
+/// malformed paths. The route is the URL's host: `companion://session/abc`.
+pub fn parse(url: &Url) -> Option<DeepLink> {
+    if url.scheme() != SCHEME {
+        return None;
+    }
+    let segments: Vec<&str> = url
+        .path_segments()
+        .into_iter()
+        .flatten()
+        .filter(|s| !s.is_empty())
+        .collect();
+    match (url.host_str()?, segments.as_slice()) {
+        ("session", [id]) => Some(DeepLink::Session {
+            session_id: segment(id)?,
+        }),
+        ("settings", []) => Some(DeepLink::Settings { section: None }),
+        ("settings", [section]) => Some(DeepLink::Settings {
+            section: Some(segment(section)?),
+        }),
+        _ => None,
+    }
+}
+
+/// Hand `link` to the frontend, or hold it until the frontend is listening.
+pub fn dispatch(app: &AppHandle, link: DeepLink) {
+    let state = app.state::<DeepLinkState>();
+    let mut pending = state.0.lock().unwrap();
+    match pending.as_mut() {
+        Some(pending) => pending.push(link),
+        None => {
+            let _ = app.emit(DEEP_LINK_EVENT, link);
+        }
+    }
+}
+
+/// Route opened links to the frontend and bring the app forward.
+fn open(app: &AppHandle, urls: Vec<Url>) {
+    for url in urls {
+        match parse(&url) {
+            Some(link) => dispatch(app, link),
+            None => log::warn!("Ignoring unrecognized link {url}"),
+        }
+    }
+    #[cfg(desktop)]
+    let _ = crate::desktop::open_main_window(app.clone());
+}
+
+/// Start routing links, including the one the app was launched with. A
+/// second launch on Windows and Linux forwards its link here through the
+/// single-instance plugin; macOS and mobile deliver them to the running app.
+pub fn setup(app: &AppHandle) {
+    app.manage(DeepLinkState::default());
+    let handle = app.clone();
+    app.deep_link()
+        .on_open_url(move |event| open(&handle, event.urls()));
+    match app.deep_link().get_current() {
+        Ok(Some(urls)) => open(app, urls),
+        Ok(None) => {}
+        Err(e) => log::warn!("Could not read the launch link: {e}"),
+    }
+}
+
+/// Links opened before the frontend was listening, oldest first. Later links
+/// are emitted as `deep-link`, so listen before calling this.
+#[tauri::command]
+pub fn take_pending_deep_links(app: AppHandle) -> Vec<DeepLink> {
+    app.state::<DeepLinkState>()
+        .0
+        .lock()
+        .unwrap()
+        .take()
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_str(url: &str) -> Option<DeepLink> {
+        parse(&Url::parse(url).unwrap())
+    }
+
+    #[test]
+    fn parses_known_routes() {
+        assert_eq!(
+            parse_str("companion://session/abc-123"),
+            Some(DeepLink::Session {
+                session_id: "abc-123".to_string()
+            })
+        );
+        assert_eq!(
+            parse_str("companion://settings"),
+            Some(DeepLink::Settings { section: None })
+        );
+        assert_eq!(
+            parse_str("companion://settings/notifications/"),
+            Some(DeepLink::Settings {
+                section: Some("notifications".to_string())
+            })
+        );
+    }
+
+    #[test]
+    fn rejects_other_links() {
+        for url in [
+            "https://session/abc",
+            "companion://session",
+            "companion://session/a/b",
+            "companion://session/%2E%2E",
+            "companion://session/<script>",
+            "companion://servers/abc",
+            "companion://send-prompt?prompt=hello",
+        ] {
+            assert_eq!(parse_str(url), None, "{url}");
+        }
+    }
+
+    #[test]
+    fn serializes_with_a_kind_tag() {
+        let link = DeepLink::Session {
+            session_id: "abc".to_string(),
+        };
+        assert_eq!(
+            serde_json::to_value(link).unwrap(),
+            serde_json::json!({ "kind": "session", "sessionId": "abc" })
+        );
+    }
+}
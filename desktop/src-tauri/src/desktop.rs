@@ -1,120 +1,762 @@
-use tauri::{
-    menu::{Menu, MenuItemBuilder, PredefinedMenuItem, SubmenuBuilder},
-    tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent},
-    Emitter, Manager, WebviewWindow, WindowEvent,
-};
+use std::{sync::Mutex, thread, time::Duration};
+
+use serde::Serialize;
+use tauri::{window::Color, CloseRequestApi, Emitter, Listener, Manager, Theme, WindowEvent};
 use tauri_plugin_autostart::MacosLauncher;
 use tauri_plugin_autostart::ManagerExt;
+use tauri_plugin_fcm::{BadgeCount, ProgressUpdate, BADGE_EVENT, PROGRESS_EVENT};
+use tauri_plugin_window_state::StateFlags;
+
+use crate::cli::Cli;
+
+mod autostart;
+mod badge;
+mod dialog;
+mod dock;
+mod environment;
+mod gpu;
+mod hide;
+mod layout;
+mod menu;
+mod mini;
+mod opacity;
+mod prewarm;
+mod quick_input;
+mod recovery;
+mod session;
+mod settings;
+mod shortcut;
+mod taskbar;
+mod titlebar;
+mod tray;
+mod url_scheme;
+mod watch;
+
+use autostart::{AutostartError, AutostartHealth};
+use badge::TrayState;
+use environment::RuntimeEnvironment;
+use layout::RestoredLayout;
+use menu::build_menu;
+use settings::{
+    CloseBehavior, Corner, Dock, Settings, SettingsState, TrayMenuTrigger, WindowPlacement,
+};
+use shortcut::ShortcutAction;
+use tray::{build_tray, TrayMenuItem, TRAY_ID};
+use url_scheme::UrlSchemeRegistration;
+
+/// Native UI state that has to survive a menu/tray rebuild.
+#[derive(Default)]
+pub struct UiState {
+    tray_tooltip: Mutex<Option<String>>,
+    unread_count: Mutex<u32>,
+    /// What the tray icon currently signals.
+    tray_state: Mutex<TrayState>,
+    /// The frontend has unsaved work, so quitting asks for confirmation.
+    dirty: Mutex<bool>,
+    /// Latest progress notification still running, shown in the tray tooltip.
+    progress: Mutex<Option<ProgressUpdate>>,
+    /// A `rebuild_ui` is waiting out [`REBUILD_DELAY`].
+    rebuild_pending: Mutex<bool>,
+    /// The close dialog is up, so closing again doesn't stack another.
+    asking_to_close: Mutex<bool>,
+    /// The tray's "Recent Sessions" submenu, most recent first.
+    recent_sessions: Mutex<Vec<TrayMenuItem>>,
+}
+
+impl UiState {
+    /// Tooltip to show on the tray: the frontend's text plus the unread count
+    /// and any running progress notification.
+    pub fn tray_tooltip(&self) -> String {
+        let base = self.tray_tooltip.lock().unwrap().clone();
+        let tooltip =
+            badge::tooltip_text(base.as_deref().unwrap_or("Companion"), self.unread_count());
+        badge::with_progress(tooltip, self.progress.lock().unwrap().as_ref())
+    }
+
+    pub fn unread_count(&self) -> u32 {
+        *self.unread_count.lock().unwrap()
+    }
+
+    pub fn is_dirty(&self) -> bool {
+        *self.dirty.lock().unwrap()
+    }
+
+    pub fn recent_sessions(&self) -> Vec<TrayMenuItem> {
+        self.recent_sessions.lock().unwrap().clone()
+    }
+
+    pub fn tray_state(&self) -> TrayState {
+        *self.tray_state.lock().unwrap()
+    }
+
+    pub fn tray_icon_variant(&self) -> String {
+        self.tray_state().variant().to_string()
+    }
+}
+
+/// Resolved native UI state, for the frontend to reconcile against after
+/// operations like `rebuild_ui` and for integration tests.
+#[derive(Debug, Clone, Serialize)]
+pub struct UiSnapshot {
+    pub tray_tooltip: String,
+    pub tray_icon_variant: String,
+    pub unread_count: u32,
+    /// Top-level menu bar titles, in order.
+    pub menu_labels: Vec<String>,
+    /// Main window properties; `None` if the main window is gone.
+    pub always_on_top: Option<bool>,
+    pub theme: Option<String>,
+    pub decorations: Option<bool>,
+}
+
+#[tauri::command]
+pub fn get_ui_state(app: tauri::AppHandle) -> UiSnapshot {
+    let ui = app.state::<UiState>();
+    let menu_labels = app
+        .menu()
+        .and_then(|menu| menu.items().ok())
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|item| item.as_submenu().and_then(|s| s.text().ok()))
+        .collect();
+    let main = app.get_webview_window("main");
+
+    UiSnapshot {
+        tray_tooltip: ui.tray_tooltip(),
+        tray_icon_variant: ui.tray_icon_variant(),
+        unread_count: ui.unread_count(),
+        menu_labels,
+        always_on_top: main.as_ref().and_then(|w| w.is_always_on_top().ok()),
+        theme: main.as_ref().and_then(|w| w.theme().ok()).map(|theme| {
+            match theme {
+                Theme::Dark => "dark",
+                _ => "light",
+            }
+            .to_string()
+        }),
+        decorations: main.as_ref().and_then(|w| w.is_decorated().ok()),
+    }
+}
 
 #[tauri::command]
 pub fn set_tray_tooltip(app: tauri::AppHandle, tooltip: String) {
-    if let Some(tray) = app.tray_by_id("main-tray") {
-        let _ = tray.set_tooltip(Some(&tooltip));
+    let ui = app.state::<UiState>();
+    *ui.tray_tooltip.lock().unwrap() = Some(tooltip);
+    if let Some(tray) = app.tray_by_id(TRAY_ID) {
+        let _ = tray.set_tooltip(Some(&ui.tray_tooltip()));
+    }
+}
+
+/// Mark whether the frontend has unsaved work that quitting would lose.
+#[tauri::command]
+pub fn set_dirty(app: tauri::AppHandle, dirty: bool) {
+    *app.state::<UiState>().dirty.lock().unwrap() = dirty;
+}
+
+/// Ask for confirmation with a native dialog, which still shows when the
+/// window is hidden.
+#[tauri::command]
+pub async fn confirm(
+    app: tauri::AppHandle,
+    title: String,
+    message: String,
+    confirm_label: String,
+    cancel_label: String,
+) -> bool {
+    dialog::confirm(&app, title, message, confirm_label, cancel_label).await
+}
+
+/// Persist the accent color and tint every open window's chrome with it.
+pub fn set_accent_color(app: &tauri::AppHandle, hex: &str, color: Color) -> Result<(), String> {
+    app.state::<SettingsState>()
+        .update(|s| s.accent_color = Some(hex.to_string()))?;
+    for window in app.webview_windows().values() {
+        titlebar::tint(window, color);
     }
+    Ok(())
+}
+
+/// Tint a window with the saved accent color, if any.
+fn apply_accent_color(window: &tauri::WebviewWindow) {
+    let accent = window.state::<SettingsState>().get().accent_color;
+    if let Some(color) = accent.as_deref().and_then(crate::accent::parse) {
+        titlebar::tint(window, color);
+    }
+}
+
+/// A command for a console tool such as `reg.exe`. Started from the GUI it
+/// would otherwise flash a console window.
+#[cfg(target_os = "windows")]
+fn console_command(program: &str) -> std::process::Command {
+    use std::os::windows::process::CommandExt;
+
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+    let mut command = std::process::Command::new(program);
+    command.creation_flags(CREATE_NO_WINDOW);
+    command
+}
+
+/// Quit, first confirming natively if there is unsaved work.
+pub fn quit_confirming_if_dirty(app: &tauri::AppHandle) {
+    if !app.state::<UiState>().is_dirty() {
+        app.exit(0);
+        return;
+    }
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let confirmed = dialog::confirm(
+            &app,
+            "Quit Companion?".to_string(),
+            "You have unsaved changes that will be lost.".to_string(),
+            "Quit".to_string(),
+            "Cancel".to_string(),
+        )
+        .await;
+        if confirmed {
+            app.exit(0);
+        }
+    });
+}
+
+/// Mirror the unread count into the tray tooltip, dock badge and taskbar
+/// overlay, whichever the platform has. Zero clears every surface.
+#[tauri::command]
+pub fn set_unread_count(app: tauri::AppHandle, count: u32) {
+    let ui = app.state::<UiState>();
+    let previous = std::mem::replace(&mut *ui.unread_count.lock().unwrap(), count);
+    if previous != count {
+        if let Err(e) = badge::save_unread(&app, count) {
+            log::warn!("Could not save the unread count: {e}");
+        }
+    }
+    badge::apply(&app);
+}
+
+/// Desktop stand-in for progress notifications: track the latest running one
+/// for the tray tooltip. Completing an older one leaves a newer one shown.
+fn on_progress(app: &tauri::AppHandle, update: ProgressUpdate) {
+    {
+        let mut progress = app.state::<UiState>().progress.lock().unwrap();
+        if !update.completed {
+            *progress = Some(update);
+        } else if progress.as_ref().is_some_and(|p| p.id == update.id) {
+            *progress = None;
+        }
+    }
+    badge::apply(app);
+}
+
+/// Choose which click opens the tray menu. Persisted and applied right away.
+#[tauri::command]
+pub fn set_tray_menu_trigger(
+    app: tauri::AppHandle,
+    trigger: TrayMenuTrigger,
+) -> Result<(), String> {
+    app.state::<SettingsState>()
+        .update(|s| s.tray_menu_trigger = Some(trigger))?;
+    tray::apply_menu_trigger(&app).map_err(|e| format!("{e}"))
+}
+
+/// Swap the tray icon for one marking `state`: busy, or a count of unread
+/// items. Kept across tray rebuilds.
+#[tauri::command]
+pub fn set_tray_state(app: tauri::AppHandle, state: TrayState) -> Result<(), String> {
+    *app.state::<UiState>().tray_state.lock().unwrap() = state;
+    tray::apply_state(&app).map_err(|e| format!("{e}"))
+}
+
+/// Show the frontend's recent sessions in a tray submenu. Clicking one
+/// focuses the main window and emits `tray-open-session` with its id.
+#[tauri::command]
+pub fn update_tray_menu(app: tauri::AppHandle, items: Vec<TrayMenuItem>) -> Result<(), String> {
+    tray::set_recent_sessions(&app, items).map_err(|e| format!("{e}"))
+}
+
+/// Replace the window (and taskbar) icon with a base64 PNG, e.g. the app
+/// icon with an unread badge drawn in by the frontend.
+#[tauri::command]
+pub fn set_app_icon_from_base64(app: tauri::AppHandle, png: String) -> Result<(), String> {
+    let icon = badge::decode_icon(&png)?;
+    badge::set_window_icons(&app, Some(icon)).map_err(|e| format!("{e}"))
+}
+
+/// Restore the bundled window icon.
+#[tauri::command]
+pub fn reset_app_icon(app: tauri::AppHandle) -> Result<(), String> {
+    badge::set_window_icons(&app, None).map_err(|e| format!("{e}"))
+}
+
+/// Packaging/sandbox details so the UI can hide features a sandbox blocks.
+#[tauri::command]
+pub fn get_runtime_environment(app: tauri::AppHandle) -> RuntimeEnvironment {
+    app.state::<RuntimeEnvironment>().inner().clone()
 }
 
 #[tauri::command]
 pub fn get_autostart_enabled(app: tauri::AppHandle) -> bool {
+    if !app
+        .state::<RuntimeEnvironment>()
+        .supports_plugin_autostart()
+    {
+        return false;
+    }
     app.autolaunch().is_enabled().unwrap_or(false)
 }
 
 #[tauri::command]
-pub fn set_autostart_enabled(app: tauri::AppHandle, enabled: bool) -> Result<(), String> {
-    let autolaunch = app.autolaunch();
+pub async fn set_autostart_enabled(
+    app: tauri::AppHandle,
+    enabled: bool,
+) -> Result<(), AutostartError> {
+    let environment = app.state::<RuntimeEnvironment>();
+    if !environment.supports_plugin_autostart() {
+        return Err(AutostartError {
+            message: format!(
+                "Launch at login is managed by the system in {} builds",
+                environment.installer_kind
+            ),
+            enabled: None,
+        });
+    }
+    tauri::async_runtime::spawn_blocking(move || autostart::set_enabled(&app, enabled))
+        .await
+        .unwrap_or_else(|e| {
+            Err(AutostartError {
+                message: format!("{e}"),
+                enabled: None,
+            })
+        })
+}
+
+/// Check the launch-at-login entry still points at this executable and
+/// re-register it if an update left it stale. Also run at startup.
+#[tauri::command]
+pub async fn verify_autostart(app: tauri::AppHandle) -> Result<AutostartHealth, String> {
+    if !app
+        .state::<RuntimeEnvironment>()
+        .supports_plugin_autostart()
+    {
+        return Ok(AutostartHealth {
+            enabled: false,
+            healthy: true,
+            repaired: false,
+        });
+    }
+    tauri::async_runtime::spawn_blocking(move || autostart::verify(&app))
+        .await
+        .map_err(|e| format!("{e}"))?
+}
+
+/// Make the app the default handler for `companion://` links. Touches the
+/// registry on Windows and `.desktop` files/`xdg-mime` on Linux; on macOS the
+/// bundle declares the scheme, so this only reports that.
+#[tauri::command]
+pub async fn register_url_scheme_handler() -> Result<UrlSchemeRegistration, String> {
+    tauri::async_runtime::spawn_blocking(url_scheme::register)
+        .await
+        .map_err(|e| format!("{e}"))?
+}
+
+/// Whether the app handles `companion://` links; `None` where the platform
+/// can't tell (macOS).
+#[tauri::command]
+pub async fn is_default_url_handler() -> Result<Option<bool>, String> {
+    tauri::async_runtime::spawn_blocking(url_scheme::is_default)
+        .await
+        .map_err(|e| format!("{e}"))?
+}
+
+#[tauri::command]
+pub fn get_close_behavior(app: tauri::AppHandle) -> CloseBehavior {
+    app.state::<SettingsState>().get().close_behavior
+}
+
+/// Choose what closing the main window does: `hide-to-tray`, `quit`, or `ask`
+/// to show a native dialog on the next close and keep the answer.
+#[tauri::command]
+pub fn set_close_behavior(app: tauri::AppHandle, behavior: CloseBehavior) -> Result<(), String> {
+    app.state::<SettingsState>()
+        .update(|s| s.close_behavior = behavior)
+        .map(|_| ())
+}
+
+#[tauri::command]
+pub fn get_start_minimized(app: tauri::AppHandle) -> bool {
+    app.state::<SettingsState>().get().start_minimized
+}
+
+/// Start in the tray, without showing the main window, when launched at login.
+#[tauri::command]
+pub fn set_start_minimized(app: tauri::AppHandle, enabled: bool) -> Result<(), String> {
+    app.state::<SettingsState>()
+        .update(|s| s.start_minimized = enabled)
+        .map(|_| ())
+}
+
+#[tauri::command]
+pub fn get_quit_on_last_window_closed(app: tauri::AppHandle) -> bool {
+    app.state::<SettingsState>()
+        .get()
+        .quit_on_last_window_closed
+}
+
+#[tauri::command]
+pub fn set_quit_on_last_window_closed(app: tauri::AppHandle, enabled: bool) -> Result<(), String> {
+    app.state::<SettingsState>()
+        .update(|s| s.quit_on_last_window_closed = enabled)
+        .map(|_| ())
+}
+
+/// Toggle the compact always-on-top mini window. Returns whether it is now open.
+#[tauri::command]
+pub fn open_mini_window(app: tauri::AppHandle) -> Result<bool, String> {
+    mini::toggle(&app).map_err(|e| format!("{e}"))
+}
+
+/// Set the mini window's opacity, clamped to 0.2–1.0, now if it is open and
+/// whenever it opens later. Fails where the window can't be made translucent.
+#[tauri::command]
+pub fn set_window_opacity(app: tauri::AppHandle, opacity: f64) -> Result<(), String> {
+    let opacity = opacity::clamp(opacity)?;
+    if let Some(window) = app.get_webview_window(mini::MINI_LABEL) {
+        opacity::apply(&window, opacity)?;
+    }
+    app.state::<SettingsState>()
+        .update(|s| s.mini_window_opacity = Some(opacity))
+        .map(|_| ())
+}
+
+/// Let clicks pass through the mini window to the apps beneath it, for a
+/// glance-only overlay. Persisted; the tray menu's "Make Mini Window
+/// Clickable" turns it back off.
+#[tauri::command]
+pub fn set_window_ignore_cursor_events(app: tauri::AppHandle, ignore: bool) -> Result<(), String> {
+    mini::set_click_through(&app, ignore)
+}
+
+/// Pin the main window to a corner of its monitor's work area, `margin`
+/// logical pixels from the edges. Persisted: the window re-docks at startup
+/// and whenever its size or scale factor changes.
+#[tauri::command]
+pub fn dock_window(app: tauri::AppHandle, corner: Corner, margin: u32) -> Result<(), String> {
+    let dock = Dock { corner, margin };
+    let window = app
+        .get_webview_window("main")
+        .ok_or("Main window not found")?;
+    dock::apply(&window.as_ref().window(), dock).map_err(|e| format!("{e}"))?;
+    app.state::<SettingsState>()
+        .update(|s| s.main_window_dock = Some(dock))
+        .map(|_| ())
+}
+
+/// Stop re-docking the main window; it stays where it is.
+#[tauri::command]
+pub fn undock_window(app: tauri::AppHandle) -> Result<(), String> {
+    app.state::<SettingsState>()
+        .update(|s| s.main_window_dock = None)
+        .map(|_| ())
+}
+
+/// Save the position, size and monitor of every open window as `name`,
+/// replacing any layout already saved under it.
+#[tauri::command]
+pub fn save_layout(app: tauri::AppHandle, name: String) -> Result<Vec<WindowPlacement>, String> {
+    layout::save(&app, &name)
+}
+
+/// Put the windows back where layout `name` had them, reopening the mini
+/// window if needed. Windows that can't be recreated are listed as skipped.
+#[tauri::command]
+pub fn restore_layout(app: tauri::AppHandle, name: String) -> Result<RestoredLayout, String> {
+    layout::restore(&app, &name)
+}
+
+#[tauri::command]
+pub fn list_layouts(app: tauri::AppHandle) -> Vec<String> {
+    app.state::<SettingsState>()
+        .get()
+        .window_layouts
+        .into_keys()
+        .collect()
+}
+
+/// Forget layout `name`. Returns whether it existed.
+#[tauri::command]
+pub fn delete_layout(app: tauri::AppHandle, name: String) -> Result<bool, String> {
+    let mut existed = false;
+    app.state::<SettingsState>()
+        .update(|s| existed = s.window_layouts.remove(&name).is_some())?;
+    Ok(existed)
+}
+
+/// Hide the main window's taskbar button, or the Dock icon on macOS, so the
+/// app lives in the tray only. Persisted and reapplied at startup.
+#[tauri::command]
+pub fn set_skip_taskbar(app: tauri::AppHandle, skip: bool) -> Result<(), String> {
+    taskbar::apply(&app, skip).map_err(|e| format!("{e}"))?;
+    app.state::<SettingsState>()
+        .update(|s| s.skip_taskbar = skip)
+        .map(|_| ())
+}
+
+/// The accelerator that toggles the main window from anywhere, e.g.
+/// `CommandOrControl+Shift+Space`; empty when turned off.
+#[tauri::command]
+pub fn get_global_shortcut(app: tauri::AppHandle) -> String {
+    shortcut::accelerator(&app, ShortcutAction::ToggleMain)
+}
+
+/// Change the global shortcut; empty turns it off. Fails and keeps the
+/// current one if the accelerator is malformed, lacks a modifier or is held
+/// by another application.
+#[tauri::command]
+pub fn set_global_shortcut(app: tauri::AppHandle, accelerator: String) -> Result<(), String> {
+    let accelerator = accelerator.trim();
+    shortcut::apply(&app, ShortcutAction::ToggleMain, accelerator)?;
+    app.state::<SettingsState>()
+        .update(|s| s.global_shortcut = Some(accelerator.to_string()))
+        .map(|_| ())
+}
+
+/// The accelerator that opens the quick-input window; empty when turned off.
+#[tauri::command]
+pub fn get_quick_input_shortcut(app: tauri::AppHandle) -> String {
+    shortcut::accelerator(&app, ShortcutAction::QuickInput)
+}
+
+/// Change the quick-input shortcut; empty turns it off. Fails like
+/// `set_global_shortcut`, and also when it matches that shortcut.
+#[tauri::command]
+pub fn set_quick_input_shortcut(app: tauri::AppHandle, accelerator: String) -> Result<(), String> {
+    let accelerator = accelerator.trim();
+    shortcut::apply(&app, ShortcutAction::QuickInput, accelerator)?;
+    app.state::<SettingsState>()
+        .update(|s| s.quick_input_shortcut = Some(accelerator.to_string()))
+        .map(|_| ())
+}
+
+/// Show the quick-input window, creating it on first use.
+#[tauri::command]
+pub fn open_quick_input(app: tauri::AppHandle) -> Result<(), String> {
+    quick_input::show(&app).map_err(|e| format!("{e}"))
+}
+
+/// Hide the quick-input window without sending anything.
+#[tauri::command]
+pub fn hide_quick_input(app: tauri::AppHandle) -> Result<(), String> {
+    quick_input::hide(&app).map_err(|e| format!("{e}"))
+}
+
+/// Hide the quick-input window and forward `prompt` to the main window as
+/// `quick-input-submitted`.
+#[tauri::command]
+pub fn submit_quick_input(app: tauri::AppHandle, prompt: String) -> Result<(), String> {
+    quick_input::submit(&app, &prompt).map_err(|e| format!("{e}"))
+}
+
+/// Show and focus the main window, e.g. from the mini window.
+#[tauri::command]
+pub fn open_main_window(app: tauri::AppHandle) -> Result<(), String> {
+    let window = app
+        .get_webview_window("main")
+        .ok_or("Main window not found")?;
+    prewarm::show_then_focus(&window);
+    Ok(())
+}
+
+/// Sent every few seconds by each page, so a window whose renderer crashed
+/// and left it blank is noticed and reloaded. `hidden` is the page's own
+/// visibility, which also covers a window covered by others.
+#[tauri::command]
+pub fn webview_heartbeat(window: tauri::WebviewWindow, hidden: Option<bool>) {
+    recovery::heartbeat(window.app_handle(), window.label(), hidden.unwrap_or(false));
+}
+
+/// Reload the main window's page, which also re-enables automatic reloads
+/// after they hit their limit.
+#[tauri::command]
+pub fn reload_webview(app: tauri::AppHandle) -> Result<(), String> {
+    let window = app
+        .get_webview_window("main")
+        .ok_or("Main window not found")?;
+    recovery::reload(&window, false).map_err(|e| format!("{e}"))
+}
+
+/// Lay out the hidden main window now so the next show paints immediately.
+#[tauri::command]
+pub fn prewarm_window(app: tauri::AppHandle) -> Result<(), String> {
+    let window = app
+        .get_webview_window("main")
+        .ok_or("Main window not found")?;
+    prewarm::nudge(&window).map_err(|e| format!("{e}"))
+}
+
+#[tauri::command]
+pub fn get_prewarm_window_enabled(app: tauri::AppHandle) -> bool {
+    app.state::<SettingsState>().get().prewarm_window
+}
+
+#[tauri::command]
+pub fn set_prewarm_window_enabled(app: tauri::AppHandle, enabled: bool) -> Result<(), String> {
+    app.state::<SettingsState>()
+        .update(|s| s.prewarm_window = enabled)
+        .map(|_| ())
+}
+
+/// Re-read `settings.json` after it was edited outside the app and apply it.
+/// A malformed file is rejected and the running settings are kept.
+#[tauri::command]
+pub fn reload_settings(app: tauri::AppHandle) -> Result<Settings, String> {
+    if let Some(settings) = app.state::<SettingsState>().reload()? {
+        apply_all_settings(&app, &settings);
+    }
+    Ok(app.state::<SettingsState>().get())
+}
+
+/// Reload the settings whenever `settings.json` changes on disk, e.g. when a
+/// sync tool replaces it. Off by default.
+#[tauri::command]
+pub fn watch_settings(app: tauri::AppHandle, enabled: bool) -> Result<(), String> {
     if enabled {
-        autolaunch.enable().map_err(|e| format!("{e}"))
+        watch::start(&app)
     } else {
-        autolaunch.disable().map_err(|e| format!("{e}"))
+        watch::stop(&app);
+        Ok(())
     }
 }
 
-fn toggle_window(window: &WebviewWindow) {
-    if window.is_visible().unwrap_or(false) {
-        let _ = window.hide();
-    } else {
-        let _ = window.show();
-        let _ = window.set_focus();
+/// Apply settings that were replaced wholesale and tell the frontend.
+/// Settings read on use (close policy, mini corner, prewarming) need nothing
+/// here; `disable_gpu` still only takes effect after a restart.
+fn apply_all_settings(app: &tauri::AppHandle, settings: &Settings) {
+    for window in app.webview_windows().values() {
+        apply_accent_color(window);
+    }
+    if let Err(e) = taskbar::apply(app, settings.skip_taskbar) {
+        log::warn!("Could not apply taskbar visibility: {e}");
     }
+    shortcut::apply_saved(app);
+    let _ = app.emit("settings-changed", settings);
+}
+
+/// Apply the persisted GPU workaround. Must run before the builder so the
+/// environment is in place when the first webview is created.
+pub fn apply_early_settings(identifier: &str, profile: Option<&str>) {
+    gpu::apply(identifier, profile);
+}
+
+#[tauri::command]
+pub fn get_disable_gpu(app: tauri::AppHandle) -> bool {
+    app.state::<SettingsState>().get().disable_gpu
+}
+
+/// Result of a setting change that only takes effect after a restart.
+#[derive(Clone, Serialize)]
+pub struct RestartRequired {
+    /// Whether the running process differs from the saved setting; call
+    /// `restart_app` to apply it.
+    pub restart_required: bool,
+}
+
+/// Fails on macOS, which has no software-rendering switch to apply.
+#[tauri::command]
+pub fn set_disable_gpu(app: tauri::AppHandle, enabled: bool) -> Result<RestartRequired, String> {
+    if !gpu::SUPPORTED {
+        return Err("Disabling GPU acceleration is not supported on this platform".to_string());
+    }
+    app.state::<SettingsState>()
+        .update(|s| s.disable_gpu = enabled)?;
+    Ok(RestartRequired {
+        restart_required: enabled != gpu::is_applied(),
+    })
+}
+
+/// Relaunch the app, e.g. to apply settings read at startup.
+#[tauri::command]
+pub fn restart_app(app: tauri::AppHandle) {
+    gpu::restore_env();
+    app.restart();
+}
+
+/// Scale factor of the main window's current monitor.
+#[tauri::command]
+pub fn get_scale_factor(app: tauri::AppHandle) -> Result<f64, String> {
+    let window = app
+        .get_webview_window("main")
+        .ok_or("Main window not found")?;
+    window.scale_factor().map_err(|e| format!("{e}"))
+}
+
+/// Payload of the `scale-factor-changed` event.
+#[derive(Clone, Serialize)]
+struct ScaleFactorChanged {
+    label: String,
+    scale_factor: f64,
+    /// New inner size in physical pixels.
+    width: u32,
+    height: u32,
+}
+
+/// How long `rebuild_ui` waits for further requests before rebuilding.
+const REBUILD_DELAY: Duration = Duration::from_millis(100);
+
+/// Tear down and rebuild the menu bar and tray (e.g. after a locale or theme
+/// change) without restarting. The app-level menu handler stays registered and
+/// the tray's own handlers are re-attached by `build_tray`.
+///
+/// Requests within [`REBUILD_DELAY`] of the first are coalesced into a single
+/// rebuild, which reads the state as it is when it runs, so changing locale,
+/// theme and labels together rebuilds once instead of flickering. Failures
+/// are logged, since the rebuild happens after this returns.
+#[tauri::command]
+pub fn rebuild_ui(app: tauri::AppHandle) {
+    {
+        let mut pending = app.state::<UiState>().rebuild_pending.lock().unwrap();
+        if *pending {
+            return;
+        }
+        *pending = true;
+    }
+    thread::spawn(move || {
+        thread::sleep(REBUILD_DELAY);
+        // Clear before rebuilding: a request arriving mid-rebuild may have
+        // changed state this rebuild already read, so it gets its own
+        *app.state::<UiState>().rebuild_pending.lock().unwrap() = false;
+        let handle = app.clone();
+        let _ = app.run_on_main_thread(move || {
+            if let Err(e) = rebuild(&handle) {
+                log::warn!("UI rebuild failed: {e}");
+            }
+        });
+    });
+}
+
+fn rebuild(app: &tauri::AppHandle) -> tauri::Result<()> {
+    app.set_menu(build_menu(app)?)?;
+    // Drop the old tray before building the new one so two icons never coexist
+    drop(app.remove_tray_by_id(TRAY_ID));
+    build_tray(app)?;
+    badge::apply(app);
+    Ok(())
 }
 
 pub fn setup_desktop(app: &mut tauri::App) -> Result<(), Box<dyn std::error::Error>> {
-    // -- Custom menu bar --
-    let app_menu = SubmenuBuilder::new(app, "Companion")
-        .about(None)
-        .separator()
-        .services()
-        .separator()
-        .hide()
-        .hide_others()
-        .show_all()
-        .separator()
-        .quit()
-        .build()?;
-
-    let file_menu = SubmenuBuilder::new(app, "File")
-        .item(
-            &MenuItemBuilder::with_id("new-session", "New Session")
-                .accelerator("CmdOrCtrl+N")
-                .build(app)?,
-        )
-        .separator()
-        .close_window()
-        .build()?;
-
-    let edit_menu = SubmenuBuilder::new(app, "Edit")
-        .undo()
-        .redo()
-        .separator()
-        .cut()
-        .copy()
-        .paste()
-        .select_all()
-        .build()?;
-
-    let view_menu = SubmenuBuilder::new(app, "View")
-        .item(
-            &MenuItemBuilder::with_id("toggle-sidebar", "Toggle Sidebar")
-                .accelerator("CmdOrCtrl+B")
-                .build(app)?,
-        )
-        .separator()
-        .item(
-            &MenuItemBuilder::with_id("reload", "Reload")
-                .accelerator("CmdOrCtrl+R")
-                .build(app)?,
-        )
-        .separator()
-        .item(
-            &MenuItemBuilder::with_id("zoom-in", "Zoom In")
-                .accelerator("CmdOrCtrl+Plus")
-                .build(app)?,
-        )
-        .item(
-            &MenuItemBuilder::with_id("zoom-out", "Zoom Out")
-                .accelerator("CmdOrCtrl+-")
-                .build(app)?,
-        )
-        .item(
-            &MenuItemBuilder::with_id("zoom-reset", "Actual Size")
-                .accelerator("CmdOrCtrl+0")
-                .build(app)?,
-        )
-        .build()?;
-
-    let window_menu = SubmenuBuilder::new(app, "Window")
-        .minimize()
-        .item(
-            &MenuItemBuilder::with_id("fullscreen", "Toggle Full Screen")
-                .accelerator("Ctrl+CmdOrCtrl+F")
-                .build(app)?,
-        )
-        .build()?;
+    let profile = app.state::<Cli>().profile.clone();
+    let (settings, problem) = SettingsState::load(app.handle(), profile.as_deref());
+    app.manage(settings);
+    if let Some(problem) = problem {
+        log::warn!("{problem}");
+        dialog::alert(app.handle(), "Companion Settings", &problem);
+    }
+    app.manage(UiState::default());
+    // Restored so the badge is right before the frontend reports a new count
+    *app.state::<UiState>().unread_count.lock().unwrap() = badge::load_unread(app.handle());
+    app.manage(watch::SettingsWatcher::default());
+    app.manage(recovery::RecoveryState::default());
+    app.manage(environment::detect());
+    app.manage(shortcut::RegisteredShortcuts::default());
 
-    let menu = Menu::with_items(
-        app,
-        &[&app_menu, &file_menu, &edit_menu, &view_menu, &window_menu],
-    )?;
-    app.set_menu(menu)?;
+    app.set_menu(build_menu(app.handle())?)?;
 
     // Handle custom menu events
     let app_handle = app.handle().clone();
@@ -129,68 +771,338 @@ pub fn setup_desktop(app: &mut tauri::App) -> Result<(), Box<dyn std::error::Err
         }
     });
 
-    // -- System tray --
-    let show_item = MenuItemBuilder::with_id("show", "Show Companion").build(app)?;
-    let quit_item = MenuItemBuilder::with_id("quit-app", "Quit").build(app)?;
-    let tray_menu = Menu::with_items(
-        app,
-        &[
-            &show_item,
-            &PredefinedMenuItem::separator(app)?,
-            &quit_item,
-        ],
-    )?;
-
-    let _tray = TrayIconBuilder::with_id("main-tray")
-        .icon(app.default_window_icon().unwrap().clone())
-        .menu(&tray_menu)
-        .show_menu_on_left_click(false)
-        .tooltip("Companion")
-        .on_tray_icon_event(|tray: &tauri::tray::TrayIcon, event| {
-            if let TrayIconEvent::Click {
-                button: MouseButton::Left,
-                button_state: MouseButtonState::Up,
-                ..
-            } = event
-            {
-                let app = tray.app_handle();
-                if let Some(window) = app.get_webview_window("main") {
-                    toggle_window(&window);
-                }
-            }
-        })
-        .on_menu_event(|app: &tauri::AppHandle, event| {
-            match event.id().0.as_str() {
-                "show" => {
-                    if let Some(window) = app.get_webview_window("main") {
-                        let _ = window.show();
-                        let _ = window.set_focus();
-                    }
-                }
-                "quit-app" => {
-                    app.exit(0);
-                }
-                _ => {}
+    build_tray(app.handle())?;
+    badge::apply(app.handle());
+
+    let app_handle = app.handle().clone();
+    app.listen(PROGRESS_EVENT, move |event| {
+        match serde_json::from_str::<ProgressUpdate>(event.payload()) {
+            Ok(update) => on_progress(&app_handle, update),
+            Err(e) => log::warn!("Ignoring malformed progress update: {e}"),
+        }
+    });
+
+    // The FCM plugin's badge API drives the same badge as `set_unread_count`
+    let app_handle = app.handle().clone();
+    app.listen(BADGE_EVENT, move |event| {
+        match serde_json::from_str::<BadgeCount>(event.payload()) {
+            Ok(update) => set_unread_count(app_handle.clone(), update.count),
+            Err(e) => log::warn!("Ignoring malformed badge update: {e}"),
+        }
+    });
+
+    for window in app.webview_windows().values() {
+        apply_accent_color(window);
+    }
+    if let Some(main) = app.get_webview_window("main") {
+        dock::reapply(&main.as_ref().window());
+        if let Err(e) = session::watch(&main) {
+            log::warn!("Could not watch for the session ending: {e}");
+        }
+    }
+
+    if app.state::<SettingsState>().get().skip_taskbar {
+        if let Err(e) = taskbar::apply(app.handle(), true) {
+            log::warn!("Could not hide the taskbar button: {e}");
+        }
+    }
+    // The main window is created hidden, so starting in the tray never
+    // flashes it on screen. start_minimized only covers launches at login;
+    // opening the app from the Start menu or Dock always shows it.
+    let cli = app.state::<Cli>();
+    let start_minimized = cli.autostart && app.state::<SettingsState>().get().start_minimized;
+    if !start_minimized && !cli.hidden {
+        if let Some(main) = app.get_webview_window("main") {
+            main.show()?;
+        }
+    }
+    prewarm::start(app.handle());
+    recovery::start(app.handle());
+    shortcut::apply_saved(app.handle());
+
+    if app
+        .state::<RuntimeEnvironment>()
+        .supports_plugin_autostart()
+    {
+        let app = app.handle().clone();
+        tauri::async_runtime::spawn_blocking(move || {
+            if let Err(e) = autostart::verify(&app) {
+                log::warn!("Could not verify the autostart entry: {e}");
             }
-        })
-        .build(app)?;
+        });
+    }
 
     Ok(())
 }
 
 pub fn setup_desktop_plugins(builder: tauri::Builder<tauri::Wry>) -> tauri::Builder<tauri::Wry> {
     builder
-        .plugin(tauri_plugin_window_state::Builder::new().build())
+        // Visibility is left to `setup_desktop`, which may start in the tray
+        .plugin(
+            tauri_plugin_window_state::Builder::new()
+                .with_state_flags(StateFlags::all() - StateFlags::VISIBLE)
+                .build(),
+        )
+        .plugin(tauri_plugin_dialog::init())
+        .plugin(
+            tauri_plugin_global_shortcut::Builder::new()
+                .with_handler(|app, shortcut, event| shortcut::on_event(app, shortcut, event))
+                .build(),
+        )
         .plugin(tauri_plugin_autostart::init(
             MacosLauncher::LaunchAgent,
-            None,
+            Some(vec![crate::cli::AUTOSTART_ARG]),
         ))
 }
 
+/// What to do when a window asks to close.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CloseAction {
+    /// Hide the window and keep running in the tray.
+    HideToTray,
+    /// Hide the window so it can be shown again; nothing else changes.
+    Hide,
+    /// Let the window close; the app keeps running.
+    Close,
+    /// Let the window close and exit the app.
+    Quit,
+    /// Keep the window while the user picks between hiding and quitting.
+    Ask,
+}
+
+/// Which kind of window asked to close.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ClosingWindow {
+    Main,
+    /// Kept around hidden between summons, see [`quick_input::show`].
+    QuickInput,
+    Other,
+}
+
+impl ClosingWindow {
+    fn of(label: &str) -> Self {
+        match label {
+            "main" => Self::Main,
+            quick_input::QUICK_INPUT_LABEL => Self::QuickInput,
+            _ => Self::Other,
+        }
+    }
+}
+
+/// The main window follows `close_behavior`, so the app either stays alive in
+/// the tray or quits with it. Quick input is only ever hidden. For other
+/// windows `quit_on_last_window_closed` governs a true quit: when a window
+/// really closes and no other window is left visible (a main window hidden in
+/// the tray or quick input doesn't count), the app exits instead of lingering
+/// with only a tray icon.
+fn resolve_close_action(
+    window: ClosingWindow,
+    close_behavior: CloseBehavior,
+    quit_on_last_window_closed: bool,
+    other_visible_windows: usize,
+) -> CloseAction {
+    if window == ClosingWindow::Main {
+        match close_behavior {
+            CloseBehavior::HideToTray => CloseAction::HideToTray,
+            CloseBehavior::Quit => CloseAction::Quit,
+            CloseBehavior::Ask => CloseAction::Ask,
+        }
+    } else if window == ClosingWindow::QuickInput {
+        CloseAction::Hide
+    } else if quit_on_last_window_closed && other_visible_windows == 0 {
+        CloseAction::Quit
+    } else {
+        CloseAction::Close
+    }
+}
+
+/// Ask whether closing the main window should quit or hide it, remember the
+/// answer as the close behavior, then act on it. Dismissing the dialog keeps
+/// the app running, the choice that loses nothing.
+fn ask_close_behavior(window: &tauri::Window) {
+    {
+        let ui = window.state::<UiState>();
+        let mut asking = ui.asking_to_close.lock().unwrap();
+        if *asking {
+            return;
+        }
+        *asking = true;
+    }
+    let window = window.clone();
+    tauri::async_runtime::spawn(async move {
+        let app = window.app_handle().clone();
+        let quit = dialog::confirm(
+            &app,
+            "Close Companion".to_string(),
+            "Quit Companion, or keep it running in the tray? Closing the window \
+             will do the same from now on."
+                .to_string(),
+            "Quit".to_string(),
+            "Keep in Tray".to_string(),
+        )
+        .await;
+        *app.state::<UiState>().asking_to_close.lock().unwrap() = false;
+        let behavior = if quit {
+            CloseBehavior::Quit
+        } else {
+            CloseBehavior::HideToTray
+        };
+        if let Err(e) = app
+            .state::<SettingsState>()
+            .update(|s| s.close_behavior = behavior)
+        {
+            log::warn!("Could not save the close behavior: {e}");
+        }
+        if quit {
+            quit_confirming_if_dirty(&app);
+        } else {
+            hide::hide_to_tray(&window);
+        }
+    });
+}
+
+fn on_close_requested(window: &tauri::Window, api: &CloseRequestApi) {
+    let app = window.app_handle();
+    // Never hold up a logout or shutdown, not even for unsaved work
+    if session::is_ending() {
+        app.exit(0);
+        return;
+    }
+    let settings = app.state::<SettingsState>().get();
+    let other_visible_windows = app
+        .webview_windows()
+        .values()
+        .filter(|w| {
+            w.label() != window.label()
+                && w.label() != quick_input::QUICK_INPUT_LABEL
+                && w.is_visible().unwrap_or(false)
+        })
+        .count();
+
+    let closing = ClosingWindow::of(window.label());
+    let is_main = closing == ClosingWindow::Main;
+    match resolve_close_action(
+        closing,
+        settings.close_behavior,
+        settings.quit_on_last_window_closed,
+        other_visible_windows,
+    ) {
+        CloseAction::HideToTray => {
+            hide::hide_to_tray(window);
+            api.prevent_close();
+        }
+        CloseAction::Hide => {
+            let _ = window.hide();
+            api.prevent_close();
+        }
+        CloseAction::Close => {}
+        // The main window carries the unsaved work, so it confirms first
+        CloseAction::Quit if is_main => {
+            api.prevent_close();
+            quit_confirming_if_dirty(app);
+        }
+        CloseAction::Quit => app.exit(0),
+        CloseAction::Ask => {
+            api.prevent_close();
+            ask_close_behavior(window);
+        }
+    }
+}
+
+/// App-level events: an exit request (Cmd+Q, a macOS logout, `app.exit`)
+/// means windows asking to close from now on should close for real.
+pub fn on_desktop_run_event(event: &tauri::RunEvent) {
+    if let tauri::RunEvent::ExitRequested { .. } = event {
+        session::mark_ending();
+    }
+}
+
 pub fn on_desktop_window_event(window: &tauri::Window, event: &WindowEvent) {
-    // Hide window on close instead of quitting (tray keeps running)
-    if let WindowEvent::CloseRequested { api, .. } = event {
-        let _ = window.hide();
-        api.prevent_close();
+    match event {
+        WindowEvent::CloseRequested { api, .. } => on_close_requested(window, api),
+        WindowEvent::ScaleFactorChanged {
+            scale_factor,
+            new_inner_size,
+            ..
+        } => {
+            let _ = window.emit(
+                "scale-factor-changed",
+                ScaleFactorChanged {
+                    label: window.label().to_string(),
+                    scale_factor: *scale_factor,
+                    width: new_inner_size.width,
+                    height: new_inner_size.height,
+                },
+            );
+            dock::reapply(window);
+        }
+        WindowEvent::Resized(_) => dock::reapply(window),
+        // Dismiss quick input when the user clicks away, like a launcher
+        WindowEvent::Focused(false) if window.label() == quick_input::QUICK_INPUT_LABEL => {
+            let _ = window.hide();
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::resolve_close_action;
+    use super::settings::CloseBehavior;
+    use super::CloseAction::{Ask, Close, Hide, HideToTray, Quit};
+    use super::ClosingWindow::{Main, Other, QuickInput};
+
+    #[test]
+    fn main_window_follows_close_behavior_regardless_of_quit_policy() {
+        for quit in [false, true] {
+            for others in [0, 1] {
+                assert_eq!(
+                    resolve_close_action(Main, CloseBehavior::HideToTray, quit, others),
+                    HideToTray
+                );
+                assert_eq!(
+                    resolve_close_action(Main, CloseBehavior::Quit, quit, others),
+                    Quit
+                );
+                assert_eq!(
+                    resolve_close_action(Main, CloseBehavior::Ask, quit, others),
+                    Ask
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn closing_last_visible_window_quits_only_when_enabled() {
+        for behavior in [
+            CloseBehavior::HideToTray,
+            CloseBehavior::Quit,
+            CloseBehavior::Ask,
+        ] {
+            assert_eq!(resolve_close_action(Other, behavior, true, 0), Quit);
+            assert_eq!(resolve_close_action(Other, behavior, false, 0), Close);
+        }
+    }
+
+    #[test]
+    fn other_visible_windows_keep_the_app_alive() {
+        assert_eq!(
+            resolve_close_action(Other, CloseBehavior::HideToTray, true, 1),
+            Close
+        );
+        assert_eq!(
+            resolve_close_action(Other, CloseBehavior::Quit, true, 2),
+            Close
+        );
+    }
+
+    #[test]
+    fn quick_input_only_hides_and_never_quits() {
+        for quit in [false, true] {
+            assert_eq!(
+                resolve_close_action(QuickInput, CloseBehavior::Quit, quit, 0),
+                Hide
+            );
+        }
     }
 }
@@ -0,0 +1,140 @@
+use std::{thread, time::Duration};
+
+use serde::Serialize;
+use tauri::AppHandle;
+use tauri_plugin_autostart::ManagerExt;
+
+use crate::cli::AUTOSTART_ARG;
+
+const ATTEMPTS: u32 = 3;
+const INITIAL_BACKOFF: Duration = Duration::from_millis(150);
+
+/// A failed autostart change, with the state actually in effect so the UI
+/// toggle can snap back to the truth.
+#[derive(Debug, Clone, Serialize)]
+pub struct AutostartError {
+    pub message: String,
+    /// Verified state after the last attempt; `None` if it couldn't be read.
+    pub enabled: Option<bool>,
+}
+
+/// Enable or disable launch at login, retrying transient failures (registry
+/// locks, file contention) and checking with `is_enabled` that the change
+/// actually took, since some failures are silent. Blocks while backing off.
+pub fn set_enabled(app: &AppHandle, enabled: bool) -> Result<(), AutostartError> {
+    let autolaunch = app.autolaunch();
+    let mut backoff = INITIAL_BACKOFF;
+    let mut last_error = String::new();
+
+    for attempt in 1..=ATTEMPTS {
+        let result = if enabled {
+            autolaunch.enable()
+        } else {
+            autolaunch.disable()
+        };
+        match result {
+            Ok(()) => match autolaunch.is_enabled() {
+                Ok(actual) if actual == enabled => return Ok(()),
+                Ok(_) => last_error = "The change did not take effect".to_string(),
+                Err(e) => last_error = format!("{e}"),
+            },
+            Err(e) => last_error = format!("{e}"),
+        }
+        if attempt < ATTEMPTS {
+            log::warn!("Autostart change failed (attempt {attempt}), retrying: {last_error}");
+            thread::sleep(backoff);
+            backoff *= 2;
+        }
+    }
+
+    Err(AutostartError {
+        message: last_error,
+        enabled: autolaunch.is_enabled().ok(),
+    })
+}
+
+/// Outcome of [`verify`].
+#[derive(Debug, Clone, Serialize)]
+pub struct AutostartHealth {
+    pub enabled: bool,
+    /// The entry pointed at this executable before any repair. Always true
+    /// when autostart is off, since there is nothing to point anywhere.
+    pub healthy: bool,
+    /// A stale entry was re-registered with the current executable.
+    pub repaired: bool,
+}
+
+/// Check that an enabled launch-at-login entry still points at the running
+/// executable and re-register it if not, e.g. after an update moved the app
+/// to a new path and left the old entry launching nothing. Entries written
+/// before they carried [`AUTOSTART_ARG`] are re-registered too.
+pub fn verify(app: &AppHandle) -> Result<AutostartHealth, String> {
+    let enabled = app.autolaunch().is_enabled().map_err(|e| format!("{e}"))?;
+    if !enabled {
+        return Ok(AutostartHealth {
+            enabled,
+            healthy: true,
+            repaired: false,
+        });
+    }
+
+    let target = launch_target()?;
+    let healthy = registered_entry(&app.package_info().name)
+        .is_some_and(|entry| entry.contains(&target) && entry.contains(AUTOSTART_ARG));
+    if !healthy {
+        log::warn!("Autostart entry does not launch {target} {AUTOSTART_ARG}, re-registering");
+        // Enabling again rewrites the entry with the current path
+        set_enabled(app, true).map_err(|e| e.message)?;
+    }
+    Ok(AutostartHealth {
+        enabled,
+        healthy,
+        repaired: !healthy,
+    })
+}
+
+/// The path the autostart plugin registers: the AppImage rather than its
+/// temporary mount on Linux, otherwise the executable itself.
+fn launch_target() -> Result<String, String> {
+    #[cfg(target_os = "linux")]
+    if let Some(appimage) = std::env::var_os("APPIMAGE") {
+        return Ok(appimage.to_string_lossy().into_owned());
+    }
+    std::env::current_exe()
+        .map(|exe| exe.display().to_string())
+        .map_err(|e| format!("Could not locate the app executable: {e}"))
+}
+
+/// Raw text of the registered entry, which contains the launch command.
+#[cfg(target_os = "linux")]
+fn registered_entry(name: &str) -> Option<String> {
+    let path = dirs::config_dir()?
+        .join("autostart")
+        .join(format!("{name}.desktop"));
+    std::fs::read_to_string(path).ok()
+}
+
+#[cfg(target_os = "macos")]
+fn registered_entry(name: &str) -> Option<String> {
+    let path = dirs::home_dir()?
+        .join("Library/LaunchAgents")
+        .join(format!("{name}.plist"));
+    std::fs::read_to_string(path).ok()
+}
+
+#[cfg(target_os = "windows")]
+fn registered_entry(name: &str) -> Option<String> {
+    let output = super::console_command("reg")
+        .args([
+            "query",
+            r"HKCU\Software\Microsoft\Windows\CurrentVersion\Run",
+            "/v",
+            name,
+        ])
+        .output()
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
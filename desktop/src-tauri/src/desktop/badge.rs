@@ -0,0 +1,272 @@
+use std::{fs, path::PathBuf};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::{Deserialize, Serialize};
+use tauri::{image::Image, AppHandle, Manager};
+use tauri_plugin_fcm::ProgressUpdate;
+
+use super::{settings::SettingsState, tray::TRAY_ID, UiState};
+
+/// Kept next to the settings file, so each profile has its own count.
+const UNREAD_FILE: &str = "unread_count.json";
+
+const OVERLAY_SIZE: u32 = 16;
+const BADGE_RED: [u8; 4] = [0xE5, 0x39, 0x35, 0xFF];
+const BUSY_AMBER: [u8; 4] = [0xFB, 0x8C, 0x00, 0xFF];
+const WHITE: [u8; 4] = [0xFF, 0xFF, 0xFF, 0xFF];
+
+/// 3x5 bitmaps for the tray badge count, one row per entry, high bit left.
+const DIGITS: [[u8; 5]; 10] = [
+    [0b111, 0b101, 0b101, 0b101, 0b111],
+    [0b010, 0b110, 0b010, 0b010, 0b111],
+    [0b111, 0b001, 0b111, 0b100, 0b111],
+    [0b111, 0b001, 0b111, 0b001, 0b111],
+    [0b101, 0b101, 0b111, 0b001, 0b001],
+    [0b111, 0b100, 0b111, 0b001, 0b111],
+    [0b111, 0b100, 0b111, 0b101, 0b111],
+    [0b111, 0b001, 0b001, 0b001, 0b001],
+    [0b111, 0b101, 0b111, 0b101, 0b111],
+    [0b111, 0b101, 0b111, 0b001, 0b111],
+];
+const PLUS: [u8; 5] = [0b000, 0b010, 0b111, 0b010, 0b000];
+
+/// What the tray icon signals. Composited onto the app icon at runtime, so
+/// there are no per-state assets to keep in sync with it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TrayState {
+    #[default]
+    Idle,
+    /// An amber dot in the bottom-right corner.
+    Busy,
+    /// A red badge with the count in the top-right corner; above 9 it reads
+    /// "9+". Zero is the same as `Idle`.
+    Unread(u32),
+}
+
+impl TrayState {
+    /// Name reported in the UI snapshot.
+    pub fn variant(self) -> &'static str {
+        match self {
+            TrayState::Idle | TrayState::Unread(0) => "default",
+            TrayState::Busy => "busy",
+            TrayState::Unread(_) => "unread",
+        }
+    }
+}
+
+/// Push the stored unread count to every native surface: the tray tooltip on
+/// all platforms, the dock badge on macOS and the taskbar overlay on Windows.
+/// Zero clears them all. Called on every count change and after a tray rebuild.
+pub fn apply(app: &AppHandle) {
+    let ui = app.state::<UiState>();
+    let count = ui.unread_count();
+
+    if let Some(tray) = app.tray_by_id(TRAY_ID) {
+        let _ = tray.set_tooltip(Some(&ui.tray_tooltip()));
+    }
+
+    let Some(window) = app.get_webview_window("main") else {
+        return;
+    };
+    // No-op outside macOS (and Unity-based Linux desktops)
+    let _ = window.set_badge_count((count > 0).then_some(i64::from(count)));
+    #[cfg(target_os = "windows")]
+    {
+        let _ = window.set_overlay_icon((count > 0).then(overlay_dot));
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct Unread {
+    count: u32,
+}
+
+fn unread_path(app: &AppHandle) -> PathBuf {
+    app.state::<SettingsState>()
+        .path()
+        .with_file_name(UNREAD_FILE)
+}
+
+/// The unread count saved before the last exit, zero if there is none.
+pub fn load_unread(app: &AppHandle) -> u32 {
+    fs::read(unread_path(app))
+        .ok()
+        .and_then(|bytes| serde_json::from_slice::<Unread>(&bytes).ok())
+        .map_or(0, |unread| unread.count)
+}
+
+pub fn save_unread(app: &AppHandle, count: u32) -> Result<(), String> {
+    let path = unread_path(app);
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| format!("{e}"))?;
+    }
+    let json = serde_json::to_vec_pretty(&Unread { count }).map_err(|e| format!("{e}"))?;
+    fs::write(path, json).map_err(|e| format!("{e}"))
+}
+
+/// Decode a base64 PNG for use as the window icon. Linux has no badge API, so
+/// the frontend draws the unread count into a copy of the icon instead.
+pub fn decode_icon(png_base64: &str) -> Result<Image<'static>, String> {
+    let bytes = STANDARD
+        .decode(png_base64.trim())
+        .map_err(|e| format!("Invalid base64: {e}"))?;
+    Image::from_bytes(&bytes).map_err(|e| format!("Invalid PNG: {e}"))
+}
+
+/// Set every window's icon, or restore the bundled one with `None`.
+pub fn set_window_icons(app: &AppHandle, icon: Option<Image<'static>>) -> tauri::Result<()> {
+    let Some(icon) = icon.or_else(|| app.default_window_icon().cloned()) else {
+        return Ok(());
+    };
+    for window in app.webview_windows().values() {
+        window.set_icon(icon.clone())?;
+    }
+    Ok(())
+}
+
+/// Compose the tray tooltip from the base text and the unread count.
+pub fn tooltip_text(base: &str, unread: u32) -> String {
+    match unread {
+        0 => base.to_string(),
+        n => format!("{base} ({n} unread {})", plural(n, "message", "messages")),
+    }
+}
+
+fn plural<'a>(n: u32, singular: &'a str, plural: &'a str) -> &'a str {
+    if n == 1 {
+        singular
+    } else {
+        plural
+    }
+}
+
+/// Append an operation's progress to the tooltip, e.g. "Companion — Syncing 40%".
+pub fn with_progress(tooltip: String, progress: Option<&ProgressUpdate>) -> String {
+    match progress {
+        None => tooltip,
+        Some(p) if p.indeterminate => format!("{tooltip} — {}…", p.title),
+        Some(p) => format!("{tooltip} — {} {}%", p.title, p.progress),
+    }
+}
+
+/// A filled red circle for the Windows taskbar overlay. The overlay is too
+/// small to render a legible count, so it only signals "something unread".
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn overlay_dot() -> Image<'static> {
+    let mut rgba = vec![0; (OVERLAY_SIZE * OVERLAY_SIZE * 4) as usize];
+    let radius = OVERLAY_SIZE as f32 / 2.0;
+    fill_circle(&mut rgba, OVERLAY_SIZE, radius, radius, radius, BADGE_RED);
+    Image::new_owned(rgba, OVERLAY_SIZE, OVERLAY_SIZE)
+}
+
+/// The tray icon for `state`: `base` with the state's mark drawn over it.
+pub fn tray_icon(base: &Image<'_>, state: TrayState) -> Image<'static> {
+    let (width, height) = (base.width(), base.height());
+    let mut rgba = base.rgba().to_vec();
+    let size = width.min(height) as f32;
+    match state {
+        TrayState::Idle | TrayState::Unread(0) => {}
+        TrayState::Busy => {
+            let radius = size * 0.2;
+            let (cx, cy) = (width as f32 - radius, height as f32 - radius);
+            fill_circle(&mut rgba, width, cx, cy, radius, BUSY_AMBER);
+        }
+        TrayState::Unread(count) => {
+            let radius = size * 0.28;
+            let (cx, cy) = (width as f32 - radius, radius);
+            fill_circle(&mut rgba, width, cx, cy, radius, BADGE_RED);
+            let glyphs = match count {
+                1..=9 => vec![DIGITS[count as usize]],
+                _ => vec![DIGITS[9], PLUS],
+            };
+            draw_text(&mut rgba, width, cx, cy, radius * 2.0, &glyphs);
+        }
+    }
+    Image::new_owned(rgba, width, height)
+}
+
+fn fill_circle(rgba: &mut [u8], width: u32, cx: f32, cy: f32, radius: f32, color: [u8; 4]) {
+    let height = rgba.len() as u32 / 4 / width;
+    for y in 0..height {
+        for x in 0..width {
+            let dx = x as f32 + 0.5 - cx;
+            let dy = y as f32 + 0.5 - cy;
+            if dx * dx + dy * dy <= radius * radius {
+                let i = ((y * width + x) * 4) as usize;
+                rgba[i..i + 4].copy_from_slice(&color);
+            }
+        }
+    }
+}
+
+/// Draw `glyphs` in white, centered on (`cx`, `cy`) and scaled by whole
+/// pixels to fit a badge `diameter` across.
+fn draw_text(rgba: &mut [u8], width: u32, cx: f32, cy: f32, diameter: f32, glyphs: &[[u8; 5]]) {
+    let height = rgba.len() as u32 / 4 / width;
+    let text_width = glyphs.len() as u32 * 4 - 1;
+    let scale = (diameter * 0.6 / 5.0)
+        .min(diameter * 0.75 / text_width as f32)
+        .max(1.0) as u32;
+    let left = (cx - (text_width * scale) as f32 / 2.0).round() as i64;
+    let top = (cy - (5 * scale) as f32 / 2.0).round() as i64;
+    for (n, glyph) in glyphs.iter().enumerate() {
+        for (row, bits) in glyph.iter().enumerate() {
+            for col in 0..3 {
+                if bits & (0b100 >> col) == 0 {
+                    continue;
+                }
+                let x0 = left + ((n as u32 * 4 + col) * scale) as i64;
+                let y0 = top + (row as u32 * scale) as i64;
+                for y in y0..y0 + scale as i64 {
+                    for x in x0..x0 + scale as i64 {
+                        if (0..width as i64).contains(&x) && (0..height as i64).contains(&y) {
+                            let i = ((y as u32 * width + x as u32) * 4) as usize;
+                            rgba[i..i + 4].copy_from_slice(&WHITE);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pixel(image: &Image<'_>, x: u32, y: u32) -> [u8; 4] {
+        let i = ((y * image.width() + x) * 4) as usize;
+        image.rgba()[i..i + 4].try_into().unwrap()
+    }
+
+    #[test]
+    fn idle_and_zero_unread_leave_the_icon_alone() {
+        let base = Image::new_owned(vec![0x20; 32 * 32 * 4], 32, 32);
+        assert_eq!(tray_icon(&base, TrayState::Idle).rgba(), base.rgba());
+        assert_eq!(tray_icon(&base, TrayState::Unread(0)).rgba(), base.rgba());
+    }
+
+    #[test]
+    fn states_mark_their_corner() {
+        let base = Image::new_owned(vec![0; 32 * 32 * 4], 32, 32);
+        let busy = tray_icon(&base, TrayState::Busy);
+        assert_eq!(pixel(&busy, 29, 29), BUSY_AMBER);
+        assert_eq!(pixel(&busy, 2, 2), [0; 4]);
+
+        let unread = tray_icon(&base, TrayState::Unread(3));
+        assert_eq!(pixel(&unread, 23, 1), BADGE_RED);
+        assert_eq!(pixel(&unread, 29, 29), [0; 4]);
+        assert!(unread.rgba().chunks(4).any(|p| p == WHITE));
+    }
+
+    #[test]
+    fn tooltip_counts_unread_messages() {
+        assert_eq!(tooltip_text("Companion", 0), "Companion");
+        assert_eq!(tooltip_text("Companion", 1), "Companion (1 unread message)");
+        assert_eq!(
+            tooltip_text("Companion", 4),
+            "Companion (4 unread messages)"
+        );
+    }
+}
@@ -0,0 +1,36 @@
+use tauri::AppHandle;
+use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
+
+/// Show a native OK/Cancel dialog and resolve to whether the user confirmed.
+/// Native dialogs work while every window is hidden, unlike webview modals.
+/// The dialog runs on a blocking thread, so dropping the future early just
+/// leaves the dialog to be answered and ignored.
+pub async fn confirm(
+    app: &AppHandle,
+    title: String,
+    message: String,
+    confirm_label: String,
+    cancel_label: String,
+) -> bool {
+    let dialog = app
+        .dialog()
+        .message(message)
+        .title(title)
+        .kind(MessageDialogKind::Warning)
+        .buttons(MessageDialogButtons::OkCancelCustom(
+            confirm_label,
+            cancel_label,
+        ));
+    tauri::async_runtime::spawn_blocking(move || dialog.blocking_show())
+        .await
+        .unwrap_or(false)
+}
+
+/// Show a native error message without waiting for it to be dismissed.
+pub fn alert(app: &AppHandle, title: &str, message: &str) {
+    app.dialog()
+        .message(message)
+        .title(title)
+        .kind(MessageDialogKind::Error)
+        .show(|_| {});
+}
@@ -0,0 +1,74 @@
+use tauri::{Manager, Monitor, PhysicalPosition, PhysicalSize, Window};
+
+use super::settings::{Corner, Dock, SettingsState};
+
+/// Move a window into `dock.corner` of its monitor's work area, `dock.margin`
+/// logical pixels from the edges. A window larger than the work area is
+/// pinned to the area's top-left instead of hanging off screen.
+pub fn apply(window: &Window, dock: Dock) -> tauri::Result<()> {
+    let Some(monitor) = window.current_monitor()?.or(window.primary_monitor()?) else {
+        return Ok(());
+    };
+    let position = corner_position(&monitor, dock, window.outer_size()?);
+    window.set_position(position)
+}
+
+/// Re-dock the main window if docking is on, after something (a resize, a
+/// scale factor change) may have moved its corner. Minimized and maximized
+/// windows are left where the OS put them; they re-dock once restored.
+pub fn reapply(window: &Window) {
+    if window.label() != "main" {
+        return;
+    }
+    if window.is_minimized().unwrap_or(false) || window.is_maximized().unwrap_or(false) {
+        return;
+    }
+    let Some(dock) = window.state::<SettingsState>().get().main_window_dock else {
+        return;
+    };
+    if let Err(e) = apply(window, dock) {
+        log::warn!("Could not dock the main window: {e}");
+    }
+}
+
+fn corner_position(
+    monitor: &Monitor,
+    dock: Dock,
+    size: PhysicalSize<u32>,
+) -> PhysicalPosition<i32> {
+    let area = monitor.work_area();
+    let margin = (f64::from(dock.margin) * monitor.scale_factor()).round() as i32;
+    let (width, height) = (size.width as i32, size.height as i32);
+    let (area_width, area_height) = (area.size.width as i32, area.size.height as i32);
+
+    let left = area.position.x + margin;
+    let right = area.position.x + area_width - width - margin;
+    let top = area.position.y + margin;
+    let bottom = area.position.y + area_height - height - margin;
+    let (x, y) = match dock.corner {
+        Corner::TopLeft => (left, top),
+        Corner::TopRight => (right, top),
+        Corner::BottomLeft => (left, bottom),
+        Corner::BottomRight => (right, bottom),
+    };
+
+    // Keep the whole window inside the work area, even with a huge margin
+    clamp_to_work_area(monitor, PhysicalPosition::new(x, y), size)
+}
+
+/// Move `position` so a window of `size` lies inside the monitor's work area,
+/// pinning one larger than the area to its top-left.
+pub fn clamp_to_work_area(
+    monitor: &Monitor,
+    position: PhysicalPosition<i32>,
+    size: PhysicalSize<u32>,
+) -> PhysicalPosition<i32> {
+    let area = monitor.work_area();
+    let clamp = |value: i32, start: i32, extent: u32, length: u32| {
+        value.min(start + extent as i32 - length as i32).max(start)
+    };
+    PhysicalPosition::new(
+        clamp(position.x, area.position.x, area.size.width, size.width),
+        clamp(position.y, area.position.y, area.size.height, size.height),
+    )
+}
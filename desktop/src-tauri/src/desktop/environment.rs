@@ -0,0 +1,87 @@
+use std::{env, path::Path};
+
+use serde::Serialize;
+
+/// How this copy of the app was packaged, as far as it can be inferred.
+/// Undetectable cases are reported as a regular, non-sandboxed install.
+#[derive(Debug, Clone, Serialize)]
+pub struct RuntimeEnvironment {
+    /// Running inside an OS sandbox (App Sandbox, MSIX container, Flatpak, Snap).
+    pub sandboxed: bool,
+    /// Installed from a store (Mac App Store, Microsoft Store, Flathub, Snap Store).
+    pub store_build: bool,
+    /// `mas`, `app`, `msix`, `installer`, `flatpak`, `snap`, `appimage`, `system` or `unknown`.
+    pub installer_kind: String,
+}
+
+impl RuntimeEnvironment {
+    fn new(sandboxed: bool, store_build: bool, installer_kind: &str) -> Self {
+        Self {
+            sandboxed,
+            store_build,
+            installer_kind: installer_kind.to_string(),
+        }
+    }
+
+    /// Whether the autostart plugin's mechanism (LaunchAgent plist, `Run`
+    /// registry key, XDG autostart entry) can work. Sandboxes either can't
+    /// write those locations or ignore them, and expect the OS settings UI instead.
+    pub fn supports_plugin_autostart(&self) -> bool {
+        !self.sandboxed
+    }
+}
+
+pub fn detect() -> RuntimeEnvironment {
+    let exe = env::current_exe().unwrap_or_default();
+    detect_for(&exe)
+}
+
+#[cfg(target_os = "macos")]
+fn detect_for(exe: &Path) -> RuntimeEnvironment {
+    let sandboxed = env::var_os("APP_SANDBOX_CONTAINER_ID").is_some();
+    // <bundle>.app/Contents/MacOS/<exe> -> <bundle>.app/Contents/_MASReceipt/receipt
+    let has_receipt = exe
+        .parent()
+        .and_then(Path::parent)
+        .map(|contents| contents.join("_MASReceipt/receipt").exists())
+        .unwrap_or(false);
+    if has_receipt {
+        RuntimeEnvironment::new(true, true, "mas")
+    } else {
+        RuntimeEnvironment::new(sandboxed, false, "app")
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn detect_for(exe: &Path) -> RuntimeEnvironment {
+    // MSIX packages are always installed under Program Files\WindowsApps
+    let packaged = exe
+        .components()
+        .any(|c| c.as_os_str().eq_ignore_ascii_case("WindowsApps"));
+    if packaged {
+        RuntimeEnvironment::new(true, true, "msix")
+    } else {
+        RuntimeEnvironment::new(false, false, "installer")
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn detect_for(exe: &Path) -> RuntimeEnvironment {
+    if env::var_os("FLATPAK_ID").is_some() || Path::new("/.flatpak-info").exists() {
+        RuntimeEnvironment::new(true, true, "flatpak")
+    } else if env::var_os("SNAP").is_some() {
+        RuntimeEnvironment::new(true, true, "snap")
+    } else if env::var_os("APPIMAGE").is_some() {
+        RuntimeEnvironment::new(false, false, "appimage")
+    } else if exe.starts_with("/usr") || exe.starts_with("/opt") {
+        RuntimeEnvironment::new(false, false, "system")
+    } else {
+        RuntimeEnvironment::new(false, false, "unknown")
+    }
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+fn detect_for(exe: &Path) -> RuntimeEnvironment {
+    let _ = exe;
+    RuntimeEnvironment::new(false, false, "unknown")
+}
@@ -0,0 +1,64 @@
+use std::{
+    env,
+    ffi::OsString,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+};
+
+use super::settings;
+
+/// WKWebView has no switch for software rendering, so macOS has no workaround.
+pub const SUPPORTED: bool = cfg!(any(target_os = "linux", target_os = "windows"));
+
+/// Whether this process was started with the GPU workaround applied.
+static APPLIED: AtomicBool = AtomicBool::new(false);
+/// Values the workaround overwrote, restored before a restart so the
+/// relaunched process starts from a clean environment.
+static ORIGINAL_ENV: Mutex<Vec<(&'static str, Option<OsString>)>> = Mutex::new(Vec::new());
+
+/// Set the webview's software-rendering switches if the persisted
+/// `disable_gpu` setting asks for them. Both WebKitGTK and WebView2 read these
+/// when the first webview is created, so this must run before the builder.
+pub fn apply(identifier: &str, profile: Option<&str>) {
+    if !settings::load_early(identifier, profile).disable_gpu {
+        return;
+    }
+    #[cfg(target_os = "linux")]
+    {
+        set_var("WEBKIT_DISABLE_DMABUF_RENDERER", "1".into());
+        set_var("WEBKIT_DISABLE_COMPOSITING_MODE", "1".into());
+        APPLIED.store(true, Ordering::SeqCst);
+    }
+    #[cfg(target_os = "windows")]
+    {
+        let mut args = env::var_os("WEBVIEW2_ADDITIONAL_BROWSER_ARGUMENTS").unwrap_or_default();
+        if !args.is_empty() {
+            args.push(" ");
+        }
+        args.push("--disable-gpu");
+        set_var("WEBVIEW2_ADDITIONAL_BROWSER_ARGUMENTS", args);
+        APPLIED.store(true, Ordering::SeqCst);
+    }
+}
+
+pub fn is_applied() -> bool {
+    APPLIED.load(Ordering::SeqCst)
+}
+
+#[cfg_attr(target_os = "macos", allow(dead_code))]
+fn set_var(key: &'static str, value: OsString) {
+    ORIGINAL_ENV.lock().unwrap().push((key, env::var_os(key)));
+    env::set_var(key, value);
+}
+
+/// Undo [`apply`] so a restart re-evaluates the setting from scratch.
+pub fn restore_env() {
+    for (key, original) in ORIGINAL_ENV.lock().unwrap().drain(..).rev() {
+        match original {
+            Some(value) => env::set_var(key, value),
+            None => env::remove_var(key),
+        }
+    }
+}
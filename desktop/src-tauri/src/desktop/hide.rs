@@ -0,0 +1,54 @@
+use tauri::{WebviewWindow, Window};
+
+/// Hide a window to the tray. On Windows, DWM plays the minimize transition
+/// for a plain hide too, which looks like the window went to the taskbar, so
+/// transitions are switched off first and back on by [`before_show`].
+pub fn hide_to_tray(window: &Window) {
+    #[cfg(target_os = "windows")]
+    if let Ok(hwnd) = window.hwnd() {
+        set_transitions(hwnd.0, false);
+    }
+    let _ = window.hide();
+}
+
+/// Undo [`hide_to_tray`]'s animation suppression so the shown window
+/// minimizes and restores with the usual transitions.
+pub fn before_show(window: &WebviewWindow) {
+    #[cfg(target_os = "windows")]
+    if let Ok(hwnd) = window.hwnd() {
+        set_transitions(hwnd.0, true);
+    }
+    #[cfg(not(target_os = "windows"))]
+    let _ = window;
+}
+
+#[cfg(target_os = "windows")]
+fn set_transitions(hwnd: *mut std::ffi::c_void, enabled: bool) {
+    use std::ffi::c_void;
+
+    const DWMWA_TRANSITIONS_FORCEDISABLED: u32 = 3;
+
+    #[link(name = "dwmapi")]
+    extern "system" {
+        fn DwmSetWindowAttribute(
+            hwnd: *mut c_void,
+            attribute: u32,
+            value: *const c_void,
+            size: u32,
+        ) -> i32;
+    }
+
+    // A Win32 BOOL
+    let disabled = i32::from(!enabled);
+    let result = unsafe {
+        DwmSetWindowAttribute(
+            hwnd,
+            DWMWA_TRANSITIONS_FORCEDISABLED,
+            &disabled as *const i32 as *const c_void,
+            std::mem::size_of::<i32>() as u32,
+        )
+    };
+    if result < 0 {
+        log::warn!("DwmSetWindowAttribute failed: {result:#x}");
+    }
+}
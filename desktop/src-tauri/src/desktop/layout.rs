@@ -0,0 +1,152 @@
+use serde::Serialize;
+use tauri::{AppHandle, Manager, Monitor, PhysicalPosition, PhysicalSize, WebviewWindow};
+
+use super::{
+    dock, hide, mini,
+    settings::{SettingsState, WindowPlacement},
+};
+
+/// What `restore` managed to put back.
+#[derive(Debug, Clone, Serialize)]
+pub struct RestoredLayout {
+    pub restored: Vec<String>,
+    /// Windows that no longer exist and can't be recreated, or that failed
+    /// to move. The rest of the layout is still applied.
+    pub skipped: Vec<String>,
+}
+
+/// Record where every open window is, replacing any layout saved as `name`.
+pub fn save(app: &AppHandle, name: &str) -> Result<Vec<WindowPlacement>, String> {
+    let mut placements = app
+        .webview_windows()
+        .values()
+        .map(placement)
+        .collect::<tauri::Result<Vec<_>>>()
+        .map_err(|e| format!("{e}"))?;
+    placements.sort_by(|a, b| a.label.cmp(&b.label));
+    app.state::<SettingsState>().update(|s| {
+        s.window_layouts
+            .insert(name.to_string(), placements.clone());
+    })?;
+    Ok(placements)
+}
+
+/// Move every window in the layout back into place, reopening the mini
+/// window if it has since been closed. Positions are clamped to the monitors
+/// connected now, so a layout saved on an unplugged display stays reachable.
+/// Windows opened since the layout was saved are left alone.
+pub fn restore(app: &AppHandle, name: &str) -> Result<RestoredLayout, String> {
+    let placements = app
+        .state::<SettingsState>()
+        .get()
+        .window_layouts
+        .remove(name)
+        .ok_or_else(|| format!("No layout named {name}"))?;
+    let monitors = app.available_monitors().map_err(|e| format!("{e}"))?;
+
+    let mut result = RestoredLayout {
+        restored: Vec::new(),
+        skipped: Vec::new(),
+    };
+    for placement in placements {
+        let window = match reopen(app, &placement.label) {
+            Ok(Some(window)) => window,
+            Ok(None) => {
+                log::warn!(
+                    "Layout {name}: window {} can't be recreated",
+                    placement.label
+                );
+                result.skipped.push(placement.label);
+                continue;
+            }
+            Err(e) => {
+                log::warn!("Layout {name}: could not reopen {}: {e}", placement.label);
+                result.skipped.push(placement.label);
+                continue;
+            }
+        };
+        match place(&window, &placement, &monitors) {
+            Ok(()) => result.restored.push(placement.label),
+            Err(e) => {
+                log::warn!("Layout {name}: could not place {}: {e}", placement.label);
+                result.skipped.push(placement.label);
+            }
+        }
+    }
+    Ok(result)
+}
+
+fn placement(window: &WebviewWindow) -> tauri::Result<WindowPlacement> {
+    let position = window.outer_position()?;
+    // Inner, since that is what set_size restores; the outer size would grow
+    // the window by its decorations on every save and restore
+    let size = window.inner_size()?;
+    Ok(WindowPlacement {
+        label: window.label().to_string(),
+        x: position.x,
+        y: position.y,
+        width: size.width,
+        height: size.height,
+        monitor: window.current_monitor()?.and_then(|m| m.name().cloned()),
+        maximized: window.is_maximized()?,
+        visible: window.is_visible()?,
+    })
+}
+
+/// The window with `label`, opening it again if it was closed. Only the mini
+/// window can be recreated; the main window hides instead of closing, and
+/// anything else (a label from an older version) is reported as skipped.
+fn reopen(app: &AppHandle, label: &str) -> tauri::Result<Option<WebviewWindow>> {
+    if let Some(window) = app.get_webview_window(label) {
+        return Ok(Some(window));
+    }
+    if label != mini::MINI_LABEL {
+        return Ok(None);
+    }
+    mini::toggle(app)?;
+    Ok(app.get_webview_window(label))
+}
+
+fn place(
+    window: &WebviewWindow,
+    placement: &WindowPlacement,
+    monitors: &[Monitor],
+) -> tauri::Result<()> {
+    // A maximized window ignores moves, so restore its normal frame first
+    if window.is_maximized()? {
+        window.unmaximize()?;
+    }
+    let size = PhysicalSize::new(placement.width, placement.height);
+    let mut position = PhysicalPosition::new(placement.x, placement.y);
+    if let Some(monitor) = target_monitor(monitors, placement) {
+        position = dock::clamp_to_work_area(monitor, position, size);
+    }
+    window.set_size(size)?;
+    window.set_position(position)?;
+    if placement.maximized {
+        window.maximize()?;
+    }
+    if placement.visible && !window.is_visible()? {
+        hide::before_show(window);
+        window.show()?;
+    }
+    Ok(())
+}
+
+/// The monitor the window was saved on if it is still connected, else the one
+/// now under its top-left corner, else the first one available.
+fn target_monitor<'a>(monitors: &'a [Monitor], placement: &WindowPlacement) -> Option<&'a Monitor> {
+    let saved = placement
+        .monitor
+        .as_ref()
+        .and_then(|name| monitors.iter().find(|m| m.name() == Some(name)));
+    saved
+        .or_else(|| {
+            monitors.iter().find(|m| {
+                let (position, size) = (m.position(), m.size());
+                (position.x..position.x + size.width as i32).contains(&placement.x)
+                    && (position.y..position.y + size.height as i32).contains(&placement.y)
+            })
+        })
+        .or(monitors.first())
+}
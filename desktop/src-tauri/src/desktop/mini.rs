@@ -0,0 +1,93 @@
+use tauri::{AppHandle, Manager, Monitor, WebviewUrl, WebviewWindowBuilder};
+
+use super::{
+    opacity,
+    settings::{Corner, SettingsState},
+};
+
+/// Window label, which is also the mini window's key in the window-state store.
+pub const MINI_LABEL: &str = "mini";
+
+const WIDTH: f64 = 320.0;
+const HEIGHT: f64 = 180.0;
+/// Gap between the window and the edges of the monitor's work area.
+const MARGIN: f64 = 16.0;
+
+/// Open the mini window, or close it if it is already open.
+/// Returns whether the mini window is open afterwards.
+pub fn toggle(app: &AppHandle) -> tauri::Result<bool> {
+    if let Some(window) = app.get_webview_window(MINI_LABEL) {
+        window.close()?;
+        return Ok(false);
+    }
+
+    let mut builder = WebviewWindowBuilder::new(app, MINI_LABEL, WebviewUrl::App("mini".into()))
+        .title("Companion")
+        .inner_size(WIDTH, HEIGHT)
+        .resizable(false)
+        .decorations(false)
+        .always_on_top(true)
+        .skip_taskbar(true);
+    // Start in the configured corner; the window-state plugin then moves it to
+    // wherever the user last dragged it, if it has a saved position.
+    if let Some(monitor) = target_monitor(app)? {
+        let corner = app.state::<SettingsState>().get().mini_window_corner;
+        let (x, y) = corner_position(&monitor, corner);
+        builder = builder.position(x, y);
+    }
+    let window = builder.build()?;
+    super::apply_accent_color(&window);
+    if let Some(value) = app.state::<SettingsState>().get().mini_window_opacity {
+        if let Err(e) = opacity::apply(&window, value) {
+            log::warn!("Could not restore mini window opacity: {e}");
+        }
+    }
+    if app.state::<SettingsState>().get().mini_window_click_through {
+        window.set_ignore_cursor_events(true)?;
+    }
+    window.set_focus()?;
+    Ok(true)
+}
+
+/// Make the mini window ignore the mouse (or take it back), now if it is open
+/// and whenever it opens later. A click-through window can't be clicked to
+/// undo this, so the tray menu always offers a way back.
+pub fn set_click_through(app: &AppHandle, ignore: bool) -> Result<(), String> {
+    if let Some(window) = app.get_webview_window(MINI_LABEL) {
+        window
+            .set_ignore_cursor_events(ignore)
+            .map_err(|e| format!("{e}"))?;
+    }
+    app.state::<SettingsState>()
+        .update(|s| s.mini_window_click_through = ignore)
+        .map(|_| ())
+}
+
+/// The monitor the main window is on, falling back to the primary one.
+fn target_monitor(app: &AppHandle) -> tauri::Result<Option<Monitor>> {
+    if let Some(main) = app.get_webview_window("main") {
+        if let Some(monitor) = main.current_monitor()? {
+            return Ok(Some(monitor));
+        }
+    }
+    app.primary_monitor()
+}
+
+/// Logical top-left position that puts the window in `corner` of the work area.
+fn corner_position(monitor: &Monitor, corner: Corner) -> (f64, f64) {
+    let scale = monitor.scale_factor();
+    let area = monitor.work_area();
+    let position = area.position.to_logical::<f64>(scale);
+    let size = area.size.to_logical::<f64>(scale);
+
+    let left = position.x + MARGIN;
+    let right = position.x + size.width - WIDTH - MARGIN;
+    let top = position.y + MARGIN;
+    let bottom = position.y + size.height - HEIGHT - MARGIN;
+    match corner {
+        Corner::TopLeft => (left, top),
+        Corner::TopRight => (right, top),
+        Corner::BottomLeft => (left, bottom),
+        Corner::BottomRight => (right, bottom),
+    }
+}
@@ -0,0 +1,97 @@
+use tauri::WebviewWindow;
+
+/// Below this the window is too faint to read or to find again.
+const MIN_OPACITY: f64 = 0.2;
+
+/// Clamp a requested opacity to what we allow. NaN is rejected.
+pub fn clamp(opacity: f64) -> Result<f64, String> {
+    if opacity.is_nan() {
+        return Err("Opacity must be a number".to_string());
+    }
+    Ok(opacity.clamp(MIN_OPACITY, 1.0))
+}
+
+/// Make the whole window, chrome included, translucent. Must run on the main
+/// thread. Fails on Linux without a compositing window manager, where GTK
+/// can't blend the window with what is behind it.
+pub fn apply(window: &WebviewWindow, opacity: f64) -> Result<(), String> {
+    #[cfg(target_os = "windows")]
+    return set_layered_alpha(window, opacity);
+    #[cfg(target_os = "macos")]
+    return set_alpha_value(window, opacity);
+    #[cfg(target_os = "linux")]
+    return set_gtk_opacity(window, opacity);
+    #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+    {
+        let _ = (window, opacity);
+        Err("Window opacity is not supported on this platform".to_string())
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn set_layered_alpha(window: &WebviewWindow, opacity: f64) -> Result<(), String> {
+    use std::ffi::c_void;
+
+    const GWL_EXSTYLE: i32 = -20;
+    const WS_EX_LAYERED: i32 = 0x0008_0000;
+    const LWA_ALPHA: u32 = 0x2;
+
+    #[link(name = "user32")]
+    extern "system" {
+        fn GetWindowLongW(hwnd: *mut c_void, index: i32) -> i32;
+        fn SetWindowLongW(hwnd: *mut c_void, index: i32, value: i32) -> i32;
+        fn SetLayeredWindowAttributes(hwnd: *mut c_void, key: u32, alpha: u8, flags: u32) -> i32;
+    }
+
+    let hwnd = window.hwnd().map_err(|e| format!("{e}"))?.0;
+    let alpha = (opacity * 255.0).round() as u8;
+    let ok = unsafe {
+        // Only layered windows can be translucent
+        let style = GetWindowLongW(hwnd, GWL_EXSTYLE);
+        SetWindowLongW(hwnd, GWL_EXSTYLE, style | WS_EX_LAYERED);
+        SetLayeredWindowAttributes(hwnd, 0, alpha, LWA_ALPHA)
+    };
+    if ok == 0 {
+        return Err("SetLayeredWindowAttributes failed".to_string());
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "macos")]
+fn set_alpha_value(window: &WebviewWindow, opacity: f64) -> Result<(), String> {
+    use std::ffi::{c_char, c_void};
+
+    #[link(name = "objc")]
+    extern "C" {
+        fn sel_registerName(name: *const c_char) -> *mut c_void;
+        fn objc_msgSend();
+    }
+
+    let ns_window = window.ns_window().map_err(|e| format!("{e}"))?;
+    unsafe {
+        // -[NSWindow setAlphaValue:(CGFloat)]
+        let send: unsafe extern "C" fn(*mut c_void, *mut c_void, f64) =
+            std::mem::transmute(objc_msgSend as unsafe extern "C" fn());
+        send(
+            ns_window,
+            sel_registerName(c"setAlphaValue:".as_ptr()),
+            opacity,
+        );
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+fn set_gtk_opacity(window: &WebviewWindow, opacity: f64) -> Result<(), String> {
+    use gtk::prelude::WidgetExt;
+
+    let gtk_window = window.gtk_window().map_err(|e| format!("{e}"))?;
+    let composited = gtk_window.screen().is_some_and(|s| s.is_composited());
+    if !composited {
+        return Err(
+            "Window opacity needs a compositing window manager, and this one isn't".to_string(),
+        );
+    }
+    gtk_window.set_opacity(opacity);
+    Ok(())
+}
@@ -0,0 +1,49 @@
+use std::{thread, time::Duration};
+
+use tauri::{AppHandle, Manager, WebviewWindow};
+
+use super::{hide, settings::SettingsState};
+
+/// How often a hidden main window is nudged while prewarming is enabled.
+const INTERVAL: Duration = Duration::from_secs(60);
+/// Gap between showing a window and focusing it, roughly one frame, so the
+/// compositor maps and paints the window before focus triggers a second pass.
+const SHOW_TO_FOCUS: Duration = Duration::from_millis(16);
+
+/// Force style and layout in a hidden webview. Engines stop laying out
+/// hidden pages, so a window hidden for a long time otherwise redoes all of
+/// it on the first frame after `show`, which is the visible delay.
+pub fn nudge(window: &WebviewWindow) -> tauri::Result<()> {
+    window.eval("void document.documentElement.getBoundingClientRect()")
+}
+
+/// Show a window and focus it a frame later instead of in the same tick.
+/// Unminimizing first keeps a window hidden while minimized from coming
+/// back minimized.
+pub fn show_then_focus(window: &WebviewWindow) {
+    hide::before_show(window);
+    let _ = window.unminimize();
+    let _ = window.show();
+    let window = window.clone();
+    thread::spawn(move || {
+        thread::sleep(SHOW_TO_FOCUS);
+        let _ = window.set_focus();
+    });
+}
+
+/// Keep nudging the main window while it is hidden and prewarming is enabled.
+/// The setting is read on every tick, so toggling it needs no restart.
+pub fn start(app: &AppHandle) {
+    let app = app.clone();
+    thread::spawn(move || loop {
+        thread::sleep(INTERVAL);
+        if !app.state::<SettingsState>().get().prewarm_window {
+            continue;
+        }
+        if let Some(window) = app.get_webview_window("main") {
+            if !window.is_visible().unwrap_or(true) {
+                let _ = nudge(&window);
+            }
+        }
+    });
+}
@@ -0,0 +1,57 @@
+use tauri::{AppHandle, Emitter, Manager, WebviewUrl, WebviewWindowBuilder};
+
+/// Window label; the page is served at `/quick-input`.
+pub const QUICK_INPUT_LABEL: &str = "quick-input";
+/// Emitted to the main window with the submitted prompt as payload.
+pub const QUICK_INPUT_EVENT: &str = "quick-input-submitted";
+
+const WIDTH: f64 = 600.0;
+const HEIGHT: f64 = 72.0;
+
+/// Show the quick-input window, creating it the first time. It is hidden
+/// rather than closed afterwards so the next summon is instant.
+pub fn show(app: &AppHandle) -> tauri::Result<()> {
+    let window = match app.get_webview_window(QUICK_INPUT_LABEL) {
+        Some(window) => window,
+        None => {
+            let window = WebviewWindowBuilder::new(
+                app,
+                QUICK_INPUT_LABEL,
+                WebviewUrl::App("quick-input".into()),
+            )
+            .title("Quick Input")
+            .inner_size(WIDTH, HEIGHT)
+            .resizable(false)
+            .decorations(false)
+            .always_on_top(true)
+            .skip_taskbar(true)
+            .visible(false)
+            .build()?;
+            super::apply_accent_color(&window);
+            window
+        }
+    };
+    // Re-center each time, in case it was summoned on another monitor
+    window.center()?;
+    window.show()?;
+    window.set_focus()?;
+    Ok(())
+}
+
+pub fn hide(app: &AppHandle) -> tauri::Result<()> {
+    if let Some(window) = app.get_webview_window(QUICK_INPUT_LABEL) {
+        window.hide()?;
+    }
+    Ok(())
+}
+
+/// Put the window away and hand `prompt` to the main window, which sends it
+/// to the session it has open. A blank prompt only hides the window.
+pub fn submit(app: &AppHandle, prompt: &str) -> tauri::Result<()> {
+    hide(app)?;
+    let prompt = prompt.trim();
+    if prompt.is_empty() {
+        return Ok(());
+    }
+    app.emit_to("main", QUICK_INPUT_EVENT, prompt)
+}
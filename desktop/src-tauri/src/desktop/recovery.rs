@@ -0,0 +1,190 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::Mutex,
+    thread,
+    time::{Duration, Instant},
+};
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager, WebviewWindow};
+
+use super::dialog;
+
+/// Emitted after a webview was reloaded, automatically or on request.
+pub const WEBVIEW_RECOVERED_EVENT: &str = "webview-recovered";
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(5);
+/// A visible window whose page hasn't sent a heartbeat for this long is
+/// assumed to have lost its renderer.
+const HEARTBEAT_TIMEOUT: Duration = Duration::from_secs(20);
+/// The timeout for a page that reported itself hidden, e.g. behind other
+/// windows: its timers are throttled to about once a minute.
+const HIDDEN_HEARTBEAT_TIMEOUT: Duration = Duration::from_secs(180);
+/// At most this many automatic reloads per [`RELOAD_WINDOW`]; past that the
+/// page is crashing on load and reloading again won't help.
+const MAX_RELOADS: usize = 3;
+const RELOAD_WINDOW: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WebviewRecovered {
+    pub label: String,
+    /// Reloaded by the watchdog rather than `reload_webview`.
+    pub automatic: bool,
+}
+
+/// Neither Tauri nor the webviews report a crashed renderer, so pages send a
+/// heartbeat and a visible window that goes quiet is reloaded. Only windows
+/// that have sent one are watched, so pages without the heartbeat are left
+/// alone.
+#[derive(Default)]
+pub struct RecoveryState {
+    heartbeats: Mutex<HashMap<String, Heartbeat>>,
+    reloads: Mutex<VecDeque<Instant>>,
+    /// Automatic reloads hit the limit; only a manual reload resumes them.
+    gave_up: Mutex<bool>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Heartbeat {
+    at: Instant,
+    /// The page said it was hidden, per `document.visibilityState`.
+    page_hidden: bool,
+}
+
+impl Heartbeat {
+    fn now(page_hidden: bool) -> Self {
+        Self {
+            at: Instant::now(),
+            page_hidden,
+        }
+    }
+
+    /// Whether the page has been silent for longer than it should be.
+    fn is_stale(self, now: Instant) -> bool {
+        let timeout = if self.page_hidden {
+            HIDDEN_HEARTBEAT_TIMEOUT
+        } else {
+            HEARTBEAT_TIMEOUT
+        };
+        now.duration_since(self.at) >= timeout
+    }
+}
+
+pub fn heartbeat(app: &AppHandle, label: &str, page_hidden: bool) {
+    app.state::<RecoveryState>()
+        .heartbeats
+        .lock()
+        .unwrap()
+        .insert(label.to_string(), Heartbeat::now(page_hidden));
+}
+
+/// Whether another automatic reload fits in the limit, recording it if so.
+fn allow_reload(reloads: &mut VecDeque<Instant>, now: Instant) -> bool {
+    while reloads
+        .front()
+        .is_some_and(|&at| now.duration_since(at) >= RELOAD_WINDOW)
+    {
+        reloads.pop_front();
+    }
+    if reloads.len() >= MAX_RELOADS {
+        return false;
+    }
+    reloads.push_back(now);
+    true
+}
+
+/// Reload a window's page from its current URL. Navigating starts a fresh
+/// renderer, which `location.reload()` from the dead one never could.
+pub fn reload(window: &WebviewWindow, automatic: bool) -> tauri::Result<()> {
+    let state = window.state::<RecoveryState>();
+    if !automatic {
+        state.reloads.lock().unwrap().clear();
+        *state.gave_up.lock().unwrap() = false;
+    }
+    // Give the new page the full timeout to start its heartbeat
+    state
+        .heartbeats
+        .lock()
+        .unwrap()
+        .insert(window.label().to_string(), Heartbeat::now(false));
+    window.navigate(window.url()?)?;
+    let _ = window.emit(
+        WEBVIEW_RECOVERED_EVENT,
+        WebviewRecovered {
+            label: window.label().to_string(),
+            automatic,
+        },
+    );
+    Ok(())
+}
+
+/// Watch heartbeats and reload windows that stopped sending them.
+pub fn start(app: &AppHandle) {
+    let app = app.clone();
+    thread::spawn(move || loop {
+        thread::sleep(CHECK_INTERVAL);
+        let state = app.state::<RecoveryState>();
+        let now = Instant::now();
+        let labels: Vec<String> = state.heartbeats.lock().unwrap().keys().cloned().collect();
+        for label in labels {
+            let Some(window) = app.get_webview_window(&label) else {
+                state.heartbeats.lock().unwrap().remove(&label);
+                continue;
+            };
+            // Hidden and minimized pages may be throttled or suspended, so
+            // silence while out of sight doesn't count against them
+            if !window.is_visible().unwrap_or(false) || window.is_minimized().unwrap_or(false) {
+                heartbeat(&app, &label, false);
+                continue;
+            }
+            let last = state.heartbeats.lock().unwrap().get(&label).copied();
+            if !last.is_some_and(|last| last.is_stale(now)) {
+                continue;
+            }
+            if *state.gave_up.lock().unwrap() {
+                continue;
+            }
+            if !allow_reload(&mut state.reloads.lock().unwrap(), now) {
+                log::error!("Webview {label} keeps crashing; not reloading it again");
+                *state.gave_up.lock().unwrap() = true;
+                dialog::alert(
+                    &app,
+                    "Companion stopped responding",
+                    "The window keeps crashing while loading. Reload it from the tray \
+                     menu, or restart Companion.",
+                );
+                continue;
+            }
+            log::warn!("Webview {label} stopped responding; reloading it");
+            if let Err(e) = reload(&window, true) {
+                log::warn!("Could not reload webview {label}: {e}");
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn limits_automatic_reloads_per_window_of_time() {
+        let mut reloads = VecDeque::new();
+        let start = Instant::now();
+        for i in 0..MAX_RELOADS as u64 {
+            assert!(allow_reload(&mut reloads, start + Duration::from_secs(i)));
+        }
+        assert!(!allow_reload(&mut reloads, start + Duration::from_secs(10)));
+        assert!(allow_reload(&mut reloads, start + RELOAD_WINDOW));
+    }
+
+    #[test]
+    fn hidden_pages_get_longer_to_report() {
+        let visible = Heartbeat::now(false);
+        let hidden = Heartbeat::now(true);
+        let later = visible.at.max(hidden.at) + HEARTBEAT_TIMEOUT;
+        assert!(visible.is_stale(later));
+        assert!(!hidden.is_stale(later));
+        assert!(hidden.is_stale(hidden.at + HIDDEN_HEARTBEAT_TIMEOUT));
+    }
+}
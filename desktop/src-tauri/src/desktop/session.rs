@@ -0,0 +1,88 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use tauri::WebviewWindow;
+
+/// Set once the OS starts ending the session (logout, restart, shutdown) or
+/// the app is asked to exit. Windows must then really close: hiding one to
+/// the tray instead can hold up the logout.
+static ENDING: AtomicBool = AtomicBool::new(false);
+
+pub fn is_ending() -> bool {
+    ENDING.load(Ordering::SeqCst)
+}
+
+/// Record that the app is on its way out, e.g. from `RunEvent::ExitRequested`,
+/// which is how a macOS logout reaches the app.
+pub fn mark_ending() {
+    ENDING.store(true, Ordering::SeqCst);
+}
+
+/// Start listening for the OS ending the session through `window`. Windows
+/// announces it with window messages, which Tauri doesn't surface, so the
+/// window procedure is subclassed to see them. Must run on the main thread.
+#[cfg(target_os = "windows")]
+pub fn watch(window: &WebviewWindow) -> Result<(), String> {
+    windows::watch(window)
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn watch(window: &WebviewWindow) -> Result<(), String> {
+    let _ = window;
+    Ok(())
+}
+
+#[cfg(target_os = "windows")]
+mod windows {
+    use std::{ffi::c_void, sync::OnceLock};
+
+    use tauri::{AppHandle, Manager, WebviewWindow};
+
+    const WM_QUERYENDSESSION: u32 = 0x0011;
+    const WM_ENDSESSION: u32 = 0x0016;
+    const SUBCLASS_ID: usize = 0x5E55;
+
+    type SubclassProc =
+        unsafe extern "system" fn(*mut c_void, u32, usize, isize, usize, usize) -> isize;
+
+    #[link(name = "comctl32")]
+    extern "system" {
+        fn SetWindowSubclass(hwnd: *mut c_void, proc: SubclassProc, id: usize, data: usize) -> i32;
+        fn DefSubclassProc(hwnd: *mut c_void, msg: u32, wparam: usize, lparam: isize) -> isize;
+    }
+
+    /// For exiting from the window procedure, which has no other way to the app.
+    static APP: OnceLock<AppHandle> = OnceLock::new();
+
+    pub fn watch(window: &WebviewWindow) -> Result<(), String> {
+        let _ = APP.set(window.app_handle().clone());
+        let hwnd = window.hwnd().map_err(|e| format!("{e}"))?.0;
+        if unsafe { SetWindowSubclass(hwnd, subclass_proc, SUBCLASS_ID, 0) } == 0 {
+            return Err("SetWindowSubclass failed".to_string());
+        }
+        Ok(())
+    }
+
+    unsafe extern "system" fn subclass_proc(
+        hwnd: *mut c_void,
+        msg: u32,
+        wparam: usize,
+        lparam: isize,
+        _id: usize,
+        _data: usize,
+    ) -> isize {
+        match msg {
+            WM_QUERYENDSESSION => super::mark_ending(),
+            // The session really ends (wparam is FALSE if another app vetoed
+            // it): exit now so plugins save their state before the process
+            // is terminated
+            WM_ENDSESSION if wparam != 0 => {
+                super::mark_ending();
+                if let Some(app) = APP.get() {
+                    app.exit(0);
+                }
+            }
+            _ => {}
+        }
+        DefSubclassProc(hwnd, msg, wparam, lparam)
+    }
+}
@@ -0,0 +1,417 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use tauri::{AppHandle, Manager};
+
+const SETTINGS_FILE: &str = "settings.json";
+/// Profiles from `--profile` keep their settings under `profiles/<name>/`.
+const PROFILES_DIR: &str = "profiles";
+
+/// Written into the file alongside the settings. Bump it when a change needs
+/// more than serde's defaults (a renamed or moved key, a changed meaning) and
+/// add the step to [`MIGRATIONS`].
+const SCHEMA_VERSION: u64 = 2;
+const VERSION_KEY: &str = "schema_version";
+
+/// `MIGRATIONS[i]` upgrades a file from version `i + 1` to `i + 2`.
+const MIGRATIONS: [fn(&mut Map<String, Value>); SCHEMA_VERSION as usize - 1] = [v1_to_v2];
+
+/// Version 1 predates the version key. Nothing was renamed, so stamping the
+/// version is all it takes.
+fn v1_to_v2(_: &mut Map<String, Value>) {}
+
+/// Native desktop settings, persisted to `<app config dir>/settings.json`.
+/// Frontend preferences live in the webview's store; this only holds what
+/// the Rust side needs before or without the webview.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// What closing the main window does.
+    pub close_behavior: CloseBehavior,
+    /// Start in the tray without showing the main window when launched at
+    /// login. `--hidden` does the same for any one launch.
+    pub start_minimized: bool,
+    /// Quit once no window is left visible instead of staying alive in the tray.
+    pub quit_on_last_window_closed: bool,
+    /// Where the mini window first opens, before the user drags it elsewhere.
+    pub mini_window_corner: Corner,
+    /// Mini window opacity from 0.2 to 1.0; `None` is fully opaque.
+    pub mini_window_opacity: Option<f64>,
+    /// Let clicks pass through the mini window to the apps beneath it.
+    pub mini_window_click_through: bool,
+    /// Force software rendering in the webview, for GPUs that render it black
+    /// or crash. Read before the webview starts, so changes need a restart.
+    pub disable_gpu: bool,
+    /// Brand color (`#RRGGBB`) used to tint native chrome where supported.
+    pub accent_color: Option<String>,
+    /// Periodically lay out the hidden main window so showing it from the
+    /// tray repaints immediately.
+    pub prewarm_window: bool,
+    /// Which click opens the tray menu; `None` picks per platform.
+    pub tray_menu_trigger: Option<TrayMenuTrigger>,
+    /// Corner the main window is pinned to; `None` leaves it where it is.
+    pub main_window_dock: Option<Dock>,
+    /// Hide the main window's taskbar button (the Dock icon on macOS) for
+    /// tray-only use.
+    pub skip_taskbar: bool,
+    /// Accelerator that toggles the main window from anywhere; `None` is
+    /// `CommandOrControl+Shift+Space` and empty turns it off.
+    pub global_shortcut: Option<String>,
+    /// Accelerator that opens the quick-input window; `None` is
+    /// `CommandOrControl+Alt+Space` and empty turns it off.
+    pub quick_input_shortcut: Option<String>,
+    /// Named window layouts saved with `save_layout`.
+    pub window_layouts: BTreeMap<String, Vec<WindowPlacement>>,
+}
+
+/// Where one window sat when a layout was saved, in physical pixels.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WindowPlacement {
+    pub label: String,
+    pub x: i32,
+    pub y: i32,
+    /// Inner size, without the window decorations.
+    pub width: u32,
+    pub height: u32,
+    /// Name of the monitor it was on, if the platform reports one.
+    pub monitor: Option<String>,
+    pub maximized: bool,
+    pub visible: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Dock {
+    pub corner: Corner,
+    /// Distance from the work area's edges, in logical pixels.
+    pub margin: u32,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CloseBehavior {
+    /// Hide the window and keep running in the tray.
+    #[default]
+    HideToTray,
+    /// Quit the app, confirming first if there is unsaved work.
+    Quit,
+    /// Ask with a native dialog the next time, then remember the answer.
+    Ask,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TrayMenuTrigger {
+    /// Left click opens the menu instead of toggling the window.
+    LeftClick,
+    /// Left click toggles the window; only right click opens the menu.
+    RightClick,
+    Both,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Corner {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    #[default]
+    BottomRight,
+}
+
+/// The settings file of `profile` relative to the config directory. The
+/// default profile keeps the original location.
+fn relative_path(profile: Option<&str>) -> PathBuf {
+    match profile {
+        Some(name) => Path::new(PROFILES_DIR).join(name).join(SETTINGS_FILE),
+        None => PathBuf::from(SETTINGS_FILE),
+    }
+}
+
+/// Read the settings before the app (and its path resolver) exists, for the
+/// few that must take effect before the webview starts. Mirrors Tauri's
+/// `app_config_dir`, which is the platform config dir joined with the identifier.
+pub fn load_early(identifier: &str, profile: Option<&str>) -> Settings {
+    dirs::config_dir()
+        .and_then(|dir| read(&dir.join(identifier).join(relative_path(profile))).ok())
+        .map(|(settings, _)| settings)
+        .unwrap_or_default()
+}
+
+/// Upgrade a settings file to [`SCHEMA_VERSION`] in place, returning the
+/// version it was at. A file from a newer version is left as it is; fields
+/// this version doesn't know are ignored when it is read.
+fn migrate_settings(value: &mut Value) -> Result<u64, String> {
+    let map = value
+        .as_object_mut()
+        .ok_or("settings are not a JSON object")?;
+    let from = match map.get(VERSION_KEY) {
+        None => 1,
+        Some(version) => version
+            .as_u64()
+            .filter(|&v| v >= 1)
+            .ok_or_else(|| format!("invalid {VERSION_KEY} {version}"))?,
+    };
+    if from > SCHEMA_VERSION {
+        return Ok(from);
+    }
+    for step in &MIGRATIONS[from as usize - 1..] {
+        step(map);
+    }
+    map.insert(VERSION_KEY.to_string(), SCHEMA_VERSION.into());
+    Ok(from)
+}
+
+/// Parse a settings file, migrating it first if it is from an older version.
+/// Returns the settings and the version the file was at.
+fn parse(bytes: &[u8]) -> Result<(Settings, u64), String> {
+    let mut value: Value = serde_json::from_slice(bytes).map_err(|e| format!("{e}"))?;
+    let from = migrate_settings(&mut value)?;
+    let settings = serde_json::from_value(value).map_err(|e| format!("{e}"))?;
+    Ok((settings, from))
+}
+
+/// A missing file reads as the defaults; one that exists but can't be read
+/// or migrated is an error.
+fn read(path: &Path) -> Result<(Settings, u64), String> {
+    match fs::read(path) {
+        Ok(bytes) => parse(&bytes),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            Ok((Settings::default(), SCHEMA_VERSION))
+        }
+        Err(e) => Err(format!("{e}")),
+    }
+}
+
+pub struct SettingsState {
+    path: PathBuf,
+    settings: Mutex<Settings>,
+    /// Why the file must not be written over, if it must not: it is from a
+    /// newer version, or unreadable without a backup.
+    read_only: Option<String>,
+}
+
+impl SettingsState {
+    /// Load the settings of `profile`. The second value describes a file
+    /// that couldn't be used as it is, for the caller to tell the user.
+    pub fn load(app: &AppHandle, profile: Option<&str>) -> (Self, Option<String>) {
+        let relative = relative_path(profile);
+        let path = app
+            .path()
+            .app_config_dir()
+            .map(|dir| dir.join(&relative))
+            .unwrap_or(relative);
+        Self::from_file(path)
+    }
+
+    /// An unreadable file is copied to `settings.corrupt.json.bak` before the
+    /// defaults take its place; if it can't be, it is left alone. A file from
+    /// a newer version is used but never written over, so the keys this
+    /// version doesn't know survive.
+    fn from_file(path: PathBuf) -> (Self, Option<String>) {
+        let state = |settings, read_only| Self {
+            path: path.clone(),
+            settings: Mutex::new(settings),
+            read_only,
+        };
+        match read(&path) {
+            Ok((settings, version)) if version > SCHEMA_VERSION => {
+                let reason = format!(
+                    "{} is from a newer version of Companion (schema {version}), so \
+                     changes to it won't be saved",
+                    path.display()
+                );
+                (state(settings, Some(reason.clone())), Some(reason))
+            }
+            Ok((settings, version)) => {
+                let state = state(settings, None);
+                if version < SCHEMA_VERSION {
+                    state.upgrade(version);
+                }
+                (state, None)
+            }
+            Err(e) => {
+                let backup = path.with_extension("corrupt.json.bak");
+                match fs::copy(&path, &backup) {
+                    Ok(_) => {
+                        let problem = format!(
+                            "{} could not be read ({e}). It was kept as {} and the \
+                             default settings are in use.",
+                            path.display(),
+                            backup.display()
+                        );
+                        (state(Settings::default(), None), Some(problem))
+                    }
+                    Err(copy) => {
+                        let reason = format!(
+                            "{} could not be read ({e}) or backed up ({copy}), so it \
+                             won't be written over",
+                            path.display()
+                        );
+                        (
+                            state(Settings::default(), Some(reason.clone())),
+                            Some(reason),
+                        )
+                    }
+                }
+            }
+        }
+    }
+
+    /// Write a migrated file back, keeping the original next to it as
+    /// `settings.v<N>.json.bak` the first time a version is upgraded. Without
+    /// a backup the file is left alone and migrated again next launch.
+    fn upgrade(&self, from: u64) {
+        let backup = self.path.with_extension(format!("v{from}.json.bak"));
+        if !backup.exists() {
+            if let Err(e) = fs::copy(&self.path, &backup) {
+                log::warn!("Could not back up {}: {e}", self.path.display());
+                return;
+            }
+        }
+        match self.write(&self.get()) {
+            Ok(()) => log::info!("Migrated settings from version {from} to {SCHEMA_VERSION}"),
+            Err(e) => log::warn!("Could not write migrated settings: {e}"),
+        }
+    }
+
+    pub fn get(&self) -> Settings {
+        self.settings.lock().unwrap().clone()
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Re-read the file after an external edit. Unlike startup, a missing or
+    /// malformed file is an error and the live settings are left untouched.
+    /// Returns the new settings, or `None` if nothing changed.
+    pub fn reload(&self) -> Result<Option<Settings>, String> {
+        let bytes = fs::read(&self.path).map_err(|e| format!("{}: {e}", self.path.display()))?;
+        let (loaded, _) = parse(&bytes).map_err(|e| format!("{}: {e}", self.path.display()))?;
+        let mut settings = self.settings.lock().unwrap();
+        if *settings == loaded {
+            return Ok(None);
+        }
+        *settings = loaded.clone();
+        Ok(Some(loaded))
+    }
+
+    /// Apply a change and persist it, returning the updated settings.
+    pub fn update(&self, change: impl FnOnce(&mut Settings)) -> Result<Settings, String> {
+        let mut settings = self.settings.lock().unwrap();
+        change(&mut settings);
+        self.write(&settings)?;
+        Ok(settings.clone())
+    }
+
+    fn write(&self, settings: &Settings) -> Result<(), String> {
+        if let Some(reason) = &self.read_only {
+            return Err(reason.clone());
+        }
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir).map_err(|e| format!("{e}"))?;
+        }
+        let mut value = serde_json::to_value(settings).map_err(|e| format!("{e}"))?;
+        if let Value::Object(map) = &mut value {
+            map.insert(VERSION_KEY.to_string(), SCHEMA_VERSION.into());
+        }
+        let json = serde_json::to_vec_pretty(&value).map_err(|e| format!("{e}"))?;
+        let tmp = self.path.with_extension("json.tmp");
+        fs::write(&tmp, json).map_err(|e| format!("{e}"))?;
+        fs::rename(&tmp, &self.path).map_err(|e| format!("{e}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn unversioned_files_migrate_without_losing_values() {
+        let (settings, from) = parse(
+            json!({ "disable_gpu": true, "mini_window_corner": "top-left" })
+                .to_string()
+                .as_bytes(),
+        )
+        .unwrap();
+        assert_eq!(from, 1);
+        assert!(settings.disable_gpu);
+        assert_eq!(settings.mini_window_corner, Corner::TopLeft);
+    }
+
+    #[test]
+    fn migration_stamps_the_current_version() {
+        let mut value = json!({ "skip_taskbar": true });
+        migrate_settings(&mut value).unwrap();
+        assert_eq!(value[VERSION_KEY], SCHEMA_VERSION);
+
+        // Already current, or from a newer version: left alone
+        assert_eq!(migrate_settings(&mut value).unwrap(), SCHEMA_VERSION);
+        let mut newer = json!({ VERSION_KEY: SCHEMA_VERSION + 1 });
+        assert_eq!(migrate_settings(&mut newer).unwrap(), SCHEMA_VERSION + 1);
+        assert_eq!(newer[VERSION_KEY], SCHEMA_VERSION + 1);
+    }
+
+    #[test]
+    fn unmigratable_files_are_errors() {
+        assert!(migrate_settings(&mut json!([])).is_err());
+        assert!(migrate_settings(&mut json!({ VERSION_KEY: 0 })).is_err());
+        assert!(migrate_settings(&mut json!({ VERSION_KEY: "2" })).is_err());
+    }
+
+    /// A fresh directory per test under the system temp dir.
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("companion-settings-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn malformed_files_are_backed_up_and_reported() {
+        let dir = scratch_dir("malformed");
+        let path = dir.join(SETTINGS_FILE);
+        fs::write(&path, b"{ not json").unwrap();
+
+        let (state, problem) = SettingsState::from_file(path.clone());
+        assert!(problem.is_some());
+        assert_eq!(state.get(), Settings::default());
+        assert_eq!(
+            fs::read(dir.join("settings.corrupt.json.bak")).unwrap(),
+            b"{ not json"
+        );
+
+        // With the original kept, changes can be saved again
+        state.update(|s| s.disable_gpu = true).unwrap();
+        assert!(read(&path).unwrap().0.disable_gpu);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn newer_files_are_never_written_over() {
+        let dir = scratch_dir("newer");
+        let path = dir.join(SETTINGS_FILE);
+        let newer = json!({
+            VERSION_KEY: SCHEMA_VERSION + 1,
+            "skip_taskbar": true,
+            "from_the_future": 1,
+        })
+        .to_string();
+        fs::write(&path, &newer).unwrap();
+
+        let (state, problem) = SettingsState::from_file(path.clone());
+        assert!(problem.is_some());
+        assert!(state.get().skip_taskbar);
+        assert!(state.update(|s| s.disable_gpu = true).is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), newer);
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
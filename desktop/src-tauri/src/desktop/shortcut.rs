@@ -0,0 +1,184 @@
+use std::{collections::HashMap, sync::Mutex};
+
+use tauri::{AppHandle, Manager};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutEvent, ShortcutState};
+
+use super::{
+    hide, prewarm, quick_input,
+    settings::{Settings, SettingsState},
+};
+
+/// Summons the main window until the user picks another shortcut.
+pub const DEFAULT_SHORTCUT: &str = "CommandOrControl+Shift+Space";
+/// Opens the quick-input window until the user picks another shortcut.
+pub const DEFAULT_QUICK_INPUT_SHORTCUT: &str = "CommandOrControl+Alt+Space";
+
+/// What a global shortcut does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ShortcutAction {
+    /// Bring the main window forward, or hide it if it already has focus.
+    ToggleMain,
+    /// Open the quick-input window.
+    QuickInput,
+}
+
+impl ShortcutAction {
+    const ALL: [ShortcutAction; 2] = [ShortcutAction::ToggleMain, ShortcutAction::QuickInput];
+
+    fn default_accelerator(self) -> &'static str {
+        match self {
+            ShortcutAction::ToggleMain => DEFAULT_SHORTCUT,
+            ShortcutAction::QuickInput => DEFAULT_QUICK_INPUT_SHORTCUT,
+        }
+    }
+
+    fn saved(self, settings: Settings) -> Option<String> {
+        match self {
+            ShortcutAction::ToggleMain => settings.global_shortcut,
+            ShortcutAction::QuickInput => settings.quick_input_shortcut,
+        }
+    }
+
+    fn describe(self) -> &'static str {
+        match self {
+            ShortcutAction::ToggleMain => "showing the main window",
+            ShortcutAction::QuickInput => "quick input",
+        }
+    }
+}
+
+/// The shortcuts currently registered with the OS, by action.
+#[derive(Default)]
+pub struct RegisteredShortcuts(Mutex<HashMap<ShortcutAction, Shortcut>>);
+
+/// The saved accelerator for `action`, or its default. Empty means turned off.
+pub fn accelerator(app: &AppHandle, action: ShortcutAction) -> String {
+    action
+        .saved(app.state::<SettingsState>().get())
+        .unwrap_or_else(|| action.default_accelerator().to_string())
+}
+
+/// A shortcut without a modifier would take its key away from every other
+/// app, so one is required.
+fn parse(accelerator: &str) -> Result<Shortcut, String> {
+    let shortcut: Shortcut = accelerator
+        .parse()
+        .map_err(|e| format!("Invalid shortcut {accelerator:?}: {e}"))?;
+    if shortcut.mods.is_empty() {
+        return Err(format!(
+            "{accelerator} needs a modifier such as Ctrl, Alt or Cmd"
+        ));
+    }
+    Ok(shortcut)
+}
+
+/// Register `accelerator` in place of the current shortcut for `action`;
+/// empty turns it off. The new one is registered before the old one is
+/// released, so if another app (or the other action) already holds it the
+/// current one stays.
+pub fn apply(app: &AppHandle, action: ShortcutAction, accelerator: &str) -> Result<(), String> {
+    let next = match accelerator {
+        "" => None,
+        accelerator => Some(parse(accelerator)?),
+    };
+    let registered = app.state::<RegisteredShortcuts>();
+    let mut registered = registered.0.lock().unwrap();
+    let current = registered.get(&action).copied();
+    if current == next {
+        return Ok(());
+    }
+    if let Some(next) = next {
+        if let Some((other, _)) = registered.iter().find(|(_, shortcut)| **shortcut == next) {
+            return Err(format!(
+                "{accelerator} is already the shortcut for {}",
+                other.describe()
+            ));
+        }
+    }
+    let shortcuts = app.global_shortcut();
+    if let Some(next) = next {
+        shortcuts.register(next).map_err(|e| {
+            format!("Could not register {accelerator}, another application may be using it: {e}")
+        })?;
+    }
+    if let Some(previous) = current {
+        if let Err(e) = shortcuts.unregister(previous) {
+            log::warn!("Could not release the previous global shortcut: {e}");
+        }
+    }
+    match next {
+        Some(next) => registered.insert(action, next),
+        None => registered.remove(&action),
+    };
+    Ok(())
+}
+
+/// Register every action's saved shortcut, logging the ones that fail.
+pub fn apply_saved(app: &AppHandle) {
+    for action in ShortcutAction::ALL {
+        if let Err(e) = apply(app, action, &accelerator(app, action)) {
+            log::warn!(
+                "Could not register the shortcut for {}: {e}",
+                action.describe()
+            );
+        }
+    }
+}
+
+/// Run the action bound to `shortcut` when it is pressed.
+pub fn on_event(app: &AppHandle, shortcut: &Shortcut, event: ShortcutEvent) {
+    if event.state() != ShortcutState::Pressed {
+        return;
+    }
+    let action = app
+        .state::<RegisteredShortcuts>()
+        .0
+        .lock()
+        .unwrap()
+        .iter()
+        .find(|(_, registered)| *registered == shortcut)
+        .map(|(action, _)| *action);
+    match action {
+        Some(ShortcutAction::ToggleMain) => toggle_main(app),
+        Some(ShortcutAction::QuickInput) => {
+            if let Err(e) = quick_input::show(app) {
+                log::warn!("Could not open the quick-input window: {e}");
+            }
+        }
+        None => {}
+    }
+}
+
+/// Bring the main window forward, or hide it if it already has focus.
+fn toggle_main(app: &AppHandle) {
+    let Some(window) = app.get_webview_window("main") else {
+        return;
+    };
+    if window.is_visible().unwrap_or(false) && window.is_focused().unwrap_or(false) {
+        hide::hide_to_tray(&window.as_ref().window());
+    } else {
+        prewarm::show_then_focus(&window);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shortcuts_need_a_modifier() {
+        assert!(parse(DEFAULT_SHORTCUT).is_ok());
+        assert!(parse(DEFAULT_QUICK_INPUT_SHORTCUT).is_ok());
+        assert!(parse("Alt+F12").is_ok());
+        assert!(parse("Space").is_err());
+        assert!(parse("Ctrl+Nope").is_err());
+    }
+
+    #[test]
+    fn default_shortcuts_differ() {
+        assert_ne!(
+            parse(DEFAULT_SHORTCUT).unwrap(),
+            parse(DEFAULT_QUICK_INPUT_SHORTCUT).unwrap()
+        );
+    }
+}
@@ -0,0 +1,25 @@
+use tauri::{AppHandle, Manager};
+
+/// Hide or show the main window's taskbar button (Windows, Linux) or the
+/// Dock icon (macOS). The window stays reachable from the tray either way.
+///
+/// macOS has no per-window taskbar entry, so this switches the activation
+/// policy instead; nothing else changes the policy, so the two can't
+/// disagree. An accessory app still takes focus when the tray shows its
+/// window.
+pub fn apply(app: &AppHandle, skip: bool) -> tauri::Result<()> {
+    #[cfg(target_os = "macos")]
+    {
+        let policy = if skip {
+            tauri::ActivationPolicy::Accessory
+        } else {
+            tauri::ActivationPolicy::Regular
+        };
+        app.set_activation_policy(policy)?;
+    }
+    #[cfg(not(target_os = "macos"))]
+    if let Some(window) = app.get_webview_window("main") {
+        window.set_skip_taskbar(skip)?;
+    }
+    Ok(())
+}
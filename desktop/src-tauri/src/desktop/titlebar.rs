@@ -0,0 +1,54 @@
+use tauri::{window::Color, WebviewWindow};
+
+/// Tint a window's native chrome: the caption bar on Windows 11 and the
+/// window background behind the webview on macOS. Elsewhere this is a no-op.
+pub fn tint(window: &WebviewWindow, color: Color) {
+    #[cfg(target_os = "macos")]
+    {
+        if let Err(e) = window.set_background_color(Some(color)) {
+            log::warn!("Could not tint {}: {e}", window.label());
+        }
+    }
+    #[cfg(target_os = "windows")]
+    {
+        if let Err(e) = set_caption_color(window, color) {
+            log::warn!("Could not tint {}: {e}", window.label());
+        }
+    }
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    let _ = (window, color);
+}
+
+#[cfg(target_os = "windows")]
+fn set_caption_color(window: &WebviewWindow, color: Color) -> Result<(), String> {
+    use std::ffi::c_void;
+
+    // Windows 11 (build 22000) and later; older versions reject the attribute
+    const DWMWA_CAPTION_COLOR: u32 = 35;
+
+    #[link(name = "dwmapi")]
+    extern "system" {
+        fn DwmSetWindowAttribute(
+            hwnd: *mut c_void,
+            attribute: u32,
+            value: *const c_void,
+            size: u32,
+        ) -> i32;
+    }
+
+    let hwnd = window.hwnd().map_err(|e| format!("{e}"))?;
+    // COLORREF is 0x00BBGGRR
+    let colorref = u32::from(color.0) | (u32::from(color.1) << 8) | (u32::from(color.2) << 16);
+    let result = unsafe {
+        DwmSetWindowAttribute(
+            hwnd.0,
+            DWMWA_CAPTION_COLOR,
+            &colorref as *const u32 as *const c_void,
+            std::mem::size_of::<u32>() as u32,
+        )
+    };
+    if result < 0 {
+        return Err(format!("DwmSetWindowAttribute failed: {result:#x}"));
+    }
+    Ok(())
+}
@@ -0,0 +1,198 @@
+use serde::Deserialize;
+use tauri::{
+    image::Image,
+    menu::{IsMenuItem, Menu, MenuItemBuilder, PredefinedMenuItem, SubmenuBuilder},
+    tray::{MouseButton, MouseButtonState, TrayIcon, TrayIconBuilder, TrayIconEvent},
+    AppHandle, Emitter, Manager, WebviewWindow, Wry,
+};
+
+use super::{
+    badge, hide, mini, prewarm, quick_input, recovery,
+    settings::{SettingsState, TrayMenuTrigger},
+    UiState,
+};
+
+pub const TRAY_ID: &str = "main-tray";
+/// Emitted with the session id when a "Recent Sessions" entry is clicked.
+pub const TRAY_OPEN_SESSION_EVENT: &str = "tray-open-session";
+/// Menu ids of "Recent Sessions" entries are the session id behind this.
+const SESSION_ITEM_PREFIX: &str = "open-session:";
+/// Longest "Recent Sessions" submenu; the frontend's list is cut to it.
+const MAX_RECENT_SESSIONS: usize = 10;
+
+/// A "Recent Sessions" entry, most recent first.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct TrayMenuItem {
+    pub session_id: String,
+    pub label: String,
+}
+
+fn toggle_window(window: &WebviewWindow) {
+    if window.is_visible().unwrap_or(false) {
+        hide::hide_to_tray(&window.as_ref().window());
+    } else {
+        prewarm::show_then_focus(window);
+    }
+}
+
+/// The configured trigger, or the platform's: many Linux trays (those backed
+/// by AppIndicator/StatusNotifierItem) never report clicks, so a right-click-only
+/// menu could not be opened there at all.
+fn menu_trigger(app: &AppHandle) -> TrayMenuTrigger {
+    app.state::<SettingsState>()
+        .get()
+        .tray_menu_trigger
+        .unwrap_or(if cfg!(target_os = "linux") {
+            TrayMenuTrigger::Both
+        } else {
+            TrayMenuTrigger::RightClick
+        })
+}
+
+/// Right click opens the menu wherever the platform supports it, so `LeftClick`
+/// and `Both` only differ in intent.
+fn menu_on_left_click(trigger: TrayMenuTrigger) -> bool {
+    trigger != TrayMenuTrigger::RightClick
+}
+
+/// Apply the menu trigger to the existing tray after the setting changed.
+pub fn apply_menu_trigger(app: &AppHandle) -> tauri::Result<()> {
+    if let Some(tray) = app.tray_by_id(TRAY_ID) {
+        tray.set_show_menu_on_left_click(menu_on_left_click(menu_trigger(app)))?;
+    }
+    Ok(())
+}
+
+/// The tray menu, with a "Recent Sessions" submenu once the frontend has
+/// sent some.
+fn build_tray_menu(app: &AppHandle) -> tauri::Result<Menu<Wry>> {
+    let show_item = MenuItemBuilder::with_id("show", "Show Companion").build(app)?;
+    let quick_input_item = MenuItemBuilder::with_id("quick-input", "Quick Input…").build(app)?;
+    let clickable_item =
+        MenuItemBuilder::with_id("mini-clickable", "Make Mini Window Clickable").build(app)?;
+    let reload_item = MenuItemBuilder::with_id("reload-window", "Reload Window").build(app)?;
+    let quit_item = MenuItemBuilder::with_id("quit-app", "Quit").build(app)?;
+    let separator = PredefinedMenuItem::separator(app)?;
+
+    let recent = app.state::<UiState>().recent_sessions();
+    let mut sessions = SubmenuBuilder::new(app, "Recent Sessions");
+    for item in &recent {
+        sessions = sessions.text(
+            format!("{SESSION_ITEM_PREFIX}{}", item.session_id),
+            &item.label,
+        );
+    }
+    let sessions = sessions.build()?;
+
+    let mut items: Vec<&dyn IsMenuItem<Wry>> = vec![&show_item, &quick_input_item];
+    if !recent.is_empty() {
+        items.push(&sessions);
+    }
+    items.extend([
+        &clickable_item as &dyn IsMenuItem<Wry>,
+        &reload_item,
+        &separator,
+        &quit_item,
+    ]);
+    Menu::with_items(app, &items)
+}
+
+/// Replace the "Recent Sessions" submenu; an empty list removes it. Kept
+/// across tray rebuilds. The menu is only rebuilt when the list changed, as
+/// rebuilding an open menu makes it flicker.
+pub fn set_recent_sessions(app: &AppHandle, mut items: Vec<TrayMenuItem>) -> tauri::Result<()> {
+    items.truncate(MAX_RECENT_SESSIONS);
+    {
+        let ui = app.state::<UiState>();
+        let mut recent = ui.recent_sessions.lock().unwrap();
+        if *recent == items {
+            return Ok(());
+        }
+        *recent = items;
+    }
+    if let Some(tray) = app.tray_by_id(TRAY_ID) {
+        tray.set_menu(Some(build_tray_menu(app)?))?;
+    }
+    Ok(())
+}
+
+/// The app icon marked with the tray state set by the frontend.
+fn state_icon(app: &AppHandle) -> Option<Image<'static>> {
+    let base = app.default_window_icon()?;
+    Some(badge::tray_icon(base, app.state::<UiState>().tray_state()))
+}
+
+/// Redraw the existing tray's icon after the state changed.
+pub fn apply_state(app: &AppHandle) -> tauri::Result<()> {
+    if let Some(tray) = app.tray_by_id(TRAY_ID) {
+        tray.set_icon(state_icon(app))?;
+    }
+    Ok(())
+}
+
+/// Bring up the main window on the session, leaving the frontend to open it.
+fn open_session(app: &AppHandle, session_id: &str) {
+    if let Some(window) = app.get_webview_window("main") {
+        prewarm::show_then_focus(&window);
+    }
+    let _ = app.emit(TRAY_OPEN_SESSION_EVENT, session_id);
+}
+
+/// Build the tray icon with its menu and click handlers, restoring the last
+/// tooltip and recent sessions set by the frontend.
+pub fn build_tray(app: &AppHandle) -> tauri::Result<TrayIcon> {
+    let tray_menu = build_tray_menu(app)?;
+    let tooltip = app.state::<UiState>().tray_tooltip();
+
+    TrayIconBuilder::with_id(TRAY_ID)
+        .icon(state_icon(app).unwrap())
+        .menu(&tray_menu)
+        .show_menu_on_left_click(menu_on_left_click(menu_trigger(app)))
+        .tooltip(tooltip)
+        .on_tray_icon_event(|tray: &tauri::tray::TrayIcon, event| {
+            if let TrayIconEvent::Click {
+                button: MouseButton::Left,
+                button_state: MouseButtonState::Up,
+                ..
+            } = event
+            {
+                let app = tray.app_handle();
+                if let Some(window) = app.get_webview_window("main") {
+                    toggle_window(&window);
+                }
+            }
+        })
+        .on_menu_event(
+            |app: &tauri::AppHandle, event| match event.id().0.as_str() {
+                "show" => {
+                    if let Some(window) = app.get_webview_window("main") {
+                        prewarm::show_then_focus(&window);
+                    }
+                }
+                "quick-input" => {
+                    if let Err(e) = quick_input::show(app) {
+                        log::warn!("Could not open the quick-input window: {e}");
+                    }
+                }
+                "mini-clickable" => {
+                    if let Err(e) = mini::set_click_through(app, false) {
+                        log::warn!("Could not make the mini window clickable: {e}");
+                    }
+                }
+                "reload-window" => {
+                    if let Some(window) = app.get_webview_window("main") {
+                        if let Err(e) = recovery::reload(&window, false) {
+                            log::warn!("Could not reload the main window: {e}");
+                        }
+                    }
+                }
+                "quit-app" => super::quit_confirming_if_dirty(app),
+                id => {
+                    if let Some(session_id) = id.strip_prefix(SESSION_ITEM_PREFIX) {
+                        open_session(app, session_id);
+                    }
+                }
+            },
+        )
+        .build(app)
+}
@@ -0,0 +1,181 @@
+use serde::Serialize;
+
+pub use crate::deep_link::SCHEME;
+
+/// Outcome of claiming the URL scheme.
+#[derive(Debug, Clone, Serialize)]
+pub struct UrlSchemeRegistration {
+    /// The app is now the default handler for the scheme; `None` where that
+    /// can't be checked, i.e. when `managed_by_bundle`.
+    pub registered: Option<bool>,
+    /// Registration comes from the app bundle (macOS `CFBundleURLTypes`) and
+    /// can't be changed at runtime.
+    pub managed_by_bundle: bool,
+}
+
+/// The path links should launch: the AppImage rather than its temporary
+/// mount on Linux, otherwise the executable itself.
+#[cfg(any(target_os = "windows", target_os = "linux"))]
+fn launch_target() -> Result<std::path::PathBuf, String> {
+    #[cfg(target_os = "linux")]
+    if let Some(appimage) = std::env::var_os("APPIMAGE") {
+        return Ok(appimage.into());
+    }
+    std::env::current_exe().map_err(|e| format!("Could not locate the app executable: {e}"))
+}
+
+#[cfg(target_os = "macos")]
+pub fn register() -> Result<UrlSchemeRegistration, String> {
+    // Launch Services picks the scheme up from Info.plist when the bundle is installed
+    Ok(UrlSchemeRegistration {
+        registered: None,
+        managed_by_bundle: true,
+    })
+}
+
+/// Launch Services may prefer another app that declares the same scheme,
+/// which can't be checked without querying it, so this reports unknown.
+#[cfg(target_os = "macos")]
+pub fn is_default() -> Result<Option<bool>, String> {
+    Ok(None)
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use super::{launch_target, SCHEME};
+    use crate::desktop::console_command;
+
+    fn class_key() -> String {
+        format!(r"HKCU\Software\Classes\{SCHEME}")
+    }
+
+    fn open_command() -> Result<String, String> {
+        Ok(format!("\"{}\" \"%1\"", launch_target()?.display()))
+    }
+
+    fn reg_add(key: &str, name: Option<&str>, value: &str) -> Result<(), String> {
+        let mut cmd = console_command("reg");
+        cmd.args(["add", key]);
+        match name {
+            Some(name) => cmd.args(["/v", name]),
+            None => cmd.arg("/ve"),
+        };
+        let output = cmd
+            .args(["/t", "REG_SZ", "/d", value, "/f"])
+            .output()
+            .map_err(|e| format!("Could not run reg.exe: {e}"))?;
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(format!(
+                "Writing {key} failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ))
+        }
+    }
+
+    /// Per-user registration under `HKCU\Software\Classes`, which needs no elevation.
+    pub fn register() -> Result<(), String> {
+        let key = class_key();
+        reg_add(&key, None, "URL:Companion")?;
+        reg_add(&key, Some("URL Protocol"), "")?;
+        reg_add(
+            &format!(r"{key}\shell\open\command"),
+            None,
+            &open_command()?,
+        )
+    }
+
+    pub fn is_default() -> Result<bool, String> {
+        let output = console_command("reg")
+            .args([
+                "query",
+                &format!(r"{}\shell\open\command", class_key()),
+                "/ve",
+            ])
+            .output()
+            .map_err(|e| format!("Could not run reg.exe: {e}"))?;
+        if !output.status.success() {
+            return Ok(false);
+        }
+        let expected = open_command()?;
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .any(|line| line.trim_end().ends_with(&expected)))
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use std::{fs, process::Command};
+
+    use super::{launch_target, SCHEME};
+
+    const DESKTOP_FILE: &str = "com.hexidecibel.companion.url-handler.desktop";
+
+    fn mime_type() -> String {
+        format!("x-scheme-handler/{SCHEME}")
+    }
+
+    fn xdg_mime(args: &[&str]) -> Result<String, String> {
+        let output = Command::new("xdg-mime")
+            .args(args)
+            .output()
+            .map_err(|e| format!("Could not run xdg-mime (is xdg-utils installed?): {e}"))?;
+        if output.status.success() {
+            Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+        } else {
+            Err(format!(
+                "xdg-mime {} failed: {}",
+                args.join(" "),
+                String::from_utf8_lossy(&output.stderr).trim()
+            ))
+        }
+    }
+
+    /// Install a hidden `.desktop` entry that opens the scheme with this
+    /// executable, then make it the default handler via `xdg-mime`.
+    pub fn register() -> Result<(), String> {
+        let dir = dirs::data_dir()
+            .ok_or("Could not locate the XDG data directory")?
+            .join("applications");
+        fs::create_dir_all(&dir).map_err(|e| format!("Could not create {}: {e}", dir.display()))?;
+
+        let exe = launch_target()?;
+        let entry = format!(
+            "[Desktop Entry]\n\
+             Type=Application\n\
+             Name=Companion\n\
+             Exec=\"{}\" %u\n\
+             NoDisplay=true\n\
+             Terminal=false\n\
+             MimeType={};\n",
+            exe.display(),
+            mime_type()
+        );
+        let path = dir.join(DESKTOP_FILE);
+        fs::write(&path, entry).map_err(|e| format!("Could not write {}: {e}", path.display()))?;
+
+        // Refreshing the cache is best effort; xdg-mime works without it
+        let _ = Command::new("update-desktop-database").arg(&dir).status();
+        xdg_mime(&["default", DESKTOP_FILE, &mime_type()]).map(|_| ())
+    }
+
+    pub fn is_default() -> Result<bool, String> {
+        Ok(xdg_mime(&["query", "default", &mime_type()])? == DESKTOP_FILE)
+    }
+}
+
+#[cfg(any(target_os = "windows", target_os = "linux"))]
+pub fn register() -> Result<UrlSchemeRegistration, String> {
+    platform::register()?;
+    Ok(UrlSchemeRegistration {
+        registered: Some(platform::is_default()?),
+        managed_by_bundle: false,
+    })
+}
+
+#[cfg(any(target_os = "windows", target_os = "linux"))]
+pub fn is_default() -> Result<Option<bool>, String> {
+    platform::is_default().map(Some)
+}
@@ -0,0 +1,56 @@
+use std::{fs, sync::Mutex};
+
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use tauri::{AppHandle, Manager};
+
+use super::settings::SettingsState;
+
+/// The active settings-file watcher, if `watch_settings` turned it on.
+#[derive(Default)]
+pub struct SettingsWatcher(Mutex<Option<RecommendedWatcher>>);
+
+/// Watch the settings file and reload it on every change. Our own writes
+/// trigger this too; `reload` ignores a file identical to the live settings.
+pub fn start(app: &AppHandle) -> Result<(), String> {
+    let mut current = app.state::<SettingsWatcher>().0.lock().unwrap();
+    if current.is_some() {
+        return Ok(());
+    }
+
+    let path = app.state::<SettingsState>().path().to_path_buf();
+    // Settings are saved by renaming a temp file over the old one, which
+    // orphans a watch on the file itself, so watch its directory instead
+    let Some(dir) = path.parent() else {
+        return Err(format!("{} has no parent directory", path.display()));
+    };
+    fs::create_dir_all(dir).map_err(|e| format!("{e}"))?;
+
+    let handle = app.clone();
+    let mut watcher = notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+        let event = match res {
+            Ok(event) => event,
+            Err(e) => {
+                log::warn!("Settings watcher error: {e}");
+                return;
+            }
+        };
+        if matches!(event.kind, EventKind::Access(_)) || !event.paths.contains(&path) {
+            return;
+        }
+        // Editors often write in several steps; a half-written file is
+        // rejected and the final write reloads it
+        if let Err(e) = super::reload_settings(handle.clone()) {
+            log::debug!("Settings not reloaded: {e}");
+        }
+    })
+    .map_err(|e| format!("{e}"))?;
+    watcher
+        .watch(dir, RecursiveMode::NonRecursive)
+        .map_err(|e| format!("{e}"))?;
+    *current = Some(watcher);
+    Ok(())
+}
+
+pub fn stop(app: &AppHandle) {
+    app.state::<SettingsWatcher>().0.lock().unwrap().take();
+}
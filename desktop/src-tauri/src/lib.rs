@@ -1,15 +1,50 @@
+mod accent;
+mod build_info;
+#[cfg(desktop)]
+pub mod cli;
+mod deep_link;
 #[cfg(desktop)]
 mod desktop;
+mod updated;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-    let mut builder = tauri::Builder::default()
+    #[cfg(desktop)]
+    start(cli::Cli::default());
+    #[cfg(mobile)]
+    start();
+}
+
+/// Desktop entry point, with the command line parsed by `main`.
+#[cfg(desktop)]
+pub fn run_with_cli(cli: cli::Cli) {
+    start(cli);
+}
+
+fn start(#[cfg(desktop)] cli: cli::Cli) {
+    let context = tauri::generate_context!();
+    #[cfg(desktop)]
+    desktop::apply_early_settings(&context.config().identifier, cli.profile.as_deref());
+
+    let mut builder = tauri::Builder::default();
+    // Registered first, so a second launch hands its arguments (and any deep
+    // link) to this instance and exits before other plugins start
+    #[cfg(desktop)]
+    {
+        builder = builder
+            .plugin(tauri_plugin_single_instance::init(|app, argv, _cwd| {
+                cli::forward(app, argv);
+            }))
+            .manage(cli);
+    }
+    builder = builder
         .plugin(tauri_plugin_notification::init())
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_opener::init())
         // FCM push notifications (no-op on desktop, active on mobile)
         .plugin(tauri_plugin_fcm::init())
-        .plugin(tauri_plugin_store::Builder::default().build());
+        .plugin(tauri_plugin_store::Builder::default().build())
+        .plugin(tauri_plugin_deep_link::init());
 
     // On mobile, intercept external link navigation and open in system browser
     #[cfg(mobile)]
@@ -61,15 +96,86 @@ pub fn run() {
             desktop::set_tray_tooltip,
             desktop::get_autostart_enabled,
             desktop::set_autostart_enabled,
+            desktop::verify_autostart,
+            desktop::get_close_behavior,
+            desktop::set_close_behavior,
+            desktop::get_start_minimized,
+            desktop::set_start_minimized,
+            desktop::get_quit_on_last_window_closed,
+            desktop::set_quit_on_last_window_closed,
+            desktop::get_scale_factor,
+            desktop::rebuild_ui,
+            desktop::get_runtime_environment,
+            desktop::set_unread_count,
+            desktop::open_mini_window,
+            desktop::open_main_window,
+            desktop::get_disable_gpu,
+            desktop::set_disable_gpu,
+            desktop::restart_app,
+            desktop::get_ui_state,
+            desktop::register_url_scheme_handler,
+            desktop::is_default_url_handler,
+            deep_link::take_pending_deep_links,
+            desktop::set_dirty,
+            desktop::confirm,
+            accent::set_accent_color,
+            desktop::prewarm_window,
+            desktop::get_prewarm_window_enabled,
+            desktop::set_prewarm_window_enabled,
+            desktop::reload_settings,
+            desktop::watch_settings,
+            build_info::get_build_info,
+            build_info::get_compiled_features,
+            desktop::set_app_icon_from_base64,
+            desktop::reset_app_icon,
+            desktop::set_window_opacity,
+            desktop::set_window_ignore_cursor_events,
+            desktop::set_tray_menu_trigger,
+            desktop::update_tray_menu,
+            desktop::set_tray_state,
+            desktop::get_global_shortcut,
+            desktop::set_global_shortcut,
+            desktop::get_quick_input_shortcut,
+            desktop::set_quick_input_shortcut,
+            desktop::open_quick_input,
+            desktop::hide_quick_input,
+            desktop::submit_quick_input,
+            desktop::dock_window,
+            desktop::undock_window,
+            desktop::save_layout,
+            desktop::restore_layout,
+            desktop::list_layouts,
+            desktop::delete_layout,
+            desktop::webview_heartbeat,
+            desktop::reload_webview,
+            desktop::set_skip_taskbar,
+            updated::was_just_updated,
+            updated::mark_update_seen,
+        ]);
+    }
+    #[cfg(mobile)]
+    {
+        builder = builder.invoke_handler(tauri::generate_handler![
+            accent::set_accent_color,
+            build_info::get_build_info,
+            build_info::get_compiled_features,
+            deep_link::take_pending_deep_links,
+            updated::was_just_updated,
+            updated::mark_update_seen,
         ]);
     }
 
     builder = builder.setup(|app| {
+        use tauri::Manager;
+
         #[cfg(desktop)]
         desktop::setup_desktop(app)?;
 
-        // Desktop-only setup is handled above
-        let _ = app;
+        app.manage(updated::UpdateState::default());
+        updated::check(app.handle());
+        deep_link::setup(app.handle());
+        #[cfg(desktop)]
+        cli::apply_startup(app.handle());
 
         Ok(())
     });
@@ -82,6 +188,12 @@ pub fn run() {
     }
 
     builder
-        .run(tauri::generate_context!())
-        .expect("error while running Companion");
+        .build(context)
+        .expect("error while building Companion")
+        .run(|_app, event| {
+            #[cfg(desktop)]
+            desktop::on_desktop_run_event(&event);
+            #[cfg(mobile)]
+            let _ = event;
+        });
 }
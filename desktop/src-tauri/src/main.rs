@@ -1,6 +1,9 @@
 // Prevents additional console window on Windows in release, DO NOT REMOVE!!
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+use clap::Parser;
+
 fn main() {
-    companion_lib::run();
+    // Exits here for --help, --version and malformed arguments
+    companion_lib::run_with_cli(companion_lib::cli::Cli::parse());
 }
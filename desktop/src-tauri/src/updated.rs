@@ -0,0 +1,125 @@
+use std::{fs, path::PathBuf, sync::Mutex};
+
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+
+const VERSION_FILE: &str = "last_seen_version.json";
+
+/// Emitted at startup with an [`AppUpdate`] when this is the first launch of
+/// a newer version. Listeners registered later can call `was_just_updated`.
+const APP_UPDATED_EVENT: &str = "app-updated";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AppUpdate {
+    pub from: String,
+    pub to: String,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct LastSeen {
+    version: Option<String>,
+}
+
+/// The update this launch started with, until the frontend acknowledges it
+/// with `mark_update_seen`.
+#[derive(Default)]
+pub struct UpdateState(Mutex<Option<AppUpdate>>);
+
+#[derive(Debug, PartialEq, Eq)]
+enum Launch {
+    /// Nothing recorded yet: a new install, which has no changelog to show.
+    FreshInstall,
+    Updated(AppUpdate),
+    /// Same version, or a downgrade.
+    Unchanged,
+}
+
+fn classify(last_seen: Option<&str>, current: &Version) -> Launch {
+    let Some(last_seen) = last_seen else {
+        return Launch::FreshInstall;
+    };
+    match Version::parse(last_seen) {
+        Ok(previous) if previous >= *current => Launch::Unchanged,
+        // An unparseable record can only have come from an older build
+        _ => Launch::Updated(AppUpdate {
+            from: last_seen.to_string(),
+            to: current.to_string(),
+        }),
+    }
+}
+
+fn path(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = app.path().app_config_dir().map_err(|e| format!("{e}"))?;
+    Ok(dir.join(VERSION_FILE))
+}
+
+fn read_last_seen(app: &AppHandle) -> Option<String> {
+    let bytes = fs::read(path(app).ok()?).ok()?;
+    serde_json::from_slice::<LastSeen>(&bytes).ok()?.version
+}
+
+fn write_last_seen(app: &AppHandle, version: &Version) -> Result<(), String> {
+    let path = path(app)?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| format!("{e}"))?;
+    }
+    let json = serde_json::to_vec_pretty(&LastSeen {
+        version: Some(version.to_string()),
+    })
+    .map_err(|e| format!("{e}"))?;
+    fs::write(path, json).map_err(|e| format!("{e}"))
+}
+
+/// Compare the running version with the last one the frontend acknowledged.
+/// A fresh install records its version right away; an update is held (and
+/// reported again on every launch) until `mark_update_seen`.
+pub fn check(app: &AppHandle) {
+    let current = &app.package_info().version;
+    match classify(read_last_seen(app).as_deref(), current) {
+        Launch::FreshInstall => {
+            if let Err(e) = write_last_seen(app, current) {
+                log::warn!("Could not record the installed version: {e}");
+            }
+        }
+        Launch::Updated(update) => {
+            *app.state::<UpdateState>().0.lock().unwrap() = Some(update.clone());
+            let _ = app.emit(APP_UPDATED_EVENT, update);
+        }
+        Launch::Unchanged => {}
+    }
+}
+
+/// The update this launch follows, if it hasn't been acknowledged yet.
+#[tauri::command]
+pub fn was_just_updated(app: AppHandle) -> Option<AppUpdate> {
+    app.state::<UpdateState>().0.lock().unwrap().clone()
+}
+
+/// Acknowledge the "what's new" for this version so it isn't shown again.
+#[tauri::command]
+pub fn mark_update_seen(app: AppHandle) -> Result<(), String> {
+    write_last_seen(&app, &app.package_info().version)?;
+    app.state::<UpdateState>().0.lock().unwrap().take();
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_install_update_and_same_version() {
+        let current = Version::new(1, 2, 0);
+        assert_eq!(classify(None, &current), Launch::FreshInstall);
+        assert_eq!(
+            classify(Some("1.1.9"), &current),
+            Launch::Updated(AppUpdate {
+                from: "1.1.9".to_string(),
+                to: "1.2.0".to_string(),
+            })
+        );
+        assert_eq!(classify(Some("1.2.0"), &current), Launch::Unchanged);
+        assert_eq!(classify(Some("2.0.0"), &current), Launch::Unchanged);
+    }
+}
@@ -6,6 +6,8 @@ import { StatusPage } from './components/StatusPage';
 import { Dashboard } from './components/Dashboard';
 import { CommandPalette, CommandAction } from './components/CommandPalette';
 import { eventBus } from './utils/eventBus';
+import { isTauri } from './utils/platform';
+import { DeepLink } from './types';
 
 const SettingsScreen = lazy(() => import('./components/SettingsScreen').then(m => ({ default: m.SettingsScreen })));
 const ConciergeView = lazy(() => import('./components/ConciergeView').then(m => ({ default: m.ConciergeView })));
@@ -19,6 +21,8 @@ type Screen =
 export function App() {
   const [screen, setScreen] = useState<Screen>({ name: 'dashboard' });
   const [showCommandPalette, setShowCommandPalette] = useState(false);
+  // Links for the dashboard, held here since it may not be mounted yet
+  const [dashboardLinks, setDashboardLinks] = useState<DeepLink[]>([]);
 
   const isDashboard = screen.name === 'dashboard';
 
@@ -93,6 +97,44 @@ export function App() {
     });
   }, []);
 
+  // companion:// links and command-line prompts: listen first, then collect
+  // the ones that arrived before the webview was ready (e.g. at launch)
+  useEffect(() => {
+    if (!isTauri()) return;
+    const openLink = (link: DeepLink) => {
+      if (link.kind === 'settings' && link.section !== 'notifications') {
+        navigateTo('settings');
+        return;
+      }
+      setDashboardLinks(prev => [...prev, link]);
+      navigateTo('dashboard');
+    };
+    let unlisten: (() => void) | undefined;
+    let cancelled = false;
+    (async () => {
+      try {
+        const { listen } = await import('@tauri-apps/api/event');
+        const { invoke } = await import('@tauri-apps/api/core');
+        const stop = await listen<DeepLink>('deep-link', (event) => openLink(event.payload));
+        if (cancelled) {
+          stop();
+          return;
+        }
+        unlisten = stop;
+        const pending = await invoke<DeepLink[]>('take_pending_deep_links');
+        pending.forEach(openLink);
+      } catch {
+        // Not in Tauri
+      }
+    })();
+    return () => {
+      cancelled = true;
+      unlisten?.();
+    };
+  }, [navigateTo]);
+
+  const clearDashboardLinks = useCallback(() => setDashboardLinks([]), []);
+
   const focusInput = useCallback(() => {
     const textarea = document.querySelector('.input-bar-textarea') as HTMLTextAreaElement | null;
     textarea?.focus();
@@ -155,6 +197,8 @@ export function App() {
             {screen.name === 'dashboard' && (
               <Dashboard
                 onSettings={() => navigateTo('settings')}
+                links={dashboardLinks}
+                onLinksHandled={clearDashboardLinks}
               />
             )}
             {screen.name === 'status' && (
@@ -1,5 +1,5 @@
 import { useState, useCallback, useMemo, useEffect, useRef, lazy, Suspense } from 'react';
-import { ActiveSession, SessionSummary, WorkGroup } from '../types';
+import { ActiveSession, DeepLink, SessionSummary, WorkGroup } from '../types';
 import { useAllServerSummaries } from '../hooks/useAllServerSummaries';
 import { useKeyboardShortcuts } from '../hooks/useKeyboardShortcuts';
 import { useConnections } from '../hooks/useConnections';
@@ -21,11 +21,17 @@ import { SIDEBAR_WIDTH_KEY, SPLIT_RATIO_KEY } from '../services/storageKeys';
 import { eventBus } from '../utils/eventBus';
 import { connectionManager } from '../services/ConnectionManager';
 
+/** How long a `companion --send` prompt waits for a session to be opened. */
+const PROMPT_TIMEOUT_MS = 10_000;
+
 interface DashboardProps {
   onSettings?: () => void;
+  /** Links to open, oldest first; `onLinksHandled` clears them once taken. */
+  links?: DeepLink[];
+  onLinksHandled?: () => void;
 }
 
-export function Dashboard({ onSettings }: DashboardProps) {
+export function Dashboard({ onSettings, links, onLinksHandled }: DashboardProps) {
   const [activeSession, setActiveSession] = useState<ActiveSession | null>(null);
   const [showNotifSettings, setShowNotifSettings] = useState(false);
   const [capabilitiesServerId, setCapabilitiesServerId] = useState<string | null>(null);
@@ -130,19 +136,41 @@ export function Dashboard({ onSettings }: DashboardProps) {
     return () => { unlisten?.(); };
   }, []);
 
-  // Update tray tooltip with waiting session count (desktop only)
+  // Update tray tooltip and icon with waiting session count (desktop only)
   useEffect(() => {
     if (!isTauriDesktop()) return;
     const waitingCount = Array.from(summaries.values()).reduce((count, serverSummary) => {
       return count + serverSummary.sessions.filter(s => s.status === 'waiting').length;
     }, 0);
+    const working = Array.from(summaries.values())
+      .some(serverSummary => serverSummary.sessions.some(s => s.status === 'working'));
     const tooltip = waitingCount > 0
       ? `Companion - ${waitingCount} session${waitingCount > 1 ? 's' : ''} waiting`
       : 'Companion';
+    const state = waitingCount > 0 ? { unread: waitingCount } : working ? 'busy' : 'idle';
     (async () => {
       try {
         const { invoke } = await import('@tauri-apps/api/core');
         await invoke('set_tray_tooltip', { tooltip });
+        await invoke('set_tray_state', { state });
+      } catch {
+        // Not in Tauri
+      }
+    })();
+  }, [summaries]);
+
+  // Offer the most recently active sessions in the tray menu (desktop only)
+  useEffect(() => {
+    if (!isTauriDesktop()) return;
+    const recent = Array.from(summaries.values())
+      .flatMap(serverSummary => serverSummary.sessions)
+      .sort((a, b) => b.lastActivity - a.lastActivity)
+      .slice(0, 5)
+      .map(s => ({ session_id: s.id, label: s.friendlyName || s.name }));
+    (async () => {
+      try {
+        const { invoke } = await import('@tauri-apps/api/core');
+        await invoke('update_tray_menu', { items: recent });
       } catch {
         // Not in Tauri
       }
@@ -237,6 +265,104 @@ export function Dashboard({ onSettings }: DashboardProps) {
     }
   }, [isMobile]);
 
+  // Take the links App held for us; a linked session opens once it has loaded
+  const [linkedSessionId, setLinkedSessionId] = useState<string | null>(null);
+  const [pendingPrompts, setPendingPrompts] = useState<{ text: string; timer: ReturnType<typeof setTimeout> }[]>([]);
+  useEffect(() => {
+    if (!links || links.length === 0) return;
+    for (const link of links) {
+      if (link.kind === 'session') {
+        setLinkedSessionId(link.sessionId);
+      } else if (link.kind === 'send-prompt') {
+        // Don't hold it for whatever session happens to be opened much later
+        const timer = setTimeout(() => {
+          setPendingPrompts(prev => prev.filter(p => p.timer !== timer));
+          eventBus.emit('companion-in-app-notification', {
+            title: 'Prompt not sent',
+            body: 'No session was open to send it to.',
+          });
+        }, PROMPT_TIMEOUT_MS);
+        setPendingPrompts(prev => [...prev, { text: link.prompt, timer }]);
+      } else {
+        setShowNotifSettings(true);
+      }
+    }
+    onLinksHandled?.();
+  }, [links, onLinksHandled]);
+  useEffect(() => {
+    if (!linkedSessionId) return;
+    const target = flatSessions.find(s => s.sessionId === linkedSessionId);
+    if (!target) return;
+    handleSelectSession(target.serverId, target.sessionId);
+    setLinkedSessionId(null);
+  }, [linkedSessionId, flatSessions, handleSelectSession]);
+
+  // Send `companion --send` prompts to the open session, once there is one
+  useEffect(() => {
+    if (!activeSession || pendingPrompts.length === 0) return;
+    for (const { text, timer } of pendingPrompts) {
+      clearTimeout(timer);
+      eventBus.emit('quick-input', { ...activeSession, text });
+    }
+    setPendingPrompts([]);
+  }, [activeSession, pendingPrompts]);
+
+  // Open the session picked from the tray's "Recent Sessions" submenu
+  useEffect(() => {
+    if (!isTauriDesktop()) return;
+    let unlisten: (() => void) | undefined;
+    let cancelled = false;
+    (async () => {
+      try {
+        const { listen } = await import('@tauri-apps/api/event');
+        const stop = await listen<string>('tray-open-session', (event) => {
+          const target = flatSessions.find(s => s.sessionId === event.payload);
+          if (target) handleSelectSession(target.serverId, target.sessionId);
+        });
+        if (cancelled) stop();
+        else unlisten = stop;
+      } catch {
+        // Not in Tauri
+      }
+    })();
+    return () => {
+      cancelled = true;
+      unlisten?.();
+    };
+  }, [flatSessions, handleSelectSession]);
+
+  // Send prompts from the quick-input window to the open session
+  useEffect(() => {
+    if (!isTauriDesktop()) return;
+    let unlisten: (() => void) | undefined;
+    let cancelled = false;
+    (async () => {
+      try {
+        const { listen } = await import('@tauri-apps/api/event');
+        const stop = await listen<string>('quick-input-submitted', async (event) => {
+          if (activeSession) {
+            eventBus.emit('quick-input', { ...activeSession, text: event.payload });
+            return;
+          }
+          eventBus.emit('companion-in-app-notification', {
+            title: 'Quick input not sent',
+            body: 'Open a session first, then try again.',
+          });
+          const { invoke } = await import('@tauri-apps/api/core');
+          await invoke('open_main_window');
+        });
+        if (cancelled) stop();
+        else unlisten = stop;
+      } catch {
+        // Not in Tauri
+      }
+    })();
+    return () => {
+      cancelled = true;
+      unlisten?.();
+    };
+  }, [activeSession]);
+
   // Ensure the base history entry has the 'base' flag on mobile so the popstate
   // handler recognizes it as the floor. App.tsx sets { screen, base } on mount;
   // this is a fallback in case it was overwritten.
@@ -356,11 +482,9 @@ export function Dashboard({ onSettings }: DashboardProps) {
     }
   }, [activeSession, summaries, flatSessions]);
 
-  // Listen for command palette event to open notification settings
+  // Open notification settings from the command palette
   useEffect(() => {
-    const handler = () => setShowNotifSettings(true);
-    window.addEventListener('open-notification-settings', handler);
-    return () => window.removeEventListener('open-notification-settings', handler);
+    return eventBus.on('open-notification-settings', () => setShowNotifSettings(true));
   }, []);
 
   // Open (or spawn) the concierge session hosted on the given server. The daemon
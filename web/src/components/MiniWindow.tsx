@@ -0,0 +1,35 @@
+import { useCallback } from 'react';
+
+/**
+ * Compact always-on-top window opened by the desktop `open_mini_window`
+ * command. Rendered instead of the full app when loaded at `/mini`.
+ */
+export function MiniWindow() {
+  const openMain = useCallback(async () => {
+    try {
+      const { invoke } = await import('@tauri-apps/api/core');
+      await invoke('open_main_window');
+    } catch {
+      // Not in Tauri
+    }
+  }, []);
+
+  const close = useCallback(async () => {
+    try {
+      const { invoke } = await import('@tauri-apps/api/core');
+      await invoke('open_mini_window');
+    } catch {
+      // Not in Tauri
+    }
+  }, []);
+
+  return (
+    <div className="mini-window" data-tauri-drag-region>
+      <span className="mini-window-title" data-tauri-drag-region>Companion</span>
+      <div className="mini-window-actions">
+        <button className="mini-window-button" onClick={openMain}>Open Companion</button>
+        <button className="mini-window-button" onClick={close} aria-label="Close">×</button>
+      </div>
+    </div>
+  );
+}
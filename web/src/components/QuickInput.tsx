@@ -0,0 +1,59 @@
+import { useCallback, useEffect, useRef, useState } from 'react';
+
+/**
+ * Single-line prompt box in the desktop quick-input window, summoned by a
+ * global shortcut or the tray. Rendered instead of the full app when loaded
+ * at `/quick-input`; Enter hands the prompt to the main window, Escape
+ * dismisses it.
+ */
+export function QuickInput() {
+  const [text, setText] = useState('');
+  const inputRef = useRef<HTMLInputElement>(null);
+
+  // The window is hidden and reshown rather than recreated, so start fresh
+  // every time it comes back
+  useEffect(() => {
+    const onFocus = () => {
+      setText('');
+      inputRef.current?.focus();
+    };
+    window.addEventListener('focus', onFocus);
+    inputRef.current?.focus();
+    return () => window.removeEventListener('focus', onFocus);
+  }, []);
+
+  const run = useCallback(async (command: string, args?: Record<string, unknown>) => {
+    try {
+      const { invoke } = await import('@tauri-apps/api/core');
+      await invoke(command, args);
+    } catch {
+      // Not in Tauri
+    }
+  }, []);
+
+  const handleKeyDown = useCallback((e: React.KeyboardEvent<HTMLInputElement>) => {
+    if (e.key === 'Enter' && !e.nativeEvent.isComposing) {
+      e.preventDefault();
+      run('submit_quick_input', { prompt: text });
+      setText('');
+    } else if (e.key === 'Escape') {
+      e.preventDefault();
+      run('hide_quick_input');
+    }
+  }, [run, text]);
+
+  return (
+    <div className="quick-input" data-tauri-drag-region>
+      <input
+        ref={inputRef}
+        className="quick-input-field"
+        value={text}
+        onChange={(e) => setText(e.target.value)}
+        onKeyDown={handleKeyDown}
+        placeholder="Send to the current session…"
+        aria-label="Quick input"
+        autoFocus
+      />
+    </div>
+  );
+}
@@ -341,6 +341,15 @@ export function SessionView({
     [sendInput],
   );
 
+  // Prompts typed into the desktop quick-input window
+  useEffect(() => {
+    return eventBus.on('quick-input', (input) => {
+      if (input.serverId === serverId && input.sessionId === sessionId) {
+        sendInput(input.text);
+      }
+    });
+  }, [serverId, sessionId, sendInput]);
+
   const handleSendWithAttachments = useCallback(
     async (text: string, attachments: PendingAttachment[]): Promise<boolean> => {
       if (!serverId) return false;
@@ -51,8 +51,10 @@ export function SettingsScreen({ onBack }: SettingsScreenProps) {
       const newValue = !autostart;
       await invoke('set_autostart_enabled', { enabled: newValue });
       setAutostart(newValue);
-    } catch {
-      // Failed
+    } catch (err) {
+      // Snap the toggle back to the state the OS actually reports
+      const enabled = (err as { enabled?: boolean | null })?.enabled;
+      if (typeof enabled === 'boolean') setAutostart(enabled);
     }
   };
 
@@ -1,15 +1,18 @@
 import React from 'react';
 import ReactDOM from 'react-dom/client';
 import { App } from './App';
+import { MiniWindow } from './components/MiniWindow';
+import { QuickInput } from './components/QuickInput';
 import { applyFontScale } from './services/storage';
 import { initStorage } from './services/persistentStorage';
-import { applySafeAreaInsets, initKeyboardHeightListener, installExternalLinkHandler } from './utils/platform';
+import { applySafeAreaInsets, initKeyboardHeightListener, installExternalLinkHandler, startWebviewHeartbeat } from './utils/platform';
 import './styles/variables.css';
 import './styles/global.css';
 
 applySafeAreaInsets();
 initKeyboardHeightListener();
 installExternalLinkHandler();
+startWebviewHeartbeat();
 
 // Initialize persistent storage (restores Tauri store to localStorage),
 // then apply settings and render.
@@ -17,7 +20,9 @@ initStorage().then(() => {
   applyFontScale();
   ReactDOM.createRoot(document.getElementById('root')!).render(
     <React.StrictMode>
-      <App />
+      {window.location.pathname === '/mini' ? <MiniWindow />
+        : window.location.pathname === '/quick-input' ? <QuickInput />
+        : <App />}
     </React.StrictMode>,
   );
 });
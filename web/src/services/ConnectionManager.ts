@@ -60,6 +60,8 @@ export class ConnectionManager {
         .then(({ listen }) => {
           listen('tauri://resume', () => this.kickReconnects()).catch(() => {});
           listen('tauri://app-resume', () => this.kickReconnects()).catch(() => {});
+          // Emitted by tauri-plugin-fcm from native onResume (mobile) / main-window focus (desktop)
+          listen('app-resumed', () => this.kickReconnects()).catch(() => {});
         })
         .catch(() => {});
     } catch {
@@ -11,7 +11,7 @@
  *   register_push { deviceId, fcmToken, tokenType }
  */
 
-import { isTauriMobile } from '../utils/platform';
+import { isTauri, isTauriMobile } from '../utils/platform';
 import { connectionManager } from './ConnectionManager';
 import { DEVICE_ID_KEY } from './storageKeys';
 
@@ -38,13 +38,74 @@ export function isRemotePushSupported(): boolean {
   return isTauriMobile();
 }
 
-/** Request notification permission via the FCM plugin (mobile only). */
-export async function requestPermission(): Promise<boolean> {
+export type PermissionStatus = 'granted' | 'requested' | 'blocked';
+
+export type FcmErrorCode =
+  | 'NOT_AVAILABLE'
+  | 'PERMISSION_DENIED'
+  | 'TOKEN_ERROR'
+  | 'PLUGIN_INVOKE'
+  | 'INVALID_TOPIC'
+  | 'STORAGE'
+  | 'INVALID_ENDPOINT'
+  | 'INVALID_HEADER'
+  | 'INVALID_ROUTE'
+  | 'INVALID_COLOR'
+  | 'INVALID_ICON'
+  | 'INVALID_BATCH_WINDOW'
+  | 'INVALID_METRIC'
+  | 'INVALID_VIBRATION_PATTERN'
+  | 'INVALID_PROGRESS'
+  | 'UNKNOWN_PROGRESS_NOTIFICATION'
+  | 'INVALID_GROUP_KEY_FIELD'
+  | 'INVALID_CHANNEL'
+  | 'CACHE_KEY_UNAVAILABLE'
+  | 'PUSH_CONNECTION'
+  | 'INVALID_QUIET_HOURS'
+  | 'PUSH_KEY_UNAVAILABLE'
+  | 'SCHEDULE_LIMIT_REACHED';
+
+/** What `plugin:fcm|*` commands reject with. Match on `code`; `message` is for logs. */
+export interface FcmError {
+  code: FcmErrorCode;
+  message: string;
+}
+
+/**
+ * Request notification permission via the FCM plugin (mobile only).
+ * 'blocked' means the system will no longer prompt; point the user to settings.
+ */
+export async function requestPermission(): Promise<PermissionStatus> {
+  if (!isTauriMobile()) return 'blocked';
+  try {
+    const { invoke } = await import('@tauri-apps/api/core');
+    return await invoke<PermissionStatus>('plugin:fcm|request_notification_permission');
+  } catch {
+    return 'blocked';
+  }
+}
+
+/** Whether requesting permission would show the system prompt (mobile only). */
+export async function canRequestPermission(): Promise<boolean> {
+  if (!isTauriMobile()) return false;
+  try {
+    const { invoke } = await import('@tauri-apps/api/core');
+    return await invoke<boolean>('plugin:fcm|can_request_notification_permission');
+  } catch {
+    return false;
+  }
+}
+
+/**
+ * Open the system notification settings for this app (mobile only), for when
+ * the permission is 'blocked'. Resolves false if the page couldn't be opened.
+ */
+export async function openNotificationSettings(): Promise<boolean> {
   if (!isTauriMobile()) return false;
   try {
     const { invoke } = await import('@tauri-apps/api/core');
-    const granted = await invoke<boolean>('plugin:fcm|request_notification_permission');
-    return granted;
+    await invoke('plugin:fcm|open_notification_settings');
+    return true;
   } catch {
     return false;
   }
@@ -76,6 +137,34 @@ export async function getToken(): Promise<string | null> {
   }
 }
 
+/**
+ * The device's X25519 public key (base64) that servers seal notification
+ * content to, or null in a browser. Desktop opens sealed pushes too.
+ */
+export async function getPushPublicKey(): Promise<string | null> {
+  if (!isTauri()) return null;
+  try {
+    const { invoke } = await import('@tauri-apps/api/core');
+    return await invoke<string>('plugin:fcm|get_push_public_key');
+  } catch {
+    return null;
+  }
+}
+
+/**
+ * Sound names a notification can set in its `sound` data field: raw
+ * resources on Android, the OS's named sounds on desktop. Empty in a browser.
+ */
+export async function getAvailableSounds(): Promise<string[]> {
+  if (!isTauri()) return [];
+  try {
+    const { invoke } = await import('@tauri-apps/api/core');
+    return await invoke<string[]>('plugin:fcm|get_available_sounds');
+  } catch {
+    return [];
+  }
+}
+
 /**
  * Register the push token with a specific daemon server.
  * Sends the FCM token via the existing register_push WebSocket protocol.
@@ -94,6 +183,7 @@ export async function registerWithServer(serverId: string): Promise<boolean> {
       deviceId: getDeviceId(),
       fcmToken: pushToken,
       tokenType: 'fcm',
+      publicKey: (await getPushPublicKey()) ?? undefined,
     });
     return response.success;
   } catch {
@@ -144,10 +234,15 @@ export async function startTokenRefreshListener(): Promise<void> {
 
   try {
     const { listen } = await import('@tauri-apps/api/event');
-    tokenRefreshUnlisten = await listen<{ token: string }>('plugin:fcm://tokenRefresh', (event) => {
+    // Debounced on the Rust side, so bursts of native refreshes arrive once
+    tokenRefreshUnlisten = await listen<{ token: string }>('fcm://token-refreshed', (event) => {
       pushToken = event.payload.token;
       registerWithAllServers();
     });
+    // Hook up the native listeners and pick up a refresh that settled
+    // before the listener was attached
+    const { invoke } = await import('@tauri-apps/api/core');
+    await invoke('plugin:fcm|init_fcm_listeners', { stickyEvents: ['fcm://token-refreshed'] });
   } catch {
     // Plugin events not available
   }
@@ -170,8 +265,9 @@ export function stopTokenRefreshListener(): void {
 export async function initPush(): Promise<void> {
   if (!isRemotePushSupported()) return;
 
-  const granted = await requestPermission();
-  if (!granted) {
+  const status = await requestPermission();
+  if (status === 'blocked') return;
+  if (status !== 'granted') {
     // Check again — requestPermission might have triggered the system dialog
     const actuallyGranted = await isPermissionGranted();
     if (!actuallyGranted) return;
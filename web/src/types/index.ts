@@ -306,6 +306,12 @@ export interface ActiveSession {
   sessionId: string;
 }
 
+/** A `companion://` link or `--send` prompt, as routed by the native side. */
+export type DeepLink =
+  | { kind: 'session'; sessionId: string }
+  | { kind: 'settings'; section: string | null }
+  | { kind: 'send-prompt'; prompt: string };
+
 export interface Skill {
   id: string;
   name: string;
@@ -4,6 +4,7 @@ type EventMap = {
   'open-notification-settings': void;
   'open-new-project': void;
   'open-concierge': { serverId: string; sessionId: string };
+  'quick-input': { serverId: string; sessionId: string; text: string };
   'companion-in-app-notification': { title: string; body?: string; tag?: string };
 };
 
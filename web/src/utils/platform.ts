@@ -190,3 +190,24 @@ export function installExternalLinkHandler(): void {
     window.location.href = href;
   }, true);
 }
+
+/**
+ * Tell the desktop shell this page is alive every few seconds. A visible
+ * window that goes quiet is assumed to have lost its renderer (a blank
+ * window) and is reloaded by the shell. A hidden page's timers are
+ * throttled, so it says so and the shell waits longer.
+ */
+export function startWebviewHeartbeat(): void {
+  if (!isTauriDesktop()) return;
+  const beat = async () => {
+    try {
+      const { invoke } = await import('@tauri-apps/api/core');
+      await invoke('webview_heartbeat', { hidden: document.visibilityState === 'hidden' });
+    } catch {
+      // Not in Tauri
+    }
+  };
+  beat();
+  setInterval(beat, 5000);
+  document.addEventListener('visibilitychange', beat);
+}